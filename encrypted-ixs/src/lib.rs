@@ -17,136 +17,475 @@ mod circuits {
     }
 
     // === New encrypted instructions ===
-    pub struct RelayOfferInput {
-        external_seller_identity_hash: u64,
+
+    /// Seller identity commitment re-encrypted to the MXE and stored on the offer PDA.
+    /// Identities are full keccak256 hashes as four u64 limbs, little-endian limb order.
+    pub struct SellerIdentity {
+        identity_hash: [u64; 4],
+    }
+
+    /// Limb-wise equality, every limb is compared so nothing leaks about where a mismatch is
+    fn identity_eq(a: [u64; 4], b: [u64; 4]) -> bool {
+        let mut equal = true;
+        for i in 0..4 {
+            equal = equal & (a[i] == b[i]);
+        }
+        equal
     }
 
-    pub struct RelayOfferOutput {
-        ack: u64,
+    pub struct RelayOfferInput {
+        external_seller_identity_hash: [u64; 4],
     }
 
     #[instruction]
     pub fn relay_offer_clone(
         input_ctxt: Enc<Shared, RelayOfferInput>,
-    ) -> Enc<Shared, RelayOfferOutput> {
+    ) -> Enc<Mxe, SellerIdentity> {
         let input = input_ctxt.to_arcis();
-        let ack = input.external_seller_identity_hash;
-        input_ctxt
-            .owner
-            .from_arcis(RelayOfferOutput { ack })
+        Mxe::get().from_arcis(SellerIdentity {
+            identity_hash: input.external_seller_identity_hash,
+        })
+    }
+
+    /// Shielded balance of one mint, readable only inside MPC and by its owner
+    pub struct Balance {
+        amount: u64,
     }
 
-    pub struct DepositInput {
+    /// Credits a deposit to the encrypted balance, a new account starts from zero.
+    /// Returns the balance for the MXE and a copy for the owner
+    #[instruction]
+    pub fn confidential_deposit_native(
+        owner: Shared,
+        balance_ctxt: Enc<Mxe, Balance>,
+        is_new: bool,
         amount: u64,
+    ) -> (Enc<Mxe, Balance>, Enc<Shared, Balance>) {
+        let balance = balance_ctxt.to_arcis();
+        let current = if is_new { 0 } else { balance.amount };
+        let updated = current + amount;
+        (
+            Mxe::get().from_arcis(Balance { amount: updated }),
+            owner.from_arcis(Balance { amount: updated }),
+        )
     }
 
-    pub struct DepositOutput {
-        processed_amount: u64,
+    /// Debits the balance only if it covers `amount`, revealing just whether it did
+    #[instruction]
+    pub fn confidential_withdraw_native(
+        owner: Shared,
+        balance_ctxt: Enc<Mxe, Balance>,
+        amount: u64,
+    ) -> (bool, Enc<Mxe, Balance>, Enc<Shared, Balance>) {
+        let balance = balance_ctxt.to_arcis();
+        let sufficient = balance.amount >= amount;
+        let updated = if sufficient {
+            balance.amount - amount
+        } else {
+            balance.amount
+        };
+        (
+            sufficient.reveal(),
+            Mxe::get().from_arcis(Balance { amount: updated }),
+            owner.from_arcis(Balance { amount: updated }),
+        )
     }
 
+    /// Credits a token deposit to the encrypted balance, a new account starts from zero.
+    /// Returns the balance for the MXE and a copy for the owner
     #[instruction]
-    pub fn confidential_deposit_native(
-        input_ctxt: Enc<Shared, DepositInput>,
-    ) -> Enc<Shared, DepositOutput> {
-        let input = input_ctxt.to_arcis();
-        let processed_amount = input.amount;
-        input_ctxt
-            .owner
-            .from_arcis(DepositOutput { processed_amount })
+    pub fn confidential_deposit_spl(
+        owner: Shared,
+        balance_ctxt: Enc<Mxe, Balance>,
+        is_new: bool,
+        amount: u64,
+    ) -> (Enc<Mxe, Balance>, Enc<Shared, Balance>) {
+        let balance = balance_ctxt.to_arcis();
+        let current = if is_new { 0 } else { balance.amount };
+        let updated = current + amount;
+        (
+            Mxe::get().from_arcis(Balance { amount: updated }),
+            owner.from_arcis(Balance { amount: updated }),
+        )
     }
 
-    pub struct DepositSplInput {
-        seller_identity_hash: u64,
+    /// Debits the token balance only if it covers `amount`, revealing just whether it did
+    #[instruction]
+    pub fn confidential_withdraw_spl(
+        owner: Shared,
+        balance_ctxt: Enc<Mxe, Balance>,
+        amount: u64,
+    ) -> (bool, Enc<Mxe, Balance>, Enc<Shared, Balance>) {
+        let balance = balance_ctxt.to_arcis();
+        let sufficient = balance.amount >= amount;
+        let updated = if sufficient {
+            balance.amount - amount
+        } else {
+            balance.amount
+        };
+        (
+            sufficient.reveal(),
+            Mxe::get().from_arcis(Balance { amount: updated }),
+            owner.from_arcis(Balance { amount: updated }),
+        )
     }
 
-    pub struct DepositSplOutput {
-        ack: u64,
+    pub struct DepositSplInput {
+        seller_identity_hash: [u64; 4],
     }
 
     #[instruction]
     pub fn interchain_origin_evm_deposit_seller_spl(
         input_ctxt: Enc<Shared, DepositSplInput>,
-    ) -> Enc<Shared, DepositSplOutput> {
+    ) -> Enc<Mxe, SellerIdentity> {
         let input = input_ctxt.to_arcis();
-        let ack = input.seller_identity_hash;
-        input_ctxt
-            .owner
-            .from_arcis(DepositSplOutput { ack })
+        Mxe::get().from_arcis(SellerIdentity {
+            identity_hash: input.seller_identity_hash,
+        })
     }
 
     pub struct DepositSellerNativeInput {
-        seller_identity_hash: u64,
-    }
-
-    pub struct DepositSellerNativeOutput {
-        ack: u64,
+        seller_identity_hash: [u64; 4],
     }
 
     #[instruction]
     pub fn deposit_seller_native(
         input_ctxt: Enc<Shared, DepositSellerNativeInput>,
-    ) -> Enc<Shared, DepositSellerNativeOutput> {
+    ) -> Enc<Mxe, SellerIdentity> {
         let input = input_ctxt.to_arcis();
-        let ack = input.seller_identity_hash;
-        input_ctxt
-            .owner
-            .from_arcis(DepositSellerNativeOutput { ack })
+        Mxe::get().from_arcis(SellerIdentity {
+            identity_hash: input.seller_identity_hash,
+        })
     }
 
     pub struct DepositSellerSPLInput {
-        seller_identity_hash: u64,
-    }
-
-    pub struct DepositSellerSPLOutput {
-        ack: u64,
+        seller_identity_hash: [u64; 4],
     }
 
     #[instruction]
     pub fn deposit_seller_spl(
         input_ctxt: Enc<Shared, DepositSellerSPLInput>,
-    ) -> Enc<Shared, DepositSellerSPLOutput> {
+    ) -> Enc<Mxe, SellerIdentity> {
         let input = input_ctxt.to_arcis();
-        let ack = input.seller_identity_hash;
-        input_ctxt
-            .owner
-            .from_arcis(DepositSellerSPLOutput { ack })
+        Mxe::get().from_arcis(SellerIdentity {
+            identity_hash: input.seller_identity_hash,
+        })
     }
 
-    pub struct FinalizeInterchainInput {
-        buyer_identity_hash: u64,
+    /// Reveals only whether the EVM address the buyer proved on-chain matches the stored
+    /// seller commitment. Interchain commitments are keccak256 of the taker's EVM address
+    /// left-padded to 256 bits, the proven address arrives as that commitment, never raw
+    #[instruction]
+    pub fn finalize_interchain_origin_evm_offer(
+        seller_ctxt: Enc<Mxe, SellerIdentity>,
+        commitment_limb_0: u64,
+        commitment_limb_1: u64,
+        commitment_limb_2: u64,
+        commitment_limb_3: u64,
+    ) -> bool {
+        let seller = seller_ctxt.to_arcis();
+        let proven = [commitment_limb_0, commitment_limb_1, commitment_limb_2, commitment_limb_3];
+        identity_eq(proven, seller.identity_hash).reveal()
     }
 
-    pub struct FinalizeInterchainOutput {
-        ack: u64,
+    /// Identity commitment the buyer was handed by the seller off-chain
+    pub struct FinalizeIntrachainInput {
+        buyer_identity_hash: [u64; 4],
     }
 
+    /// Reveals only whether the buyer's claim matches the stored seller commitment
     #[instruction]
-    pub fn finalize_interchain_origin_evm_offer(
-        input_ctxt: Enc<Shared, FinalizeInterchainInput>,
-    ) -> Enc<Shared, FinalizeInterchainOutput> {
+    pub fn finalize_intrachain_offer(
+        input_ctxt: Enc<Shared, FinalizeIntrachainInput>,
+        seller_ctxt: Enc<Mxe, SellerIdentity>,
+    ) -> bool {
         let input = input_ctxt.to_arcis();
-        let ack = input.buyer_identity_hash;
-        input_ctxt
-            .owner
-            .from_arcis(FinalizeInterchainOutput { ack })
+        let seller = seller_ctxt.to_arcis();
+        identity_eq(input.buyer_identity_hash, seller.identity_hash).reveal()
     }
 
-    pub struct FinalizeIntrachainInput {
-        buyer_identity_hash: u64,
-    }
+    // === Confidential offers ===
 
-    pub struct FinalizeIntrachainOutput {
-        ack: u64,
+    /// Offer amounts, kept encrypted to the MXE on the offer account
+    pub struct OfferTerms {
+        token_a_amount: u64,
+        token_b_amount: u64,
     }
 
+    /// Re-encrypts the maker's ask to the MXE so only later computations can read it
     #[instruction]
-    pub fn finalize_intrachain_offer(
-        input_ctxt: Enc<Shared, FinalizeIntrachainInput>,
-    ) -> Enc<Shared, FinalizeIntrachainOutput> {
+    pub fn create_confidential_offer(input_ctxt: Enc<Shared, OfferTerms>) -> Enc<Mxe, OfferTerms> {
         let input = input_ctxt.to_arcis();
-        let ack = input.buyer_identity_hash;
-        input_ctxt
-            .owner
-            .from_arcis(FinalizeIntrachainOutput { ack })
+        Mxe::get().from_arcis(input)
+    }
+
+    /// Taker's bid, the token A amount to take and the most token B they will pay for it
+    pub struct Bid {
+        token_a_amount: u64,
+        max_token_b_amount: u64,
+    }
+
+    /// Amounts each side settles at, zero when the bid did not match
+    pub struct Settlement {
+        token_a_amount: u64,
+        token_b_amount: u64,
+    }
+
+    /// Reveals only whether the bid crosses the ask and both sides' balances cover it, and
+    /// whether that used the ask up. A bid may take any part of the remaining ask, priced
+    /// pro rata at the maker's rate and rounded up in the maker's favour. On a match the
+    /// slice changes hands inside the encrypted balances. The settlement goes out encrypted
+    /// once to the taker and once to the maker, the four balances and what is left of the
+    /// ask back to the MXE
+    #[instruction]
+    pub fn match_confidential_offer(
+        bid_ctxt: Enc<Shared, Bid>,
+        ask_ctxt: Enc<Mxe, OfferTerms>,
+        maker: Shared,
+        maker_a_ctxt: Enc<Mxe, Balance>,
+        maker_b_ctxt: Enc<Mxe, Balance>,
+        maker_b_is_new: bool,
+        taker_a_ctxt: Enc<Mxe, Balance>,
+        taker_a_is_new: bool,
+        taker_b_ctxt: Enc<Mxe, Balance>,
+    ) -> (
+        bool,
+        Enc<Shared, Settlement>,
+        Enc<Shared, Settlement>,
+        Enc<Mxe, Balance>,
+        Enc<Mxe, Balance>,
+        Enc<Mxe, Balance>,
+        Enc<Mxe, Balance>,
+        Enc<Mxe, OfferTerms>,
+        bool,
+    ) {
+        let bid = bid_ctxt.to_arcis();
+        let ask = ask_ctxt.to_arcis();
+        let maker_a = maker_a_ctxt.to_arcis().amount;
+        let maker_b = if maker_b_is_new { 0 } else { maker_b_ctxt.to_arcis().amount };
+        let taker_a = if taker_a_is_new { 0 } else { taker_a_ctxt.to_arcis().amount };
+        let taker_b = taker_b_ctxt.to_arcis().amount;
+        let ask_a = if ask.token_a_amount == 0 { 1 } else { ask.token_a_amount };
+        let slice_price = ((bid.token_a_amount as u128) * (ask.token_b_amount as u128) + (ask_a as u128) - 1)
+            / (ask_a as u128);
+        let slice_price = slice_price as u64;
+        let matched = (bid.token_a_amount > 0)
+            & (bid.token_a_amount <= ask.token_a_amount)
+            & (bid.max_token_b_amount >= slice_price)
+            & (maker_a >= bid.token_a_amount)
+            & (taker_b >= slice_price)
+            // Neither credited balance may wrap
+            & ((maker_b as u128) + (slice_price as u128) <= u64::MAX as u128)
+            & ((taker_a as u128) + (bid.token_a_amount as u128) <= u64::MAX as u128);
+        let token_a_amount = if matched { bid.token_a_amount } else { 0 };
+        let token_b_amount = if matched { slice_price } else { 0 };
+        let remaining = OfferTerms {
+            token_a_amount: ask.token_a_amount - token_a_amount,
+            token_b_amount: ask.token_b_amount - token_b_amount,
+        };
+        let used_up = matched & (remaining.token_a_amount == 0);
+        let taker_settlement = Settlement {
+            token_a_amount,
+            token_b_amount,
+        };
+        let maker_settlement = Settlement {
+            token_a_amount,
+            token_b_amount,
+        };
+        (
+            matched.reveal(),
+            bid_ctxt.owner.from_arcis(taker_settlement),
+            maker.from_arcis(maker_settlement),
+            Mxe::get().from_arcis(Balance { amount: maker_a - token_a_amount }),
+            Mxe::get().from_arcis(Balance { amount: maker_b + token_b_amount }),
+            Mxe::get().from_arcis(Balance { amount: taker_a + token_a_amount }),
+            Mxe::get().from_arcis(Balance { amount: taker_b - token_b_amount }),
+            Mxe::get().from_arcis(remaining),
+            used_up.reveal(),
+        )
+    }
+
+    // === Sealed-bid auctions ===
+
+    /// One buyer's bid, in lamports
+    pub struct SealedBid {
+        amount: u64,
+    }
+
+    /// Every bid on an auction, slot order matches the program's bidder list.
+    /// Eight slots, the program's `MAX_AUCTION_BIDS`
+    pub struct AuctionBids {
+        amounts: [u64; 8],
+    }
+
+    /// Writes a bid into `slot` of the MXE-held bid table, starting an empty table on the first bid.
+    /// A bid above the auction's collateral tier counts as the tier, so a winner can always pay
+    #[instruction]
+    pub fn place_sealed_bid(
+        bid_ctxt: Enc<Shared, SealedBid>,
+        bids_ctxt: Enc<Mxe, AuctionBids>,
+        is_first: bool,
+        slot: u8,
+        collateral: u64,
+    ) -> Enc<Mxe, AuctionBids> {
+        let bid = bid_ctxt.to_arcis();
+        let bids = bids_ctxt.to_arcis();
+        let amount = if bid.amount > collateral { collateral } else { bid.amount };
+        let mut amounts = [0u64; 8];
+        for i in 0..8 {
+            let current = if is_first { 0 } else { bids.amounts[i] };
+            amounts[i] = if i == slot as usize { amount } else { current };
+        }
+        Mxe::get().from_arcis(AuctionBids { amounts })
+    }
+
+    /// Reveals only whether any bid met the reserve, the winning slot and its bid.
+    /// Ties go to the earliest bid
+    #[instruction]
+    pub fn resolve_sealed_auction(
+        bids_ctxt: Enc<Mxe, AuctionBids>,
+        bid_count: u8,
+        reserve_price: u64,
+    ) -> (bool, u8, u64) {
+        let bids = bids_ctxt.to_arcis();
+        let mut found = false;
+        let mut winner = 0u8;
+        let mut best = 0u64;
+        for i in 0..8 {
+            let amount = bids.amounts[i];
+            let beats = ((i as u8) < bid_count)
+                & (amount >= reserve_price)
+                & (!found | (amount > best));
+            winner = if beats { i as u8 } else { winner };
+            best = if beats { amount } else { best };
+            found = found | beats;
+        }
+        (found.reveal(), winner.reveal(), best.reveal())
+    }
+
+    // === Dark pool order book ===
+
+    /// A limit order, price in quote units per base unit
+    pub struct Order {
+        is_buy: bool,
+        price: u64,
+        size: u64,
+    }
+
+    /// Resting orders by slot, eight slots, the program's `MAX_BOOK_ORDERS`.
+    /// A slot with size zero is empty
+    pub struct Book {
+        is_buy: [bool; 8],
+        price: [u64; 8],
+        size: [u64; 8],
+    }
+
+    /// Writes an order into `slot` of the MXE-held book, starting an empty book on the first order.
+    /// An order its escrow can't cover rests with size zero: a sell needs `base_escrow` for its
+    /// size, a buy needs `quote_escrow` for size times its limit price
+    #[instruction]
+    pub fn add_order(
+        order_ctxt: Enc<Shared, Order>,
+        book_ctxt: Enc<Mxe, Book>,
+        is_first: bool,
+        slot: u8,
+        base_escrow: u64,
+        quote_escrow: u64,
+    ) -> Enc<Mxe, Book> {
+        let order = order_ctxt.to_arcis();
+        let book = book_ctxt.to_arcis();
+        let funded = if order.is_buy {
+            (order.price as u128) * (order.size as u128) <= quote_escrow as u128
+        } else {
+            order.size <= base_escrow
+        };
+        let mut is_buy = [false; 8];
+        let mut price = [0u64; 8];
+        let mut size = [0u64; 8];
+        for i in 0..8 {
+            let here = i == slot as usize;
+            is_buy[i] = if here { order.is_buy } else { !is_first & book.is_buy[i] };
+            price[i] = if here {
+                order.price
+            } else if is_first {
+                0
+            } else {
+                book.price[i]
+            };
+            size[i] = if here {
+                if funded {
+                    order.size
+                } else {
+                    0
+                }
+            } else if is_first {
+                0
+            } else {
+                book.size[i]
+            };
+        }
+        Mxe::get().from_arcis(Book {
+            is_buy,
+            price,
+            size,
+        })
+    }
+
+    /// Crosses every resting buy against every resting sell in slot order, at the midpoint
+    /// of the two limits, up to four fills per call. Slots not marked `live` hold a cancelled
+    /// order and are skipped. Reveals only the fills, never a resting price or size, nor
+    /// which orders are left
+    #[instruction]
+    pub fn match_orders(
+        book_ctxt: Enc<Mxe, Book>,
+        live: [bool; 8],
+    ) -> (Enc<Mxe, Book>, u8, [u8; 4], [u8; 4], [u64; 4], [u64; 4]) {
+        let mut book = book_ctxt.to_arcis();
+        let mut fill_count = 0u8;
+        let mut buy_slots = [0u8; 4];
+        let mut sell_slots = [0u8; 4];
+        let mut prices = [0u64; 4];
+        let mut sizes = [0u64; 4];
+        for b in 0..8 {
+            for s in 0..8 {
+                let crosses = live[b]
+                    & live[s]
+                    & book.is_buy[b]
+                    & !book.is_buy[s]
+                    & (book.size[b] > 0)
+                    & (book.size[s] > 0)
+                    & (book.price[b] >= book.price[s])
+                    & (fill_count < 4);
+                let qty = if book.size[b] < book.size[s] {
+                    book.size[b]
+                } else {
+                    book.size[s]
+                };
+                let spread = if crosses {
+                    book.price[b] - book.price[s]
+                } else {
+                    0
+                };
+                let price = book.price[s] + spread / 2;
+                for k in 0..4 {
+                    let write = crosses & (fill_count == k as u8);
+                    buy_slots[k] = if write { b as u8 } else { buy_slots[k] };
+                    sell_slots[k] = if write { s as u8 } else { sell_slots[k] };
+                    prices[k] = if write { price } else { prices[k] };
+                    sizes[k] = if write { qty } else { sizes[k] };
+                }
+                book.size[b] = if crosses { book.size[b] - qty } else { book.size[b] };
+                book.size[s] = if crosses { book.size[s] - qty } else { book.size[s] };
+                fill_count = if crosses { fill_count + 1 } else { fill_count };
+            }
+        }
+        (
+            Mxe::get().from_arcis(book),
+            fill_count.reveal(),
+            buy_slots.reveal(),
+            sell_slots.reveal(),
+            prices.reveal(),
+            sizes.reveal(),
+        )
     }
 }
\ No newline at end of file
@@ -1,16 +1,67 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer as SplTransfer};
-use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::token_interface::{self, TokenInterface, TokenAccount, Mint, CloseAccount};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022_extensions::transfer_fee::{harvest_withheld_tokens_to_mint, HarvestWithheldTokensToMint};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use spl_token_2022::extension::{
+    non_transferable::NonTransferable, permanent_delegate::PermanentDelegate,
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+};
 use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::types::{CircuitSource, OffChainCircuitSource};
+use anchor_lang::solana_program::{hash, keccak, secp256k1_program, secp256k1_recover::secp256k1_recover};
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
 
 const COMP_DEF_OFFSET_ADD_TOGETHER: u32 = comp_def_offset("add_together");
 const COMP_DEF_OFFSET_RELAY_OFFER_CLONE: u32 = comp_def_offset("relay_offer_clone");
 const COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE: u32 = comp_def_offset("confidential_deposit_native");
+const COMP_DEF_OFFSET_CONFIDENTIAL_WITHDRAW_NATIVE: u32 = comp_def_offset("confidential_withdraw_native");
+const COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_SPL: u32 = comp_def_offset("confidential_deposit_spl");
+const COMP_DEF_OFFSET_CONFIDENTIAL_WITHDRAW_SPL: u32 = comp_def_offset("confidential_withdraw_spl");
 const COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL: u32 = comp_def_offset("interchain_origin_evm_deposit_seller_spl");
 const COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER: u32 = comp_def_offset("finalize_interchain_origin_evm_offer");
 const COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE: u32 = comp_def_offset("deposit_seller_native");
 const COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL: u32 = comp_def_offset("deposit_seller_spl");
 const COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER: u32 = comp_def_offset("finalize_intrachain_offer");
+const COMP_DEF_OFFSET_CREATE_CONFIDENTIAL_OFFER: u32 = comp_def_offset("create_confidential_offer");
+const COMP_DEF_OFFSET_MATCH_CONFIDENTIAL_OFFER: u32 = comp_def_offset("match_confidential_offer");
+const COMP_DEF_OFFSET_PLACE_SEALED_BID: u32 = comp_def_offset("place_sealed_bid");
+const COMP_DEF_OFFSET_RESOLVE_SEALED_AUCTION: u32 = comp_def_offset("resolve_sealed_auction");
+const COMP_DEF_OFFSET_ADD_ORDER: u32 = comp_def_offset("add_order");
+const COMP_DEF_OFFSET_MATCH_ORDERS: u32 = comp_def_offset("match_orders");
+
+/// Lamports taken from an expired offer's rent to pay whoever cranks `expire_*_offer`
+const EXPIRY_CRANK_BOUNTY: u64 = 100_000;
+
+/// Lamports a taker posts with each confidential bid, returned once the bid settles
+/// and paid to the maker when it does not, so locking an offer with junk bids costs
+const MATCH_BOND: u64 = 1_000_000;
+
+/// Mint native lamport balances are kept under, the wrapped SOL mint
+const NATIVE_BALANCE_MINT: Pubkey = anchor_spl::token::spl_token::native_mint::ID;
+
+/// Upper bound on guardians per set, as in Wormhole
+const MAX_GUARDIANS: usize = 19;
+/// Largest message payload a posted VAA can carry
+const MAX_VAA_PAYLOAD: usize = 256;
+
+/// Hard cap on any protocol fee, 1%
+const MAX_FEE_BPS: u16 = 100;
+/// Fee treasuries the exchange config can hold, one per asset
+const MAX_TREASURIES: usize = 16;
+/// Token A addresses a chain config can allow
+const MAX_CHAIN_TOKENS: usize = 16;
+const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Bid slots per sealed auction, fixed by the `AuctionBids` circuit type
+const MAX_AUCTION_BIDS: usize = 8;
+/// Resting order slots per order book, fixed by the `Book` circuit type
+const MAX_BOOK_ORDERS: usize = 8;
+/// Fills a single `match_orders` call can reveal
+const MAX_BOOK_FILLS: usize = 4;
+
+/// First line of the message a buyer signs with their EVM key to take an interchain offer
+const EVM_PROOF_DOMAIN: &str = "Take an offer on the confidential cross-chain exchange";
 
 
 declare_id!("DzueqW4xsJRhv5pQdcwTsWgeKcV2xfEoKRALN4Ma8dHd");
@@ -19,8 +70,11 @@ declare_id!("DzueqW4xsJRhv5pQdcwTsWgeKcV2xfEoKRALN4Ma8dHd");
 pub mod confidential_cross_chain_exchange {
     use super::*;
 
-    pub fn init_add_together_comp_def(ctx: Context<InitAddTogetherCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    // Comp defs are registered by the exchange authority, from the uploaded circuit
+    // or from an off-chain URL pinned to the circuit hash. The authority is whoever held
+    // the upgrade authority at `initialize_exchange`, so the config must exist first
+    pub fn init_add_together_comp_def(ctx: Context<InitAddTogetherCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
         Ok(())
     }
 
@@ -51,108 +105,532 @@ pub mod confidential_cross_chain_exchange {
         Ok(())
     }
 
-    pub fn init_relay_offer_clone_comp_def(ctx: Context<InitRelayOfferCloneCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    pub fn init_relay_offer_clone_comp_def(ctx: Context<InitRelayOfferCloneCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_confidential_deposit_native_comp_def(ctx: Context<InitConfidentialDepositNativeCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_confidential_withdraw_native_comp_def(ctx: Context<InitConfidentialWithdrawNativeCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_confidential_deposit_spl_comp_def(ctx: Context<InitConfidentialDepositSplCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_confidential_withdraw_spl_comp_def(ctx: Context<InitConfidentialWithdrawSplCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_interchain_origin_evm_deposit_seller_spl_comp_def(ctx: Context<InitInterchainOriginEvmDepositSellerSplCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_finalize_interchain_origin_evm_offer_comp_def(ctx: Context<InitFinalizeInterchainOriginEvmOfferCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_deposit_seller_native_comp_def(ctx: Context<InitDepositSellerNativeCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_deposit_seller_spl_comp_def(ctx: Context<InitDepositSellerSplCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_finalize_intrachain_offer_comp_def(ctx: Context<InitFinalizeIntrachainOfferCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_create_confidential_offer_comp_def(ctx: Context<InitCreateConfidentialOfferCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_match_confidential_offer_comp_def(ctx: Context<InitMatchConfidentialOfferCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_place_sealed_bid_comp_def(ctx: Context<InitPlaceSealedBidCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_resolve_sealed_auction_comp_def(ctx: Context<InitResolveSealedAuctionCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_add_order_comp_def(ctx: Context<InitAddOrderCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    pub fn init_match_orders_comp_def(ctx: Context<InitMatchOrdersCompDef>, circuit: Option<OffChainCircuit>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, circuit.map(OffChainCircuit::into_source), None)?;
+        Ok(())
+    }
+
+    // === EXCHANGE CONFIG ===
+
+    /// Create the global config. Only the program's upgrade authority may call this, so the
+    /// deploy cannot be front-run, and it becomes the exchange authority
+    pub fn initialize_exchange(
+        ctx: Context<InitializeExchange>,
+        fees: FeeSchedule,
+        treasuries: Vec<AssetTreasury>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.exchange_config;
+        config.authority = ctx.accounts.authority.key();
+        config.bump = ctx.bumps.exchange_config;
+        config.set(fees, treasuries)?;
+
+        msg!("⚙️ Exchange initialized, authority {}", config.authority);
+        Ok(())
+    }
+
+    /// Replace the fee schedule and treasuries, authority only
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fees: FeeSchedule,
+        treasuries: Vec<AssetTreasury>,
+    ) -> Result<()> {
+        ctx.accounts.exchange_config.set(fees, treasuries)?;
+
+        msg!("⚙️ Exchange config updated");
+        Ok(())
+    }
+
+    /// Flip the kill switches, authority only
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: PauseFlags) -> Result<()> {
+        ctx.accounts.exchange_config.paused = paused;
+
+        msg!(
+            "⏸️ Pause flags: new offers {}, settlements {}, relays {}",
+            paused.new_offers,
+            paused.settlements,
+            paused.relays
+        );
+        emit!(PauseUpdatedEvent {
+            new_offers: paused.new_offers,
+            settlements: paused.settlements,
+            relays: paused.relays,
+        });
         Ok(())
     }
 
-    pub fn init_confidential_deposit_native_comp_def(ctx: Context<InitConfidentialDepositNativeCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    /// First step of an authority handover, `None` withdraws a pending proposal
+    pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.exchange_config.pending_authority = new_authority;
         Ok(())
     }
 
-    pub fn init_interchain_origin_evm_deposit_seller_spl_comp_def(ctx: Context<InitInterchainOriginEvmDepositSellerSplCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    /// Second step of an authority handover, signed by the proposed authority
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.exchange_config;
+        let previous = config.authority;
+        config.authority = ctx.accounts.pending_authority.key();
+        config.pending_authority = None;
+
+        msg!("🔑 Exchange authority moved from {} to {}", previous, config.authority);
+        emit!(AuthorityTransferredEvent {
+            previous,
+            authority: config.authority,
+        });
         Ok(())
     }
 
-    pub fn init_finalize_interchain_origin_evm_offer_comp_def(ctx: Context<InitFinalizeInterchainOriginEvmOfferCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    // === CROSS-CHAIN MESSAGES ===
+
+    /// Replace the guardian set whose signatures `post_vaa` accepts, authority only
+    pub fn set_guardian_set(
+        ctx: Context<SetGuardianSet>,
+        index: u32,
+        keys: Vec<[u8; 20]>,
+    ) -> Result<()> {
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        require!(
+            !keys.is_empty() && keys.len() <= MAX_GUARDIANS,
+            ErrorCode::InvalidGuardianSet
+        );
+        // A fresh account has index 0, later sets must move forward
+        require!(
+            guardian_set.keys.is_empty() || index > guardian_set.index,
+            ErrorCode::InvalidGuardianSet
+        );
+        guardian_set.index = index;
+        guardian_set.keys = keys;
+        guardian_set.bump = ctx.bumps.guardian_set;
+
+        msg!("🛡️ Guardian set {} stored with {} keys", index, guardian_set.keys.len());
         Ok(())
     }
 
-    pub fn init_deposit_seller_native_comp_def(ctx: Context<InitDepositSellerNativeCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    /// Route offers for `chain_id` over `bridge`, trusting `emitter` as the origin
+    /// contract on the bridge's side, authority only
+    pub fn register_bridge_route(
+        ctx: Context<RegisterBridgeRoute>,
+        chain_id: u64,
+        bridge: BridgeAdapter,
+        bridge_chain_id: u32,
+        emitter: [u8; 32],
+        consistency_level: u8,
+        finality_seconds: u32,
+    ) -> Result<()> {
+        let route = &mut ctx.accounts.bridge_route;
+        route.chain_id = chain_id;
+        route.bridge = bridge;
+        route.bridge_chain_id = bridge_chain_id;
+        route.emitter = emitter;
+        route.consistency_level = consistency_level;
+        route.finality_seconds = finality_seconds;
+        route.bump = ctx.bumps.bridge_route;
+
+        msg!("🌉 Chain {} routed over {:?} as bridge chain {}", chain_id, bridge, bridge_chain_id);
         Ok(())
     }
 
-    pub fn init_deposit_seller_spl_comp_def(ctx: Context<InitDepositSellerSplCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    /// Add or update a supported chain, authority only
+    pub fn set_chain_config(
+        ctx: Context<SetChainConfig>,
+        chain_id: u64,
+        params: ChainParams,
+    ) -> Result<()> {
+        params.validate()?;
+        let chain = &mut ctx.accounts.chain_config;
+        chain.chain_id = chain_id;
+        chain.name_hash = params.name_hash;
+        chain.native_decimals = params.native_decimals;
+        chain.allowed_tokens = params.allowed_tokens;
+        chain.min_offer_size = params.min_offer_size;
+        chain.max_offer_size = params.max_offer_size;
+        chain.enabled = params.enabled;
+        chain.bump = ctx.bumps.chain_config;
+
+        msg!("🔗 Chain {} config stored, enabled: {}", chain_id, chain.enabled);
         Ok(())
     }
 
-    pub fn init_finalize_intrachain_offer_comp_def(ctx: Context<InitFinalizeIntrachainOfferCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
+    /// Switch offers for an already configured chain on or off, authority only
+    pub fn set_chain_enabled(
+        ctx: Context<SetChainEnabled>,
+        chain_id: u64,
+        enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.chain_config.enabled = enabled;
+        msg!("🔗 Chain {} enabled: {}", chain_id, enabled);
         Ok(())
     }
 
+    /// Verify guardian signatures over a VAA body and store it for `relay_offer_clone`
+    pub fn post_vaa(
+        ctx: Context<PostVaa>,
+        vaa_hash: [u8; 32],
+        guardian_set_index: u32,
+        signatures: Vec<GuardianSignature>,
+        body: Vec<u8>,
+    ) -> Result<()> {
+        let guardian_set = &ctx.accounts.guardian_set;
+        require!(guardian_set.index == guardian_set_index, ErrorCode::InvalidGuardianSet);
+
+        // Guardians sign the double keccak of the body, the single hash identifies the message
+        let digest = keccak::hash(&body).to_bytes();
+        require!(digest == vaa_hash, ErrorCode::InvalidVaaPayload);
+        guardian_set.verify(&keccak::hash(&digest).to_bytes(), &signatures)?;
+
+        let vaa = &mut ctx.accounts.posted_vaa;
+        vaa.guardian_set_index = guardian_set_index;
+        vaa.hash = vaa_hash;
+        vaa.parse_body(&body)?;
 
+        msg!(
+            "📨 VAA from chain {} sequence {} verified by {} guardians",
+            vaa.emitter_chain,
+            vaa.sequence,
+            signatures.len()
+        );
+        Ok(())
+    }
+
+    /// Clone an origin-chain offer from a verified cross-chain message. The business
+    /// fields come from the posted VAA, each message can only be relayed once. The identity
+    /// commitment is not in the message, so the maker it names co-signs the relay and only
+    /// they choose who can take the offer.
     pub fn relay_offer_clone(
         ctx: Context<RelayOfferClone>,
-        // Public business fields (matching original program)
+        // Public business fields, checked against the message payload
         id: u64,
-        token_b_wanted_amount: u64,
-        token_a_offered_amount: u64,
-        is_taker_native: bool,
         chain_id: u64,
-        deadline: i64,
+        vaa_hash: [u8; 32],
         // Confidential identity
-        ciphertext_external_seller_identity_hash: [u8; 32],
+        ciphertext_external_seller_identity_hash: [[u8; 32]; 4],
         // Arcium handshake
         pub_key: [u8; 32],
         nonce: u128,
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        
+
+        // The consumed-message account was just created, so this VAA has not been relayed before
+        let posted_vaa = &ctx.accounts.posted_vaa;
+        let route = &ctx.accounts.bridge_route;
+        route.bridge.verify_inbound(route, posted_vaa)?;
+        let payload = OfferPayload::decode(&posted_vaa.payload)?;
+        require!(payload.id == id, ErrorCode::InvalidVaaPayload);
+        require_keys_eq!(payload.maker, ctx.accounts.maker.key(), ErrorCode::InvalidVaaPayload);
+        ctx.accounts
+            .chain_config
+            .check_offer(&payload.token_a, payload.token_a_offered_amount)?;
+        ctx.accounts.consumed_message.vaa_hash = vaa_hash;
+        ctx.accounts.consumed_message.consumed_at = Clock::get()?.unix_timestamp;
+
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.interchain_offer;
         offer.id = id;
-        offer.token_a_offered_amount = token_a_offered_amount;
-        offer.token_b_wanted_amount = token_b_wanted_amount;
-        offer.is_taker_native = is_taker_native;
-        offer.chain_id = chain_id;
-        offer.deadline = deadline;
+        offer.maker = ctx.accounts.maker.key();
+        offer.payer = ctx.accounts.payer.key();
+        offer.token_a_offered_amount = payload.token_a_offered_amount;
+        offer.token_b_wanted_amount = payload.token_b_wanted_amount;
+        offer.is_taker_native = payload.is_taker_native;
+        // Token A stays escrowed on the origin chain, only the taker leg settles here
+        offer.token_a_mint = Pubkey::default();
+        offer.origin_escrowed = true;
+        offer.token_b_mint = taker_mint(ctx.accounts.token_b_mint.as_deref(), payload.is_taker_native)?;
+        offer.chain_id = route.chain_id;
+        require_before_deadline(payload.deadline)?;
+        offer.deadline = payload.deadline;
         offer.bump = ctx.bumps.interchain_offer;
+        offer.status.transition(OfferStatus::IdentityPending)?;
 
         // Only pass encrypted inputs expected by the circuit (handshake + encrypted identity)
-        let args = vec![
+        let mut args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_external_seller_identity_hash),
         ];
+        args.extend(identity_arguments(&ciphertext_external_seller_identity_hash));
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![RelayOfferCloneCallback::callback_ix(&[])],
+            vec![RelayOfferCloneCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.interchain_offer.key(),
+                is_writable: true,
+            }])],
         )?;
 
         Ok(())
     }
 
+    /// Move lamports into the shared pool and credit them to the payer's encrypted balance.
+    /// The deposit itself is public, what the balance is later spent on is not
     pub fn confidential_deposit_native(
         ctx: Context<ConfidentialDepositNative>,
         computation_offset: u64,
-        ciphertext_amount: [u8; 32],
+        amount: u64,
+        // Key and nonce the owner's copy of the new balance is encrypted to
         pub_key: [u8; 32],
         nonce: u128,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        let args = vec![
+        require!(amount > 0, ErrorCode::ZeroAmount);
+
+        let balance = &mut ctx.accounts.confidential_balance;
+        require!(balance.pending.is_none(), ErrorCode::BalanceUpdatePending);
+        balance.owner = ctx.accounts.payer.key();
+        balance.mint = NATIVE_BALANCE_MINT;
+        balance.bump = ctx.bumps.confidential_balance;
+        // Refunded by the callback if the computation aborts
+        balance.pending = Some(BalanceUpdate::Deposit(amount));
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.confidential_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+        ];
+        args.extend(EncryptedBalance::arguments(balance.balance.as_ref()));
+        args.push(Argument::PlaintextBool(balance.balance.is_none()));
+        args.push(Argument::PlaintextU64(amount));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ConfidentialDepositNativeCallback::callback_ix(&balance_callback_accounts(
+                ctx.accounts.confidential_balance.key(),
+                ctx.accounts.confidential_pool.key(),
+                ctx.accounts.payer.key(),
+            ))],
+        )?;
+
+        Ok(())
+    }
+
+    /// Pay lamports out of the pool if the encrypted balance covers them
+    pub fn confidential_withdraw_native(
+        ctx: Context<ConfidentialWithdrawNative>,
+        computation_offset: u64,
+        amount: u64,
+        // Key and nonce the owner's copy of the new balance is encrypted to
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+
+        let balance = &mut ctx.accounts.confidential_balance;
+        require!(balance.pending.is_none(), ErrorCode::BalanceUpdatePending);
+        let current = balance.balance.as_ref().ok_or(ErrorCode::NoConfidentialBalance)?;
+
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+        ];
+        args.extend(EncryptedBalance::arguments(Some(current)));
+        args.push(Argument::PlaintextU64(amount));
+        balance.pending = Some(BalanceUpdate::Withdraw(amount));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ConfidentialWithdrawNativeCallback::callback_ix(&balance_callback_accounts(
+                ctx.accounts.confidential_balance.key(),
+                ctx.accounts.confidential_pool.key(),
+                ctx.accounts.payer.key(),
+            ))],
+        )?;
+
+        Ok(())
+    }
+
+    /// Move tokens into the mint's pool vault and credit what arrives, net of any transfer
+    /// fee, to the payer's encrypted balance of that mint
+    pub fn confidential_deposit_spl(
+        ctx: Context<ConfidentialDepositSpl>,
+        computation_offset: u64,
+        amount: u64,
+        // Key and nonce the owner's copy of the new balance is encrypted to
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let mint = ctx.accounts.mint.to_account_info();
+        require_escrowable_mint(&mint)?;
+        let received = amount.saturating_sub(transfer_fee(&mint, amount)?);
+        require!(received > 0, ErrorCode::ZeroAmount);
+
+        let balance = &mut ctx.accounts.confidential_balance;
+        require!(balance.pending.is_none(), ErrorCode::BalanceUpdatePending);
+        balance.owner = ctx.accounts.payer.key();
+        balance.mint = mint.key();
+        balance.bump = ctx.bumps.confidential_balance;
+        // Refunded by the callback if the computation aborts
+        balance.pending = Some(BalanceUpdate::Deposit(received));
+
+        spl_token_2022::onchain::invoke_transfer_checked(
+            &ctx.accounts.token_program.key(),
+            ctx.accounts.payer_token_account.to_account_info(),
+            mint,
+            ctx.accounts.pool_token_vault.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+        ];
+        args.extend(EncryptedBalance::arguments(balance.balance.as_ref()));
+        args.push(Argument::PlaintextBool(balance.balance.is_none()));
+        args.push(Argument::PlaintextU64(received));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ConfidentialDepositSplCallback::callback_ix(&token_balance_callback_accounts(
+                ctx.accounts.confidential_balance.key(),
+                ctx.accounts.mint.key(),
+                ctx.accounts.pool_token_vault.key(),
+                ctx.accounts.payer_token_account.key(),
+                ctx.accounts.escrow_authority.key(),
+                ctx.accounts.token_program.key(),
+            ))],
+        )?;
+
+        Ok(())
+    }
+
+    /// Pay tokens out of the mint's pool vault if the encrypted balance covers them
+    pub fn confidential_withdraw_spl(
+        ctx: Context<ConfidentialWithdrawSpl>,
+        computation_offset: u64,
+        amount: u64,
+        // Key and nonce the owner's copy of the new balance is encrypted to
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+
+        let balance = &mut ctx.accounts.confidential_balance;
+        require!(balance.pending.is_none(), ErrorCode::BalanceUpdatePending);
+        let current = balance.balance.as_ref().ok_or(ErrorCode::NoConfidentialBalance)?;
+
+        let mut args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_amount),
         ];
+        args.extend(EncryptedBalance::arguments(Some(current)));
+        args.push(Argument::PlaintextU64(amount));
+        balance.pending = Some(BalanceUpdate::Withdraw(amount));
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![ConfidentialDepositNativeCallback::callback_ix(&[])],
+            vec![ConfidentialWithdrawSplCallback::callback_ix(&token_balance_callback_accounts(
+                ctx.accounts.confidential_balance.key(),
+                ctx.accounts.mint.key(),
+                ctx.accounts.pool_token_vault.key(),
+                ctx.accounts.payer_token_account.key(),
+                ctx.accounts.escrow_authority.key(),
+                ctx.accounts.token_program.key(),
+            ))],
         )?;
 
         Ok(())
@@ -167,8 +645,10 @@ pub mod confidential_cross_chain_exchange {
         is_taker_native: bool,
         chain_id: u64,
         deadline: i64,
+        // Hashlock and timelock for a bridgeless atomic swap, `None` settles over the bridge route
+        htlc: Option<HtlcTerms>,
         // Confidential identity
-        ciphertext_seller_identity_hash: [u8; 32],
+        ciphertext_seller_identity_hash: [[u8; 32]; 4],
         // Arcium handshake
         pub_key: [u8; 32],
         nonce: u128,
@@ -179,56 +659,119 @@ pub mod confidential_cross_chain_exchange {
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.interchain_offer;
         offer.id = id;
+        offer.maker = ctx.accounts.maker.key();
+        offer.payer = ctx.accounts.payer.key();
         offer.token_a_offered_amount = token_a_offered_amount;
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_taker_native = is_taker_native;
+        offer.token_a_mint = ctx.accounts.token_a_mint.key();
+        offer.origin_escrowed = false;
+        offer.token_b_mint = taker_mint(ctx.accounts.token_b_mint.as_deref(), is_taker_native)?;
         offer.chain_id = chain_id;
+        require_before_deadline(deadline)?;
+        match (&htlc, ctx.accounts.bridge_route.as_deref()) {
+            (Some(terms), _) => terms.validate(deadline)?,
+            (None, Some(route)) => route.require_settlement_window(deadline)?,
+            (None, None) => return err!(ErrorCode::MissingBridgeRoute),
+        }
+        offer.htlc = htlc;
         offer.deadline = deadline;
         offer.bump = ctx.bumps.interchain_offer;
+        offer.status.transition(OfferStatus::IdentityPending)?;
+
+        // Escrow token A in the offer-owned associated token account,
+        // transfer-hook accounts are forwarded from remaining_accounts
+        require_escrowable_mint(&ctx.accounts.token_a_mint.to_account_info())?;
+        spl_token_2022::onchain::invoke_transfer_checked(
+            &ctx.accounts.token_program.key(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.offer_token_vault.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            ctx.remaining_accounts,
+            token_a_offered_amount,
+            ctx.accounts.token_a_mint.decimals,
+            &[],
+        )?;
+
+        // Quote what the vault can hand the buyer, net of the inbound and outbound transfer fee
+        ctx.accounts.offer_token_vault.reload()?;
+        offer.token_a_offered_amount = deliverable_amount(
+            &ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.offer_token_vault.amount,
+        )?;
+        ctx.accounts
+            .chain_config
+            .check_offer(&offer.token_a_mint.to_bytes(), offer.token_a_offered_amount)?;
 
         // Only pass encrypted inputs expected by the circuit
-        let args = vec![
+        let mut args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_seller_identity_hash),
         ];
+        args.extend(identity_arguments(&ciphertext_seller_identity_hash));
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![InterchainOriginEvmDepositSellerSplCallback::callback_ix(&[])],
+            vec![InterchainOriginEvmDepositSellerSplCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.interchain_offer.key(),
+                is_writable: true,
+            }])],
         )?;
 
         Ok(())
     }
 
+    /// Queue the identity match for a buyer that proved control of `buyer_evm_address`
+    /// with a secp256k1 precompile instruction placed right before this one
     pub fn finalize_interchain_origin_evm_offer(
         ctx: Context<FinalizeInterchainOriginEvmOffer>,
         // Public business field
         id: u64,
-        // Confidential buyer identity
-        ciphertext_buyer_identity_hash: [u8; 32],
-        // Arcium handshake
-        pub_key: [u8; 32],
-        nonce: u128,
+        // EVM address the taker settles from on the origin chain
+        buyer_evm_address: [u8; 20],
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        // Circuit expects only encrypted buyer identity (plus handshake)
-        let args = vec![
-            Argument::ArcisPubkey(pub_key),
-            Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_buyer_identity_hash),
-        ];
+        let offer = &ctx.accounts.interchain_offer;
+        offer.status.require_open()?;
+        require_before_deadline(offer.deadline)?;
+
+        // The EVM key must have signed this offer and this Solana taker
+        let message = evm_ownership_message(&offer.key(), &ctx.accounts.payer.key());
+        verify_evm_signature(&ctx.accounts.instructions_sysvar, &buyer_evm_address, &message)?;
+        let evm_proof = &mut ctx.accounts.evm_proof;
+        evm_proof.evm_address = buyer_evm_address;
+        evm_proof.bump = ctx.bumps.evm_proof;
+
+        // The stored MXE-encrypted seller identity, then the commitment of the proven address
+        // it must equal. It has to come from the verified address rather than the buyer, so
+        // it is derived here; the address itself is already public in the precompile data
+        let mut args = offer.seller_identity.to_arguments();
+        args.extend(evm_identity_commitment(&buyer_evm_address).map(Argument::PlaintextU64));
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![FinalizeInterchainOriginEvmOfferCallback::callback_ix(&[])],
+            vec![FinalizeInterchainOriginEvmOfferCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.interchain_offer.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.payer.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.evm_proof.key(),
+                    is_writable: true,
+                },
+            ])],
         )?;
 
         Ok(())
@@ -242,8 +785,10 @@ pub mod confidential_cross_chain_exchange {
         token_a_offered_amount: u64,
         is_taker_native: bool,
         deadline: i64,
+        // Smallest slice of token A a single taker may fill
+        min_fill_amount: u64,
         // Confidential identity
-        ciphertext_seller_identity_hash: [u8; 32],
+        ciphertext_seller_identity_hash: [[u8; 32]; 4],
         // Arcium handshake
         pub_key: [u8; 32],
         nonce: u128,
@@ -254,25 +799,37 @@ pub mod confidential_cross_chain_exchange {
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.intrachain_offer;
         offer.id = id;
+        offer.maker = ctx.accounts.maker.key();
+        offer.payer = ctx.accounts.payer.key();
         offer.token_a_offered_amount = token_a_offered_amount;
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_taker_native = is_taker_native;
+        offer.token_a_mint = Pubkey::default();
+        offer.token_b_mint = taker_mint(ctx.accounts.token_b_mint.as_deref(), is_taker_native)?;
+        require!(min_fill_amount <= token_a_offered_amount, ErrorCode::InvalidFillAmount);
+        offer.min_fill_amount = min_fill_amount;
+        offer.remaining_amount = token_a_offered_amount;
+        require_before_deadline(deadline)?;
         offer.deadline = deadline;
         offer.bump = ctx.bumps.intrachain_offer;
+        offer.status.transition(OfferStatus::IdentityPending)?;
 
         // Only pass encrypted inputs expected by the circuit
-        let args = vec![
+        let mut args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_seller_identity_hash),
         ];
+        args.extend(identity_arguments(&ciphertext_seller_identity_hash));
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![DepositSellerNativeCallback::callback_ix(&[])],
+            vec![DepositSellerNativeCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.key(),
+                is_writable: true,
+            }])],
         )?;
 
         Ok(())
@@ -286,8 +843,10 @@ pub mod confidential_cross_chain_exchange {
         token_a_offered_amount: u64,
         is_taker_native: bool,
         deadline: i64,
+        // Smallest slice of token A a single taker may fill
+        min_fill_amount: u64,
         // Confidential identity
-        ciphertext_seller_identity_hash: [u8; 32],
+        ciphertext_seller_identity_hash: [[u8; 32]; 4],
         // Arcium handshake
         pub_key: [u8; 32],
         nonce: u128,
@@ -298,25 +857,63 @@ pub mod confidential_cross_chain_exchange {
         // Store public metadata in PDA
         let offer = &mut ctx.accounts.intrachain_offer;
         offer.id = id;
+        offer.maker = ctx.accounts.maker.key();
+        offer.payer = ctx.accounts.payer.key();
         offer.token_a_offered_amount = token_a_offered_amount;
         offer.token_b_wanted_amount = token_b_wanted_amount;
         offer.is_taker_native = is_taker_native;
+        offer.token_a_mint = ctx.accounts.token_a_mint.key();
+        offer.token_b_mint = taker_mint(ctx.accounts.token_b_mint.as_deref(), is_taker_native)?;
+        require_before_deadline(deadline)?;
         offer.deadline = deadline;
         offer.bump = ctx.bumps.intrachain_offer;
+        offer.status.transition(OfferStatus::IdentityPending)?;
+
+        // Escrow token A in the offer-owned associated token account,
+        // transfer-hook accounts are forwarded from remaining_accounts
+        require_escrowable_mint(&ctx.accounts.token_a_mint.to_account_info())?;
+        spl_token_2022::onchain::invoke_transfer_checked(
+            &ctx.accounts.token_program.key(),
+            ctx.accounts.seller_token_account.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.offer_token_vault.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            ctx.remaining_accounts,
+            token_a_offered_amount,
+            ctx.accounts.token_a_mint.decimals,
+            &[],
+        )?;
+
+        // Quote what the vault can hand the buyer, net of the inbound and outbound transfer fee
+        let token_a_mint = ctx.accounts.token_a_mint.to_account_info();
+        ctx.accounts.offer_token_vault.reload()?;
+        offer.token_a_offered_amount = deliverable_amount(&token_a_mint, ctx.accounts.offer_token_vault.amount)?;
+        require!(min_fill_amount <= offer.token_a_offered_amount, ErrorCode::InvalidFillAmount);
+        // The outbound fee is reserved once for the whole vault, per-slice fees round up
+        // past it, so a fee-bearing mint settles in a single fill
+        offer.min_fill_amount = if transfer_fee_config(&token_a_mint)?.is_some() {
+            offer.token_a_offered_amount
+        } else {
+            min_fill_amount
+        };
+        offer.remaining_amount = offer.token_a_offered_amount;
 
         // Only pass encrypted inputs expected by the circuit
-        let args = vec![
+        let mut args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_seller_identity_hash),
         ];
+        args.extend(identity_arguments(&ciphertext_seller_identity_hash));
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![DepositSellerSplCallback::callback_ix(&[])],
+            vec![DepositSellerSplCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.key(),
+                is_writable: true,
+            }])],
         )?;
 
         Ok(())
@@ -324,29 +921,170 @@ pub mod confidential_cross_chain_exchange {
 
     pub fn finalize_intrachain_offer(
         ctx: Context<FinalizeIntrachainOffer>,
-        // Public business field
+        // Public business fields
         id: u64,
+        fill_amount: u64,
         // Confidential buyer identity
-        ciphertext_buyer_identity_hash: [u8; 32],
+        ciphertext_buyer_identity_hash: [[u8; 32]; 4],
         // Arcium handshake
         pub_key: [u8; 32],
         nonce: u128,
         computation_offset: u64,
     ) -> Result<()> {
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        // Circuit expects only encrypted buyer identity (plus handshake)
-        let args = vec![
+
+        // The callback settles the trade, so the seller leg must already be escrowed
+        let offer = &ctx.accounts.intrachain_offer;
+        offer.status.require_open()?;
+        require_before_deadline(offer.deadline)?;
+        require!(
+            fill_amount > 0 && fill_amount <= offer.remaining_amount,
+            ErrorCode::InvalidFillAmount
+        );
+        // Only the last slice may be smaller than the maker's minimum
+        require!(
+            fill_amount >= offer.min_fill_amount || fill_amount == offer.remaining_amount,
+            ErrorCode::FillBelowMinimum
+        );
+        // Upper bound on the pro-rata price, the callback charges the exact slice
+        let token_b_amount = pro_rata_ceil(fill_amount, offer.token_b_wanted_amount, offer.token_a_offered_amount)?;
+        if offer.token_a_mint == Pubkey::default() {
+            require!(
+                escrowed_lamports(&ctx.accounts.seller_vault.to_account_info())? >= fill_amount,
+                ErrorCode::InsufficientEscrow
+            );
+        } else {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            SplLeg::new(
+                ctx.accounts.token_a_mint.as_deref(),
+                ctx.accounts.offer_token_vault.as_deref(),
+                ctx.accounts.buyer_token_a_account.as_deref(),
+                offer.token_a_mint,
+                get_associated_token_address_with_program_id(&offer.key(), &offer.token_a_mint, &token_program.key()),
+                ctx.accounts.payer.key(),
+                fill_amount,
+            )?;
+        }
+        // The taker funds its slice in this same instruction, topping up any earlier deposit
+        if offer.is_taker_native {
+            let buyer_vault = ctx.accounts.buyer_vault.as_ref().ok_or(ErrorCode::InvalidBuyerVault)?;
+            let shortfall = token_b_amount.saturating_sub(escrowed_lamports(&buyer_vault.to_account_info())?);
+            if shortfall > 0 {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: buyer_vault.to_account_info(),
+                        },
+                    ),
+                    shortfall,
+                )?;
+            }
+        } else {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            let source = ctx.accounts.buyer_token_b_account.as_deref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            let leg = SplLeg::new(
+                ctx.accounts.token_b_mint.as_deref(),
+                ctx.accounts.buyer_token_vault.as_deref(),
+                ctx.accounts.seller_token_b_account.as_deref(),
+                offer.token_b_mint,
+                buyer_token_vault_address(&offer.key(), &ctx.accounts.payer.key()),
+                ctx.accounts.maker.key(),
+                0,
+            )?;
+            let shortfall = token_b_amount.saturating_sub(leg.vault.amount);
+            if shortfall > 0 {
+                // Gross up so the vault nets the wanted amount after any transfer fee
+                let fee = inverse_transfer_fee(&leg.mint.to_account_info(), shortfall)?;
+                spl_token_2022::onchain::invoke_transfer_checked(
+                    &token_program.key(),
+                    source.to_account_info(),
+                    leg.mint.to_account_info(),
+                    leg.vault.to_account_info(),
+                    ctx.accounts.payer.to_account_info(),
+                    ctx.remaining_accounts,
+                    shortfall.checked_add(fee).ok_or(ErrorCode::MathOverflow)?,
+                    leg.mint.decimals,
+                    &[],
+                )?;
+            }
+        }
+
+        // Buyer's encrypted claim (plus handshake), then the stored MXE-encrypted seller identity
+        let mut args = vec![
             Argument::ArcisPubkey(pub_key),
             Argument::PlaintextU128(nonce),
-            Argument::EncryptedU64(ciphertext_buyer_identity_hash),
         ];
+        args.extend(identity_arguments(&ciphertext_buyer_identity_hash));
+        args.extend(offer.seller_identity.to_arguments());
+
+        // The requested slice travels to the callback in a per-taker account
+        let pending_fill = &mut ctx.accounts.pending_fill;
+        pending_fill.token_a_amount = fill_amount;
+        pending_fill.bump = ctx.bumps.pending_fill;
+
+        // Accounts the callback needs to release both escrows
+        let callback_accounts = [
+            CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.maker.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.payer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.intrachain_offer.payer,
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.pending_fill.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.seller_vault.key(),
+                is_writable: true,
+            },
+            optional_callback_account(ctx.accounts.buyer_vault.as_ref().map(|a| a.key()), true),
+            optional_callback_account(ctx.accounts.token_program.as_ref().map(|a| a.key()), false),
+            optional_callback_account(ctx.accounts.token_a_mint.as_ref().map(|a| a.key()), false),
+            optional_callback_account(ctx.accounts.offer_token_vault.as_ref().map(|a| a.key()), true),
+            optional_callback_account(ctx.accounts.buyer_token_a_account.as_ref().map(|a| a.key()), true),
+            optional_callback_account(ctx.accounts.token_b_mint.as_ref().map(|a| a.key()), false),
+            optional_callback_account(ctx.accounts.buyer_token_vault.as_ref().map(|a| a.key()), true),
+            optional_callback_account(ctx.accounts.seller_token_b_account.as_ref().map(|a| a.key()), true),
+            optional_callback_account(ctx.accounts.buyer_token_b_account.as_ref().map(|a| a.key()), true),
+            CallbackAccount {
+                pubkey: ctx.accounts.escrow_authority.key(),
+                is_writable: false,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.exchange_config.key(),
+                is_writable: false,
+            },
+            optional_callback_account(ctx.accounts.token_a_treasury.as_ref().map(|a| a.key()), true),
+            optional_callback_account(ctx.accounts.token_b_treasury.as_ref().map(|a| a.key()), true),
+        ];
+        // Transfer-hook accounts ride along after the fixed list
+        let callback_accounts: Vec<CallbackAccount> = callback_accounts
+            .into_iter()
+            .chain(ctx.remaining_accounts.iter().map(|a| CallbackAccount {
+                pubkey: a.key(),
+                is_writable: a.is_writable,
+            }))
+            .collect();
 
         queue_computation(
             ctx.accounts,
             computation_offset,
             args,
             None,
-            vec![FinalizeIntrachainOfferCallback::callback_ix(&[])],
+            vec![FinalizeIntrachainOfferCallback::callback_ix(&callback_accounts)],
         )?;
 
         Ok(())
@@ -354,70 +1092,361 @@ pub mod confidential_cross_chain_exchange {
 
     // === ASSET TRANSFER INSTRUCTIONS ===
     
-    /// Execute atomic swap after both identities verified via MPC
-    pub fn execute_intrachain_swap(
-        ctx: Context<ExecuteIntrachainSwap>,
+    /// Taker-driven settlement of a matched interchain offer: the verified buyer pays
+    /// token B straight to the seller and receives token A from the PDA-owned escrow.
+    /// Relayed offers keep token A on the origin chain, so only token B moves here.
+    /// Completing the Solana leg posts a sequenced outbound message for the origin chain.
+    pub fn execute_interchain_swap(
+        ctx: Context<ExecuteInterchainSwap>,
         offer_id: u64,
+        // Taker's origin-chain payout address, sealed for the relayer
+        encrypted_taker_destination: [u8; 32],
+        destination_nonce: u128,
     ) -> Result<()> {
-        let offer = &ctx.accounts.intrachain_offer;
-        
-        msg!("🔄 Executing intrachain swap for offer ID: {}", offer_id);
-        msg!("  Seller vault → Buyer: {} lamports (token A)", offer.token_a_offered_amount);
-        msg!("  Buyer vault → Seller: {} lamports (token B)", offer.token_b_wanted_amount);
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status.transition(OfferStatus::Settled)?;
+        require_before_deadline(offer.deadline)?;
+
+        msg!("🔄 Executing interchain swap for offer ID: {}", offer_id);
+        if offer.origin_escrowed {
+            msg!("  Origin chain → Buyer: {} (token A, released by the outbound message)", offer.token_a_offered_amount);
+        } else {
+            msg!("  Seller vault → Buyer: {} (token A)", offer.token_a_offered_amount);
+        }
+        msg!("  Buyer → Seller: {} (token B)", offer.token_b_wanted_amount);
+
+        let seller_key = ctx.accounts.maker.key();
+        let buyer_key = ctx.accounts.buyer.key();
+
+        // Taker fee comes out of token A, maker fee out of token B. Token A of a relayed
+        // offer never reaches Solana, so there is nothing to take the taker fee from
+        let config = &ctx.accounts.exchange_config;
+        let taker_fee = if offer.origin_escrowed {
+            0
+        } else {
+            fee_amount(offer.token_a_offered_amount, config.fees.interchain_taker_bps)?
+        };
+        let maker_fee = fee_amount(offer.token_b_wanted_amount, config.fees.interchain_maker_bps)?;
+        let token_a_treasury = config.fee_treasury(offer.token_a_mint, ctx.accounts.token_a_treasury.as_ref(), taker_fee)?;
+        let token_b_treasury = config.fee_treasury(offer.token_b_mint, ctx.accounts.token_b_treasury.as_ref(), maker_fee)?;
+        let token_a_to_buyer = offer.token_a_offered_amount - taker_fee;
+        let token_b_to_seller = offer.token_b_wanted_amount - maker_fee;
 
         // Transfer token A from seller vault to buyer
-        **ctx.accounts.seller_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_a_offered_amount;
-        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += offer.token_a_offered_amount;
+        if offer.origin_escrowed {
+            // Released to the taker on the origin chain once the settlement message lands
+        } else if offer.token_a_mint == Pubkey::default() {
+            transfer_lamports(
+                &ctx.accounts.seller_vault.to_account_info(),
+                &ctx.accounts.buyer.to_account_info(),
+                token_a_to_buyer,
+            )?;
+            if let Some(treasury) = &token_a_treasury {
+                transfer_lamports(&ctx.accounts.seller_vault.to_account_info(), treasury, taker_fee)?;
+            }
+        } else {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            let leg = SplLeg::new(
+                ctx.accounts.token_a_mint.as_deref(),
+                ctx.accounts.offer_token_vault.as_deref(),
+                ctx.accounts.buyer_token_a_account.as_deref(),
+                offer.token_a_mint,
+                get_associated_token_address_with_program_id(&offer.key(), &offer.token_a_mint, &token_program.key()),
+                buyer_key,
+                offer.token_a_offered_amount,
+            )?;
+            let id_bytes = offer.id.to_le_bytes();
+            let bump = [offer.bump];
+            let offer_seeds: &[&[u8]] = &[b"InterChainoffer", seller_key.as_ref(), &id_bytes, &bump];
+            leg.transfer_net(
+                token_program,
+                offer.to_account_info(),
+                offer_seeds,
+                ctx.remaining_accounts,
+                token_a_to_buyer,
+            )?;
+            if let Some(treasury) = &token_a_treasury {
+                leg.transfer_to(
+                    token_program,
+                    treasury.clone(),
+                    offer.to_account_info(),
+                    offer_seeds,
+                    ctx.remaining_accounts,
+                    taker_fee,
+                )?;
+            }
+        }
+
+        // The buyer signs, so token B goes straight to the seller without an escrow
+        if offer.is_taker_native {
+            let pay = |to, amount| {
+                anchor_lang::system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        anchor_lang::system_program::Transfer {
+                            from: ctx.accounts.buyer.to_account_info(),
+                            to,
+                        },
+                    ),
+                    amount,
+                )
+            };
+            pay(ctx.accounts.maker.to_account_info(), token_b_to_seller)?;
+            if let Some(treasury) = &token_b_treasury {
+                pay(treasury.clone(), maker_fee)?;
+            }
+        } else {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            let (Some(mint), Some(source), Some(destination)) = (
+                ctx.accounts.token_b_mint.as_deref(),
+                ctx.accounts.buyer_token_b_account.as_deref(),
+                ctx.accounts.seller_token_b_account.as_deref(),
+            ) else {
+                return err!(ErrorCode::MissingTokenAccounts);
+            };
+            require_keys_eq!(mint.key(), offer.token_b_mint, ErrorCode::InvalidMint);
+            require_keys_eq!(destination.mint, offer.token_b_mint, ErrorCode::InvalidMint);
+            require_keys_eq!(destination.owner, seller_key, ErrorCode::InvalidTokenRecipient);
+            spl_token_2022::onchain::invoke_transfer_checked(
+                &token_program.key(),
+                source.to_account_info(),
+                mint.to_account_info(),
+                destination.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.remaining_accounts,
+                token_b_to_seller,
+                mint.decimals,
+                &[],
+            )?;
+            if let Some(treasury) = &token_b_treasury {
+                spl_token_2022::onchain::invoke_transfer_checked(
+                    &token_program.key(),
+                    source.to_account_info(),
+                    mint.to_account_info(),
+                    treasury.clone(),
+                    ctx.accounts.buyer.to_account_info(),
+                    ctx.remaining_accounts,
+                    maker_fee,
+                    mint.decimals,
+                    &[],
+                )?;
+            }
+        }
 
-        // Transfer token B from buyer vault to seller
-        **ctx.accounts.buyer_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_b_wanted_amount;
-        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += offer.token_b_wanted_amount;
+        emit_fee(offer.id, offer.token_a_mint, token_a_treasury.as_ref(), taker_fee);
+        emit_fee(offer.id, offer.token_b_mint, token_b_treasury.as_ref(), maker_fee);
 
-        msg!("✅ Swap completed successfully");
+        // Tell the origin chain the Solana leg is done so it can release token A there
+        let emitter = &mut ctx.accounts.outbound_emitter;
+        emitter.bump = ctx.bumps.outbound_emitter;
+        let route = &ctx.accounts.bridge_route;
+        let settlement = SettlementMessage {
+            offer_id: offer.id,
+            destination_chain: route.bridge_chain_id,
+            token_a_amount: offer.token_a_offered_amount,
+            token_b_amount: offer.token_b_wanted_amount,
+            encrypted_taker_destination,
+            destination_nonce,
+            emitter: emitter.key(),
+            sequence: emitter.sequence,
+        };
+        let message = &mut ctx.accounts.outbound_message;
+        message.emitter = settlement.emitter;
+        message.sequence = settlement.sequence;
+        message.bridge = route.bridge;
+        message.offer_id = offer.id;
+        message.payload = route.bridge.encode(&settlement);
+        message.posted_at = Clock::get()?.unix_timestamp;
+        emitter.sequence = emitter.sequence.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(OutboundMessageEvent {
+            emitter: message.emitter,
+            sequence: message.sequence,
+            bridge: message.bridge,
+            payload: message.payload.clone(),
+        });
+        msg!("✅ Swap completed successfully, outbound message {} posted", message.sequence);
         Ok(())
     }
 
-    /// Execute atomic swap for interchain offers after identities verified
-    pub fn execute_interchain_swap(
-        ctx: Context<ExecuteInterchainSwap>,
+    /// HTLC settlement: the MPC-verified taker presents the preimage of the hashlock before
+    /// the timelock and receives token A. The maker chose the hashlock and holds the secret,
+    /// the taker only learns it once the maker reveals it to claim token B from the taker's
+    /// lock on the origin chain. That lock has to expire before `timelock` so the taker still
+    /// has time to claim here after the secret goes public.
+    pub fn claim_htlc(
+        ctx: Context<ClaimHtlc>,
         offer_id: u64,
+        preimage: [u8; 32],
     ) -> Result<()> {
-        let offer = &ctx.accounts.interchain_offer;
-        
-        msg!("🔄 Executing interchain swap for offer ID: {}", offer_id);
-        msg!("  Seller vault → Buyer: {} lamports (token A)", offer.token_a_offered_amount);
-        msg!("  Buyer vault → Seller: {} lamports (token B)", offer.token_b_wanted_amount);
+        let offer = &mut ctx.accounts.interchain_offer;
+        let terms = offer.htlc.ok_or(ErrorCode::NotHtlcOffer)?;
+        require!(hash::hash(&preimage).to_bytes() == terms.hashlock, ErrorCode::InvalidPreimage);
+        require_before_deadline(terms.timelock)?;
+        offer.status.transition(OfferStatus::Settled)?;
 
-        // Transfer token A from seller vault to buyer
-        **ctx.accounts.seller_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_a_offered_amount;
-        **ctx.accounts.buyer.to_account_info().try_borrow_mut_lamports()? += offer.token_a_offered_amount;
+        let seller_key = ctx.accounts.maker.key();
+        let buyer_key = ctx.accounts.buyer.key();
+        let token_program = &ctx.accounts.token_program;
+        let expected_vault = get_associated_token_address_with_program_id(&offer.key(), &offer.token_a_mint, &token_program.key());
+        let id_bytes = offer_id.to_le_bytes();
+        let bump = [offer.bump];
+        let offer_seeds: &[&[u8]] = &[b"InterChainoffer", seller_key.as_ref(), &id_bytes, &bump];
 
-        // Transfer token B from buyer vault to seller
-        **ctx.accounts.buyer_vault.to_account_info().try_borrow_mut_lamports()? -= offer.token_b_wanted_amount;
-        **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += offer.token_b_wanted_amount;
+        // Taker fee comes out of token A, the rest of the vault goes to the taker
+        let config = &ctx.accounts.exchange_config;
+        let taker_fee = fee_amount(offer.token_a_offered_amount, config.fees.interchain_taker_bps)?;
+        let token_a_treasury = config.fee_treasury(offer.token_a_mint, ctx.accounts.token_a_treasury.as_ref(), taker_fee)?;
+        if let Some(treasury) = &token_a_treasury {
+            SplLeg::new(
+                Some(&*ctx.accounts.token_a_mint),
+                Some(&*ctx.accounts.offer_token_vault),
+                Some(&*ctx.accounts.buyer_token_a_account),
+                offer.token_a_mint,
+                expected_vault,
+                buyer_key,
+                taker_fee,
+            )?
+            .transfer_to(
+                token_program,
+                treasury.clone(),
+                offer.to_account_info(),
+                offer_seeds,
+                ctx.remaining_accounts,
+                taker_fee,
+            )?;
+            ctx.accounts.offer_token_vault.reload()?;
+        }
+        let claimed = SplLeg::new(
+            Some(&*ctx.accounts.token_a_mint),
+            Some(&*ctx.accounts.offer_token_vault),
+            Some(&*ctx.accounts.buyer_token_a_account),
+            offer.token_a_mint,
+            expected_vault,
+            buyer_key,
+            0,
+        )?
+        .drain(
+            token_program,
+            ctx.accounts.maker.to_account_info(),
+            offer.to_account_info(),
+            offer_seeds,
+            ctx.remaining_accounts,
+        )?;
+        emit_fee(offer.id, offer.token_a_mint, token_a_treasury.as_ref(), taker_fee);
 
-        msg!("✅ Swap completed successfully");
+        msg!("🔓 HTLC offer {} claimed, {} tokens released", offer_id, claimed);
+        emit!(HtlcClaimedEvent {
+            offer_id,
+            taker: buyer_key,
+            preimage,
+            token_a_amount: claimed,
+        });
         Ok(())
     }
 
-    /// Deposit seller assets into escrow vault
-    pub fn deposit_to_seller_vault(
-        ctx: Context<DepositToSellerVault>,
+    /// Permissionless crank: return token A of an HTLC offer to the maker once the timelock passed
+    pub fn reclaim_htlc(
+        ctx: Context<ReclaimHtlc>,
         offer_id: u64,
-        amount: u64,
     ) -> Result<()> {
-        msg!("💰 Seller depositing {} lamports to vault", amount);
-        
-        // Transfer from seller to vault
-        anchor_lang::system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                anchor_lang::system_program::Transfer {
-                    from: ctx.accounts.seller.to_account_info(),
-                    to: ctx.accounts.seller_vault.to_account_info(),
-                },
-            ),
-            amount,
+        let offer = &mut ctx.accounts.interchain_offer;
+        let terms = offer.htlc.ok_or(ErrorCode::NotHtlcOffer)?;
+        require_after_deadline(terms.timelock)?;
+        offer.status.transition(OfferStatus::Expired)?;
+
+        let seller_key = ctx.accounts.maker.key();
+        let token_program = &ctx.accounts.token_program;
+        let id_bytes = offer_id.to_le_bytes();
+        let bump = [offer.bump];
+        let offer_seeds: &[&[u8]] = &[b"InterChainoffer", seller_key.as_ref(), &id_bytes, &bump];
+        let refunded_tokens = SplLeg::new(
+            Some(&*ctx.accounts.token_a_mint),
+            Some(&*ctx.accounts.offer_token_vault),
+            Some(&*ctx.accounts.seller_token_account),
+            offer.token_a_mint,
+            get_associated_token_address_with_program_id(&offer.key(), &offer.token_a_mint, &token_program.key()),
+            seller_key,
+            0,
+        )?
+        .drain(
+            token_program,
+            ctx.accounts.maker.to_account_info(),
+            offer.to_account_info(),
+            offer_seeds,
+            ctx.remaining_accounts,
+        )?;
+
+        msg!("⌛ HTLC offer {} reclaimed, refunded {} tokens", offer_id, refunded_tokens);
+        emit!(HtlcReclaimedEvent {
+            offer_id,
+            refunded_tokens,
+        });
+        Ok(())
+    }
+
+    /// Freeze a matched interchain offer when the origin-chain leg went wrong, maker or taker
+    /// only. Settlement and expiry wait for `resolve_interchain_dispute`
+    pub fn dispute_interchain_offer(
+        ctx: Context<DisputeInterchainOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        let caller = ctx.accounts.caller.key();
+        let offer = &mut ctx.accounts.interchain_offer;
+        require!(
+            caller == offer.maker || offer.taker == Some(caller),
+            ErrorCode::NotOfferParty
+        );
+        require_before_deadline(offer.deadline)?;
+        offer.status.transition(OfferStatus::Disputed)?;
+
+        msg!("⚖️ Interchain offer {} disputed by {}", offer_id, caller);
+        emit!(OfferDisputedEvent {
+            offer_id,
+            raised_by: caller,
+        });
+        Ok(())
+    }
+
+    /// Settle a dispute, authority only. Releasing returns the offer to `Matched` so the taker
+    /// can still execute it before the deadline, otherwise it is expired and anyone may crank
+    /// the refund with `expire_interchain_offer` straight away
+    pub fn resolve_interchain_dispute(
+        ctx: Context<ResolveInterchainDispute>,
+        offer_id: u64,
+        release: bool,
+    ) -> Result<()> {
+        let offer = &mut ctx.accounts.interchain_offer;
+        require!(offer.status == OfferStatus::Disputed, ErrorCode::OfferNotDisputed);
+        offer.status.transition(if release {
+            OfferStatus::Matched
+        } else {
+            OfferStatus::Expired
+        })?;
+
+        msg!("⚖️ Dispute on interchain offer {} resolved, released: {}", offer_id, release);
+        emit!(OfferDisputeResolvedEvent { offer_id, released: release });
+        Ok(())
+    }
+
+    /// Deposit seller assets into escrow vault
+    pub fn deposit_to_seller_vault(
+        ctx: Context<DepositToSellerVault>,
+        offer_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        msg!("💰 Seller depositing {} lamports to vault", amount);
+        
+        // Transfer from seller to vault
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.seller.to_account_info(),
+                    to: ctx.accounts.seller_vault.to_account_info(),
+                },
+            ),
+            amount,
         )?;
 
         msg!("✅ Deposit successful");
@@ -448,1096 +1477,6782 @@ pub mod confidential_cross_chain_exchange {
         Ok(())
     }
 
-
-    #[arcium_callback(encrypted_ix = "add_together")]
-    pub fn add_together_callback(
-        ctx: Context<AddTogetherCallback>,
-        output: ComputationOutputs<AddTogetherOutput>,
+    /// Deposit buyer SPL tokens into the escrow token vault
+    pub fn deposit_to_buyer_token_vault(
+        ctx: Context<DepositToBuyerTokenVault>,
+        offer_id: u64,
+        amount: u64,
     ) -> Result<()> {
-        let o = match output {
-            ComputationOutputs::Success(AddTogetherOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+        msg!("💰 Buyer depositing {} tokens to vault", amount);
+
+        // Transfer from buyer token account to vault, the buyer covers any
+        // transfer fee so the vault must end up holding `token_b_wanted_amount`
+        require_escrowable_mint(&ctx.accounts.token_b_mint.to_account_info())?;
+        spl_token_2022::onchain::invoke_transfer_checked(
+            &ctx.accounts.token_program.key(),
+            ctx.accounts.buyer_token_account.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.buyer_token_vault.to_account_info(),
+            ctx.accounts.buyer.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.token_b_mint.decimals,
+            &[],
+        )?;
+
+        msg!("✅ Deposit successful");
+        Ok(())
+    }
+
+    /// Return a buyer's escrows for an offer that no longer exists, e.g. a fill still in
+    /// flight when the offer was fully taken, cancelled or expired
+    pub fn withdraw_buyer_vault(ctx: Context<WithdrawBuyerVault>) -> Result<()> {
+        let offer_key = ctx.accounts.offer.key();
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let refunded = drain_vault(&ctx.accounts.buyer_vault.to_account_info(), &buyer)?;
+
+        let refunded_tokens = match ctx.accounts.buyer_token_vault.as_deref() {
+            Some(vault) => {
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+                let leg = SplLeg::new(
+                    ctx.accounts.token_b_mint.as_deref(),
+                    Some(vault),
+                    ctx.accounts.buyer_token_b_account.as_deref(),
+                    vault.mint,
+                    buyer_token_vault_address(&offer_key, &buyer.key()),
+                    buyer.key(),
+                    0,
+                )?;
+                let bump = [ctx.bumps.escrow_authority];
+                leg.drain(
+                    token_program,
+                    buyer.clone(),
+                    ctx.accounts.escrow_authority.to_account_info(),
+                    &[b"escrow_authority", &bump],
+                    ctx.remaining_accounts,
+                )?
+            }
+            None => 0,
         };
 
-        emit!(SumEvent {
-            sum: o.ciphertexts[0],
-            nonce: o.nonce.to_le_bytes(),
+        msg!("↩️ Buyer withdrew {} lamports and {} tokens escrowed for a closed offer", refunded, refunded_tokens);
+        emit!(BuyerVaultWithdrawnEvent {
+            offer: offer_key,
+            buyer: buyer.key(),
+            refunded,
+            refunded_tokens,
         });
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "relay_offer_clone")]
-    pub fn relay_offer_clone_callback(
-        ctx: Context<RelayOfferCloneCallback>,
-        output: ComputationOutputs<RelayOfferCloneOutput>,
+    /// Refund the seller vault and close an intrachain offer that has not been taken
+    pub fn cancel_intrachain_offer(
+        ctx: Context<RefundIntrachainOffer>,
+        offer_id: u64,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(RelayOfferCloneOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        require_keys_eq!(ctx.accounts.caller.key(), ctx.accounts.maker.key(), ErrorCode::NotOfferMaker);
+        ctx.accounts.intrachain_offer.status.transition(OfferStatus::Cancelled)?;
 
-        // Public data already stored in PDA during relay_offer_clone call
-        // Just emit acknowledgment
-        emit!(RelayOfferClonedEvent {
-            acknowledged: 1,
-        });
+        let refunds = ctx.accounts.refund(offer_id, ctx.bumps.escrow_authority, ctx.remaining_accounts, 0)?;
+
+        msg!(
+            "🚫 Intrachain offer {} cancelled, refunded {} lamports and {} tokens",
+            offer_id,
+            refunds.seller_refund,
+            refunds.seller_token_refund
+        );
+        emit!(refunds.cancelled(offer_id));
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "confidential_deposit_native")]
-    pub fn confidential_deposit_native_callback(
-        ctx: Context<ConfidentialDepositNativeCallback>,
-        output: ComputationOutputs<ConfidentialDepositNativeOutput>,
+    /// Refund the seller vault and close an interchain offer that has not been taken
+    pub fn cancel_interchain_offer(
+        ctx: Context<RefundInterchainOffer>,
+        offer_id: u64,
     ) -> Result<()> {
-        let o = match output {
-            ComputationOutputs::Success(ConfidentialDepositNativeOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        require_keys_eq!(ctx.accounts.caller.key(), ctx.accounts.maker.key(), ErrorCode::NotOfferMaker);
+        ctx.accounts.interchain_offer.status.transition(OfferStatus::Cancelled)?;
 
-        emit!(ConfidentialDepositNativeEvent {
-            processed_amount: o.ciphertexts[0],
-            nonce: o.nonce.to_le_bytes(),
-        });
+        let refunds = ctx.accounts.refund(offer_id, ctx.bumps.escrow_authority, ctx.remaining_accounts, 0)?;
+
+        msg!(
+            "🚫 Interchain offer {} cancelled, refunded {} lamports and {} tokens",
+            offer_id,
+            refunds.seller_refund,
+            refunds.seller_token_refund
+        );
+        emit!(refunds.cancelled(offer_id));
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "interchain_origin_evm_deposit_seller_spl")]
-    pub fn interchain_origin_evm_deposit_seller_spl_callback(
-        ctx: Context<InterchainOriginEvmDepositSellerSplCallback>,
-        output: ComputationOutputs<InterchainOriginEvmDepositSellerSplOutput>,
+    /// Permissionless crank: refund both vaults of an intrachain offer past its deadline
+    pub fn expire_intrachain_offer(
+        ctx: Context<RefundIntrachainOffer>,
+        offer_id: u64,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(InterchainOriginEvmDepositSellerSplOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        let offer = &mut ctx.accounts.intrachain_offer;
+        require_after_deadline(offer.deadline)?;
+        offer.status.transition(OfferStatus::Expired)?;
 
-        // Public data already stored in PDA during interchain_origin_evm_deposit_seller_spl call
-        emit!(InterchainOriginEvmDepositSellerSplEvent {
-            acknowledged: 1,
-        });
+        let refunds = ctx.accounts.refund(
+            offer_id,
+            ctx.bumps.escrow_authority,
+            ctx.remaining_accounts,
+            EXPIRY_CRANK_BOUNTY,
+        )?;
+
+        msg!("⌛ Intrachain offer {} expired", offer_id);
+        emit!(refunds.expired(offer_id));
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "finalize_interchain_origin_evm_offer")]
-    pub fn finalize_interchain_origin_evm_offer_callback(
-        ctx: Context<FinalizeInterchainOriginEvmOfferCallback>,
-        output: ComputationOutputs<FinalizeInterchainOriginEvmOfferOutput>,
+    /// Permissionless crank: refund both vaults of an interchain offer past its deadline, or of
+    /// one whose dispute was resolved for the maker
+    pub fn expire_interchain_offer(
+        ctx: Context<RefundInterchainOffer>,
+        offer_id: u64,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(FinalizeInterchainOriginEvmOfferOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        let offer = &mut ctx.accounts.interchain_offer;
+        if offer.status != OfferStatus::Expired {
+            // Only the authority ends a dispute
+            require!(offer.status != OfferStatus::Disputed, ErrorCode::OfferDisputed);
+            require_after_deadline(offer.deadline)?;
+            offer.status.transition(OfferStatus::Expired)?;
+        }
 
-        // TODO: Asset transfers require vault accounts to be passed to callback
-        // For now, just emit acknowledgment event
-        // In production, you would:
-        // 1. Pass seller/buyer vault accounts to this callback
-        // 2. Deserialize the interchain_offer PDA to read amounts
-        // 3. Execute SOL or SPL token transfers based on is_taker_native flag
-        
-        msg!("✅ Finalize interchain offer callback executed - identity verified via MPC");
+        let refunds = ctx.accounts.refund(
+            offer_id,
+            ctx.bumps.escrow_authority,
+            ctx.remaining_accounts,
+            EXPIRY_CRANK_BOUNTY,
+        )?;
 
-        emit!(FinalizeInterchainOriginEvmOfferEvent {
-            acknowledged: 1,
-        });
+        msg!("⌛ Interchain offer {} expired", offer_id);
+        emit!(refunds.expired(offer_id));
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "deposit_seller_native")]
-    pub fn deposit_seller_native_callback(
-        ctx: Context<DepositSellerNativeCallback>,
-        output: ComputationOutputs<DepositSellerNativeOutput>,
+    // === CONFIDENTIAL OFFERS ===
+    // Amounts never appear in plaintext, the MPC match reveals only whether a bid crossed
+    // the ask. Both legs are funded from the parties' confidential balances and change
+    // hands inside MPC, so no per-offer amount is ever escrowed in the clear.
+
+    /// Open an offer whose token A and token B amounts are encrypted to the MXE
+    pub fn create_confidential_offer(
+        ctx: Context<CreateConfidentialOffer>,
+        // Public business fields
+        id: u64,
+        token_a_mint: Pubkey,
+        token_b_mint: Pubkey,
+        deadline: i64,
+        // Confidential ask, token A offered then token B wanted
+        ciphertext_terms: [[u8; 32]; 2],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(DepositSellerNativeOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        require_keys_neq!(token_a_mint, token_b_mint, ErrorCode::SameMint);
+
+        let offer = &mut ctx.accounts.confidential_offer;
+        offer.id = id;
+        offer.maker = ctx.accounts.maker.key();
+        offer.payer = ctx.accounts.payer.key();
+        offer.token_a_mint = token_a_mint;
+        offer.token_b_mint = token_b_mint;
+        require_before_deadline(deadline)?;
+        offer.deadline = deadline;
+        // Match results are re-encrypted to the key the maker submitted the ask with
+        offer.maker_encryption_key = pub_key;
+        offer.bump = ctx.bumps.confidential_offer;
+        offer.status.transition(OfferStatus::IdentityPending)?;
+
+        // Token B is credited here on settlement, opened now if the maker never held any
+        let token_b_balance = &mut ctx.accounts.maker_token_b_balance;
+        token_b_balance.owner = ctx.accounts.maker.key();
+        token_b_balance.mint = token_b_mint;
+        token_b_balance.bump = ctx.bumps.maker_token_b_balance;
+
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+        ];
+        args.extend(ciphertext_terms.iter().map(|c| Argument::EncryptedU64(*c)));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CreateConfidentialOfferCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.confidential_offer.key(),
+                is_writable: true,
+            }])],
+        )?;
 
-        // Public data already stored in PDA during deposit_seller_native call
-        emit!(DepositSellerNativeEvent {
-            acknowledged: 1,
-        });
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "deposit_seller_spl")]
-    pub fn deposit_seller_spl_callback(
-        ctx: Context<DepositSellerSplCallback>,
-        output: ComputationOutputs<DepositSellerSplOutput>,
+    /// Check an encrypted bid against the offer's encrypted ask and, if it crosses and both
+    /// balances cover it, swap the slice between the parties' confidential balances.
+    /// The taker posts `MATCH_BOND` with the bid, forfeited to the maker if it does not settle
+    pub fn match_confidential_offer(
+        ctx: Context<MatchConfidentialOffer>,
+        _id: u64,
+        // Confidential bid, token A wanted then the most token B the taker pays
+        ciphertext_bid: [[u8; 32]; 2],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        // Fresh nonce for the maker's copy of the settlement
+        maker_nonce: u128,
+        computation_offset: u64,
     ) -> Result<()> {
-        let _o = match output {
-            ComputationOutputs::Success(DepositSellerSplOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
-        // Public data already stored in PDA during deposit_seller_spl call
-        emit!(DepositSellerSplEvent {
-            acknowledged: 1,
+        let taker = ctx.accounts.payer.key();
+        let offer = &mut ctx.accounts.confidential_offer;
+        offer.status.require_open()?;
+        require_before_deadline(offer.deadline)?;
+        require!(offer.taker.is_none(), ErrorCode::MatchPending);
+        // Held until the callback, which releases it unless the bid settles
+        offer.taker = Some(taker);
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: offer.to_account_info(),
+                },
+            ),
+            MATCH_BOND,
+        )?;
+
+        let token_a_balance = &mut ctx.accounts.taker_token_a_balance;
+        token_a_balance.owner = taker;
+        token_a_balance.mint = offer.token_a_mint;
+        token_a_balance.bump = ctx.bumps.taker_token_a_balance;
+
+        let maker_token_a = ctx.accounts.maker_token_a_balance.balance.ok_or(ErrorCode::NoConfidentialBalance)?;
+        let maker_token_b = ctx.accounts.maker_token_b_balance.balance;
+        let taker_token_a = ctx.accounts.taker_token_a_balance.balance;
+        let taker_token_b = ctx.accounts.taker_token_b_balance.balance.ok_or(ErrorCode::NoConfidentialBalance)?;
+        for balance in [
+            &mut ctx.accounts.maker_token_a_balance,
+            &mut ctx.accounts.maker_token_b_balance,
+            &mut ctx.accounts.taker_token_a_balance,
+            &mut ctx.accounts.taker_token_b_balance,
+        ] {
+            balance.lock(BalanceUpdate::Match)?;
+        }
+
+        let offer = &ctx.accounts.confidential_offer;
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+        ];
+        args.extend(ciphertext_bid.iter().map(|c| Argument::EncryptedU64(*c)));
+        args.extend(offer.terms.to_arguments());
+        args.push(Argument::ArcisPubkey(offer.maker_encryption_key));
+        args.push(Argument::PlaintextU128(maker_nonce));
+        args.extend(EncryptedBalance::arguments(Some(&maker_token_a)));
+        args.extend(EncryptedBalance::arguments(maker_token_b.as_ref()));
+        args.push(Argument::PlaintextBool(maker_token_b.is_none()));
+        args.extend(EncryptedBalance::arguments(taker_token_a.as_ref()));
+        args.push(Argument::PlaintextBool(taker_token_a.is_none()));
+        args.extend(EncryptedBalance::arguments(Some(&taker_token_b)));
+
+        let mut callback_accounts = vec![
+            CallbackAccount {
+                pubkey: ctx.accounts.confidential_offer.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: taker,
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: offer.maker,
+                is_writable: true,
+            },
+        ];
+        callback_accounts.extend(
+            [
+                ctx.accounts.maker_token_a_balance.key(),
+                ctx.accounts.maker_token_b_balance.key(),
+                ctx.accounts.taker_token_a_balance.key(),
+                ctx.accounts.taker_token_b_balance.key(),
+            ]
+            .map(|pubkey| CallbackAccount {
+                pubkey,
+                is_writable: true,
+            }),
+        );
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![MatchConfidentialOfferCallback::callback_ix(&callback_accounts)],
+        )?;
+
+        Ok(())
+    }
+
+    /// Close a confidential offer, cancelling it unless a taker already settled it
+    pub fn close_confidential_offer(
+        ctx: Context<CloseConfidentialOffer>,
+        offer_id: u64,
+    ) -> Result<()> {
+        let offer = &mut ctx.accounts.confidential_offer;
+        if offer.status == OfferStatus::Settled {
+            msg!("✅ Confidential offer {} closed after settlement", offer_id);
+            return Ok(());
+        }
+        // The match callback still needs the offer to release the balances it locked
+        require!(offer.taker.is_none(), ErrorCode::MatchPending);
+        offer.status.transition(OfferStatus::Cancelled)?;
+
+        // Token A never left the maker's confidential balance, there is nothing to refund
+        msg!("🚫 Confidential offer {} cancelled", offer_id);
+        emit!(OfferCancelledEvent {
+            offer_id,
+            refunded: 0,
+            refunded_tokens: 0,
+            buyer_refund: 0,
+            buyer_token_refund: 0,
         });
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "finalize_intrachain_offer")]
-    pub fn finalize_intrachain_offer_callback(
-            ctx: Context<FinalizeIntrachainOfferCallback>,
-        output: ComputationOutputs<FinalizeIntrachainOfferOutput>,
+    // === SEALED-BID AUCTIONS ===
+    // Buyers bid blind during the window, MPC picks the highest bid once it closes and
+    // reveals only the winner and clearing price. The lot is native SOL escrowed up front,
+    // each bid is backed by the auction's fixed collateral tier in the bidder's vault, the
+    // same for every bidder so it says nothing about any one bid. The tier caps the bids,
+    // the winner settles out of that vault and forfeits it if they let the deadline pass.
+
+    /// Escrow the lot and open the bidding window
+    pub fn create_sealed_auction(
+        ctx: Context<CreateSealedAuction>,
+        id: u64,
+        lot_amount: u64,
+        reserve_price: u64,
+        // Collateral every bid escrows, the most any bid can pay
+        bid_collateral: u64,
+        bidding_ends_at: i64,
+        settle_deadline: i64,
     ) -> Result<()> {
-            let _o = match output {
-            ComputationOutputs::Success(FinalizeIntrachainOfferOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+        require!(lot_amount > 0 && bid_collateral > 0, ErrorCode::ZeroAmount);
+        require!(bid_collateral >= reserve_price, ErrorCode::CollateralBelowReserve);
+        require_before_deadline(bidding_ends_at)?;
+        require!(settle_deadline > bidding_ends_at, ErrorCode::InvalidAuctionWindow);
 
-        // TODO: Asset transfers require vault accounts to be passed to callback
-        // For now, just emit acknowledgment event
-        // In production, you would:
-        // 1. Pass seller/buyer vault accounts to this callback
-        // 2. Deserialize the intrachain_offer PDA to read amounts
-        // 3. Execute SOL or SPL token transfers based on is_taker_native flag
-        
-        msg!("✅ Finalize intrachain offer callback executed - identity verified via MPC");
+        let auction = &mut ctx.accounts.sealed_auction;
+        auction.id = id;
+        auction.maker = ctx.accounts.maker.key();
+        auction.payer = ctx.accounts.payer.key();
+        auction.lot_amount = lot_amount;
+        auction.reserve_price = reserve_price;
+        auction.bid_collateral = bid_collateral;
+        auction.bidding_ends_at = bidding_ends_at;
+        auction.settle_deadline = settle_deadline;
+        auction.status = AuctionStatus::Bidding;
+        auction.bump = ctx.bumps.sealed_auction;
 
-        emit!(FinalizeIntrachainOfferEvent {
-                acknowledged: 1,
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.maker.to_account_info(),
+                    to: ctx.accounts.auction_vault.to_account_info(),
+                },
+            ),
+            lot_amount,
+        )?;
+
+        msg!("🔨 Sealed auction {} open for bids until {}", id, bidding_ends_at);
+        emit!(SealedAuctionCreatedEvent {
+            auction_id: id,
+            maker: auction.maker,
+            lot_amount,
+            reserve_price,
+            bid_collateral,
+            bidding_ends_at,
         });
         Ok(())
     }
-}
 
-#[queue_computation_accounts("add_together", payer)]
-#[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct AddTogether<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
+    /// Escrow the auction's collateral tier and add an encrypted bid, one per buyer, while
+    /// the window is open
+    pub fn place_sealed_bid(
+        ctx: Context<PlaceSealedBid>,
+        _id: u64,
+        ciphertext_bid: [u8; 32],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let bidder = ctx.accounts.payer.key();
+        let auction = &mut ctx.accounts.sealed_auction;
+        require!(auction.status == AuctionStatus::Bidding, ErrorCode::InvalidAuctionStatus);
+        require!(Clock::get()?.unix_timestamp <= auction.bidding_ends_at, ErrorCode::BiddingClosed);
+        // Each callback rewrites the whole bid table, so bids land one at a time
+        require!(auction.pending_bidder.is_none(), ErrorCode::AuctionComputationPending);
+        require!(auction.bidders.len() < MAX_AUCTION_BIDS, ErrorCode::AuctionFull);
+        require!(!auction.bidders.contains(&bidder), ErrorCode::AlreadyBid);
+        auction.pending_bidder = Some(bidder);
+        let collateral = auction.bid_collateral;
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.bid_vault.to_account_info(),
+                },
+            ),
+            collateral,
+        )?;
+
+        let auction = &ctx.accounts.sealed_auction;
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(ciphertext_bid),
+        ];
+        args.extend(EncryptedBids::arguments(auction.bids.as_ref()));
+        args.push(Argument::PlaintextBool(auction.bids.is_none()));
+        args.push(Argument::PlaintextU8(auction.bidders.len() as u8));
+        args.push(Argument::PlaintextU64(collateral));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![PlaceSealedBidCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.sealed_auction.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        Ok(())
+    }
+
+    /// Pick the winner once bidding has closed, callable by anyone
+    pub fn resolve_sealed_auction(
+        ctx: Context<ResolveSealedAuction>,
+        _id: u64,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let auction = &mut ctx.accounts.sealed_auction;
+        require!(auction.status == AuctionStatus::Bidding, ErrorCode::InvalidAuctionStatus);
+        require!(Clock::get()?.unix_timestamp > auction.bidding_ends_at, ErrorCode::BiddingOpen);
+        require!(auction.pending_bidder.is_none(), ErrorCode::AuctionComputationPending);
+        let bids = auction.bids.as_ref().ok_or(ErrorCode::NoBids)?;
+
+        let mut args = EncryptedBids::arguments(Some(bids));
+        args.push(Argument::PlaintextU8(auction.bidders.len() as u8));
+        args.push(Argument::PlaintextU64(auction.reserve_price));
+        auction.status = AuctionStatus::Resolving;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ResolveSealedAuctionCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.sealed_auction.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        Ok(())
+    }
+
+    /// Pay the clearing price to the maker out of the winner's collateral, then hand the
+    /// winner the lot and the rest of their collateral
+    pub fn settle_sealed_auction(
+        ctx: Context<SettleSealedAuction>,
+        auction_id: u64,
+    ) -> Result<()> {
+        let auction = &mut ctx.accounts.sealed_auction;
+        require!(auction.status == AuctionStatus::Awarded, ErrorCode::InvalidAuctionStatus);
+        require_before_deadline(auction.settle_deadline)?;
+        auction.status = AuctionStatus::Settled;
+
+        let winner = ctx.accounts.winner.to_account_info();
+        let bid_vault = ctx.accounts.bid_vault.to_account_info();
+        transfer_lamports(&bid_vault, &ctx.accounts.maker.to_account_info(), auction.clearing_price)?;
+        drain_vault(&bid_vault, &winner)?;
+        let lot = drain_vault(&ctx.accounts.auction_vault.to_account_info(), &winner)?;
+
+        msg!("✅ Sealed auction {} settled at {} lamports", auction_id, auction.clearing_price);
+        emit!(SealedAuctionSettledEvent {
+            auction_id,
+            winner: ctx.accounts.winner.key(),
+            clearing_price: auction.clearing_price,
+            lot_amount: lot,
+        });
+        Ok(())
+    }
+
+    /// Return the lot and close an auction that drew no bids, no winner, or no settlement.
+    /// A winner who let the settlement deadline pass forfeits their collateral to the maker
+    pub fn close_sealed_auction(
+        ctx: Context<CloseSealedAuction>,
+        auction_id: u64,
+    ) -> Result<()> {
+        let auction = &ctx.accounts.sealed_auction;
+        let closable = match auction.status {
+            AuctionStatus::Bidding => auction.bidders.is_empty() && auction.pending_bidder.is_none(),
+            AuctionStatus::Unsold => true,
+            // The winner let the settlement deadline pass
+            AuctionStatus::Awarded => Clock::get()?.unix_timestamp > auction.settle_deadline,
+            AuctionStatus::Resolving | AuctionStatus::Settled => false,
+        };
+        require!(closable, ErrorCode::AuctionNotClosable);
+
+        let maker = ctx.accounts.maker.to_account_info();
+        if let (AuctionStatus::Awarded, Some(winner)) = (auction.status, auction.winner) {
+            let bid_vault = ctx.accounts.winner_bid_vault.as_ref().ok_or(ErrorCode::MissingBidVault)?;
+            let slashed = drain_vault(&bid_vault.to_account_info(), &maker)?;
+            msg!("⚖️ Winner of sealed auction {} forfeited {} lamports", auction_id, slashed);
+            emit!(SealedAuctionSlashedEvent {
+                auction_id,
+                winner,
+                slashed,
+            });
+        }
+        let refunded = drain_vault(&ctx.accounts.auction_vault.to_account_info(), &maker)?;
+
+        msg!("🚫 Sealed auction {} closed, refunded {} lamports", auction_id, refunded);
+        emit!(OfferCancelledEvent {
+            offer_id: auction_id,
+            refunded,
+            refunded_tokens: 0,
+            buyer_refund: 0,
+            buyer_token_refund: 0,
+        });
+        Ok(())
+    }
+
+    /// Return a bidder's collateral once it can no longer pay for the lot: a losing or
+    /// aborted bid, an unsold auction, or an auction that is already closed
+    pub fn withdraw_bid_collateral(ctx: Context<WithdrawBidCollateral>) -> Result<()> {
+        let bidder = ctx.accounts.bidder.key();
+        let auction_info = ctx.accounts.sealed_auction.to_account_info();
+        if !auction_info.data_is_empty() {
+            let auction = Account::<SealedAuctionOffer>::try_from(&auction_info)?;
+            let refundable = match auction.status {
+                AuctionStatus::Bidding => {
+                    !auction.bidders.contains(&bidder) && auction.pending_bidder != Some(bidder)
+                }
+                AuctionStatus::Resolving => false,
+                AuctionStatus::Awarded | AuctionStatus::Settled => auction.winner != Some(bidder),
+                AuctionStatus::Unsold => true,
+            };
+            require!(refundable, ErrorCode::CollateralLocked);
+        }
+
+        let refunded = drain_vault(
+            &ctx.accounts.bid_vault.to_account_info(),
+            &ctx.accounts.bidder.to_account_info(),
+        )?;
+
+        msg!("↩️ Returned {} lamports of bid collateral", refunded);
+        emit!(BidCollateralWithdrawnEvent {
+            auction: auction_info.key(),
+            bidder,
+            refunded,
+        });
+        Ok(())
+    }
+
+    // === DARK POOL ORDER BOOKS ===
+    // Limit orders rest encrypted in a per-market book and are crossed inside MPC. Only
+    // fills are revealed, resting side, price and size never leave the MXE. Each order is
+    // backed by public base and quote escrow that fills move between slots, an order the
+    // escrow can't cover rests with size zero.
+
+    /// Open an order book for a market, authority only
+    pub fn create_order_book(ctx: Context<CreateOrderBook>, order_ttl: i64) -> Result<()> {
+        require!(order_ttl > 0, ErrorCode::InvalidOrderTtl);
+        let base_mint = ctx.accounts.base_mint.key();
+        let quote_mint = ctx.accounts.quote_mint.key();
+        require_keys_neq!(base_mint, quote_mint, ErrorCode::SameMint);
+        require_escrowable_mint(&ctx.accounts.base_mint.to_account_info())?;
+        require_escrowable_mint(&ctx.accounts.quote_mint.to_account_info())?;
+
+        let book = &mut ctx.accounts.order_book;
+        book.base_mint = base_mint;
+        book.quote_mint = quote_mint;
+        book.order_ttl = order_ttl;
+        book.bump = ctx.bumps.order_book;
+
+        msg!("📒 Order book opened for {} / {}", base_mint, quote_mint);
+        Ok(())
+    }
+
+    /// Rest an encrypted limit order in the first free slot, escrowing the base and quote
+    /// it can trade. A sell needs base escrow for its size, a buy quote escrow for size
+    /// times its limit price. Escrowing both keeps the side hidden.
+    pub fn add_order(
+        ctx: Context<AddOrder>,
+        base_amount: u64,
+        quote_amount: u64,
+        // Confidential order, side then price then size
+        ciphertext_order: [[u8; 32]; 3],
+        // Arcium handshake
+        pub_key: [u8; 32],
+        nonce: u128,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        require!(base_amount > 0 || quote_amount > 0, ErrorCode::ZeroAmount);
+
+        // Credited net of any transfer fee, so the ledger never exceeds the vaults
+        let mut base_escrow = 0;
+        if base_amount > 0 {
+            let mint = ctx.accounts.base_mint.to_account_info();
+            spl_token_2022::onchain::invoke_transfer_checked(
+                &ctx.accounts.token_program.key(),
+                ctx.accounts.trader_base_account.to_account_info(),
+                mint.clone(),
+                ctx.accounts.base_vault.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.remaining_accounts,
+                base_amount,
+                ctx.accounts.base_mint.decimals,
+                &[],
+            )?;
+            base_escrow = base_amount.saturating_sub(transfer_fee(&mint, base_amount)?);
+        }
+        let mut quote_escrow = 0;
+        if quote_amount > 0 {
+            let mint = ctx.accounts.quote_mint.to_account_info();
+            spl_token_2022::onchain::invoke_transfer_checked(
+                &ctx.accounts.token_program.key(),
+                ctx.accounts.trader_quote_account.to_account_info(),
+                mint.clone(),
+                ctx.accounts.quote_vault.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.remaining_accounts,
+                quote_amount,
+                ctx.accounts.quote_mint.decimals,
+                &[],
+            )?;
+            quote_escrow = quote_amount.saturating_sub(transfer_fee(&mint, quote_amount)?);
+        }
+
+        let owner = ctx.accounts.payer.key();
+        let book = &mut ctx.accounts.order_book;
+        // Each callback rewrites the whole book, so updates land one at a time
+        require!(book.pending.is_none(), ErrorCode::BookUpdatePending);
+        let slot = book.slots.iter().position(Option::is_none).ok_or(ErrorCode::BookFull)? as u8;
+        // Reserved now so an aborted add can still be cancelled for a refund
+        book.slots[slot as usize] = Some(BookSlot {
+            owner,
+            base_escrow,
+            quote_escrow,
+            placed_at: Clock::get()?.unix_timestamp,
+            resting: false,
+        });
+        book.pending = Some(BookUpdate::AddOrder { slot });
+
+        let mut args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedBool(ciphertext_order[0]),
+            Argument::EncryptedU64(ciphertext_order[1]),
+            Argument::EncryptedU64(ciphertext_order[2]),
+        ];
+        args.extend(EncryptedBook::arguments(book.orders.as_ref()));
+        args.push(Argument::PlaintextBool(book.orders.is_none()));
+        args.push(Argument::PlaintextU8(slot));
+        args.push(Argument::PlaintextU64(base_escrow));
+        args.push(Argument::PlaintextU64(quote_escrow));
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![AddOrderCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.order_book.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        Ok(())
+    }
+
+    /// Cross the book inside MPC, callable by anyone
+    pub fn match_orders(
+        ctx: Context<MatchOrders>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let book = &mut ctx.accounts.order_book;
+        require!(book.pending.is_none(), ErrorCode::BookUpdatePending);
+        let orders = book.orders.as_ref().ok_or(ErrorCode::EmptyOrderBook)?;
+        let mut args = EncryptedBook::arguments(Some(orders));
+        // Cancelled slots keep their stale ciphertexts, only resting orders may cross
+        args.extend(
+            book.slots
+                .iter()
+                .map(|slot| Argument::PlaintextBool(slot.is_some_and(|s| s.resting))),
+        );
+        book.pending = Some(BookUpdate::Match);
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![MatchOrdersCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.order_book.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        Ok(())
+    }
+
+    /// Free a slot and refund its escrow, fill proceeds included, to the owner. The owner
+    /// may cancel at any time, anyone may evict an order older than the book's `order_ttl`.
+    pub fn cancel_order(ctx: Context<CancelOrder>, slot: u8) -> Result<()> {
+        let order_book = ctx.accounts.order_book.key();
+        let book = &mut ctx.accounts.order_book;
+        // A pending match or add may still move this slot's escrow
+        require!(book.pending.is_none(), ErrorCode::BookUpdatePending);
+        let order = book
+            .slots
+            .get(slot as usize)
+            .copied()
+            .flatten()
+            .ok_or(ErrorCode::EmptyOrderSlot)?;
+        let evicted = ctx.accounts.caller.key() != order.owner;
+        if evicted {
+            let now = Clock::get()?.unix_timestamp;
+            require!(
+                now >= order.placed_at.saturating_add(book.order_ttl),
+                ErrorCode::OrderNotExpired
+            );
+        }
+        book.slots[slot as usize] = None;
+
+        let token_program = &ctx.accounts.token_program;
+        let authority = ctx.accounts.escrow_authority.to_account_info();
+        let bump = [ctx.bumps.escrow_authority];
+        let signer_seeds: &[&[u8]] = &[b"escrow_authority", &bump];
+        let base = SplLeg::new(
+            Some(&*ctx.accounts.base_mint),
+            Some(&*ctx.accounts.base_vault),
+            Some(&*ctx.accounts.owner_base_account),
+            book.base_mint,
+            ctx.accounts.base_vault.key(),
+            order.owner,
+            order.base_escrow,
+        )?;
+        let quote = SplLeg::new(
+            Some(&*ctx.accounts.quote_mint),
+            Some(&*ctx.accounts.quote_vault),
+            Some(&*ctx.accounts.owner_quote_account),
+            book.quote_mint,
+            ctx.accounts.quote_vault.key(),
+            order.owner,
+            order.quote_escrow,
+        )?;
+        if order.base_escrow > 0 {
+            base.transfer(
+                token_program,
+                authority.clone(),
+                signer_seeds,
+                ctx.remaining_accounts,
+                order.base_escrow,
+            )?;
+        }
+        if order.quote_escrow > 0 {
+            quote.transfer(
+                token_program,
+                authority,
+                signer_seeds,
+                ctx.remaining_accounts,
+                order.quote_escrow,
+            )?;
+        }
+
+        msg!("🗑️ Order in slot {} cancelled", slot);
+        emit!(OrderCancelledEvent {
+            order_book,
+            owner: order.owner,
+            slot,
+            base_refund: order.base_escrow,
+            quote_refund: order.quote_escrow,
+            evicted,
+        });
+        Ok(())
+    }
+
+
+    #[arcium_callback(encrypted_ix = "add_together")]
+    pub fn add_together_callback(
+        ctx: Context<AddTogetherCallback>,
+        output: ComputationOutputs<AddTogetherOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(AddTogetherOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        emit!(SumEvent {
+            sum: o.ciphertexts[0],
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "relay_offer_clone")]
+    pub fn relay_offer_clone_callback(
+        ctx: Context<RelayOfferCloneCallback>,
+        output: ComputationOutputs<RelayOfferCloneOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(RelayOfferCloneOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        // Public data already stored in PDA during relay_offer_clone call,
+        // persist the MXE-encrypted seller identity for finalize
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status.transition(OfferStatus::Open)?;
+        offer.seller_identity = EncryptedIdentity {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        };
+
+        emit!(RelayOfferClonedEvent {
+            acknowledged: 1,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "confidential_deposit_native")]
+    pub fn confidential_deposit_native_callback(
+        ctx: Context<ConfidentialDepositNativeCallback>,
+        output: ComputationOutputs<ConfidentialDepositNativeOutput>,
+    ) -> Result<()> {
+        let balance = &mut ctx.accounts.confidential_balance;
+        let Some(BalanceUpdate::Deposit(amount)) = balance.pending.take() else {
+            return err!(ErrorCode::InvalidBalanceUpdate);
+        };
+        let o = match output {
+            ComputationOutputs::Success(ConfidentialDepositNativeOutput { field_0 }) => field_0,
+            _ => {
+                // Nothing was credited, hand the deposit back and unlock the balance
+                transfer_lamports(
+                    &ctx.accounts.confidential_pool.to_account_info(),
+                    &ctx.accounts.owner.to_account_info(),
+                    amount,
+                )?;
+                msg!("↩️ Confidential deposit of {} lamports aborted and refunded", amount);
+                return Ok(());
+            }
+        };
+        let (stored, owner_copy) = (o.field_0, o.field_1);
+
+        balance.balance = Some(EncryptedBalance {
+            ciphertext: stored.ciphertexts[0],
+            nonce: stored.nonce,
+        });
+        emit!(ConfidentialDepositNativeEvent {
+            owner: balance.owner,
+            amount,
+            balance: owner_copy.ciphertexts[0],
+            nonce: owner_copy.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "confidential_withdraw_native")]
+    pub fn confidential_withdraw_native_callback(
+        ctx: Context<ConfidentialWithdrawNativeCallback>,
+        output: ComputationOutputs<ConfidentialWithdrawNativeOutput>,
+    ) -> Result<()> {
+        let balance = &mut ctx.accounts.confidential_balance;
+        let Some(BalanceUpdate::Withdraw(amount)) = balance.pending.take() else {
+            return err!(ErrorCode::InvalidBalanceUpdate);
+        };
+        let o = match output {
+            ComputationOutputs::Success(ConfidentialWithdrawNativeOutput { field_0 }) => field_0,
+            _ => {
+                // The stored balance is untouched, only the lock is released
+                msg!("↩️ Confidential withdrawal of {} lamports aborted", amount);
+                return Ok(());
+            }
+        };
+        let (withdrawn, stored, owner_copy) = (o.field_0, o.field_1, o.field_2);
+
+        balance.balance = Some(EncryptedBalance {
+            ciphertext: stored.ciphertexts[0],
+            nonce: stored.nonce,
+        });
+        if withdrawn {
+            transfer_lamports(
+                &ctx.accounts.confidential_pool.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+                amount,
+            )?;
+            msg!("💸 Withdrew {} lamports from confidential balance", amount);
+        }
+        emit!(ConfidentialWithdrawNativeEvent {
+            owner: balance.owner,
+            amount,
+            withdrawn,
+            balance: owner_copy.ciphertexts[0],
+            nonce: owner_copy.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "confidential_deposit_spl")]
+    pub fn confidential_deposit_spl_callback(
+        ctx: Context<ConfidentialDepositSplCallback>,
+        output: ComputationOutputs<ConfidentialDepositSplOutput>,
+    ) -> Result<()> {
+        let balance = &mut ctx.accounts.confidential_balance;
+        let Some(BalanceUpdate::Deposit(amount)) = balance.pending.take() else {
+            return err!(ErrorCode::InvalidBalanceUpdate);
+        };
+        let o = match output {
+            ComputationOutputs::Success(ConfidentialDepositSplOutput { field_0 }) => field_0,
+            _ => {
+                // Nothing was credited, hand the tokens back and unlock the balance
+                let leg = SplLeg::new(
+                    Some(&ctx.accounts.mint),
+                    Some(&ctx.accounts.pool_token_vault),
+                    Some(&ctx.accounts.owner_token_account),
+                    balance.mint,
+                    ctx.accounts.pool_token_vault.key(),
+                    balance.owner,
+                    amount,
+                )?;
+                let bump = [ctx.bumps.escrow_authority];
+                leg.transfer(
+                    &ctx.accounts.token_program,
+                    ctx.accounts.escrow_authority.to_account_info(),
+                    &[b"escrow_authority", &bump],
+                    &[],
+                    amount,
+                )?;
+                msg!("↩️ Confidential deposit of {} tokens aborted and refunded", amount);
+                return Ok(());
+            }
+        };
+        let (stored, owner_copy) = (o.field_0, o.field_1);
+
+        balance.balance = Some(EncryptedBalance {
+            ciphertext: stored.ciphertexts[0],
+            nonce: stored.nonce,
+        });
+        emit!(ConfidentialDepositSplEvent {
+            owner: balance.owner,
+            mint: balance.mint,
+            amount,
+            balance: owner_copy.ciphertexts[0],
+            nonce: owner_copy.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "confidential_withdraw_spl")]
+    pub fn confidential_withdraw_spl_callback(
+        ctx: Context<ConfidentialWithdrawSplCallback>,
+        output: ComputationOutputs<ConfidentialWithdrawSplOutput>,
+    ) -> Result<()> {
+        let balance = &mut ctx.accounts.confidential_balance;
+        let Some(BalanceUpdate::Withdraw(amount)) = balance.pending.take() else {
+            return err!(ErrorCode::InvalidBalanceUpdate);
+        };
+        let o = match output {
+            ComputationOutputs::Success(ConfidentialWithdrawSplOutput { field_0 }) => field_0,
+            _ => {
+                // The stored balance is untouched, only the lock is released
+                msg!("↩️ Confidential withdrawal of {} tokens aborted", amount);
+                return Ok(());
+            }
+        };
+        let (withdrawn, stored, owner_copy) = (o.field_0, o.field_1, o.field_2);
+
+        balance.balance = Some(EncryptedBalance {
+            ciphertext: stored.ciphertexts[0],
+            nonce: stored.nonce,
+        });
+        if withdrawn {
+            let leg = SplLeg::new(
+                Some(&ctx.accounts.mint),
+                Some(&ctx.accounts.pool_token_vault),
+                Some(&ctx.accounts.owner_token_account),
+                balance.mint,
+                ctx.accounts.pool_token_vault.key(),
+                balance.owner,
+                amount,
+            )?;
+            let bump = [ctx.bumps.escrow_authority];
+            leg.transfer(
+                &ctx.accounts.token_program,
+                ctx.accounts.escrow_authority.to_account_info(),
+                &[b"escrow_authority", &bump],
+                &[],
+                amount,
+            )?;
+            msg!("💸 Withdrew {} tokens from confidential balance", amount);
+        }
+        emit!(ConfidentialWithdrawSplEvent {
+            owner: balance.owner,
+            mint: balance.mint,
+            amount,
+            withdrawn,
+            balance: owner_copy.ciphertexts[0],
+            nonce: owner_copy.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "interchain_origin_evm_deposit_seller_spl")]
+    pub fn interchain_origin_evm_deposit_seller_spl_callback(
+        ctx: Context<InterchainOriginEvmDepositSellerSplCallback>,
+        output: ComputationOutputs<InterchainOriginEvmDepositSellerSplOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(InterchainOriginEvmDepositSellerSplOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        // Public data already stored in PDA during interchain_origin_evm_deposit_seller_spl call,
+        // persist the MXE-encrypted seller identity for finalize
+        let offer = &mut ctx.accounts.interchain_offer;
+        offer.status.transition(OfferStatus::Open)?;
+        offer.seller_identity = EncryptedIdentity {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        };
+        emit!(InterchainOriginEvmDepositSellerSplEvent {
+            acknowledged: 1,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "finalize_interchain_origin_evm_offer")]
+    pub fn finalize_interchain_origin_evm_offer_callback(
+        ctx: Context<FinalizeInterchainOriginEvmOfferCallback>,
+        output: ComputationOutputs<FinalizeInterchainOriginEvmOfferOutput>,
+    ) -> Result<()> {
+        let matched = match output {
+            ComputationOutputs::Success(FinalizeInterchainOriginEvmOfferOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let offer = &mut ctx.accounts.interchain_offer;
+        if matched {
+            // Bind the offer to the verified taker, who settles it alone via execute_interchain_swap
+            require_before_deadline(offer.deadline)?;
+            offer.status.transition(OfferStatus::Matched)?;
+            offer.taker = Some(ctx.accounts.buyer.key());
+            offer.taker_evm_address = Some(ctx.accounts.evm_proof.evm_address);
+            msg!("✅ Finalize interchain offer callback executed - identity verified via MPC");
+        } else {
+            msg!("❌ Buyer identity claim does not match the seller commitment");
+        }
+
+        emit!(FinalizeInterchainOriginEvmOfferEvent {
+            acknowledged: 1,
+            offer_id: offer.id,
+            matched,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "deposit_seller_native")]
+    pub fn deposit_seller_native_callback(
+        ctx: Context<DepositSellerNativeCallback>,
+        output: ComputationOutputs<DepositSellerNativeOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(DepositSellerNativeOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        // Public data already stored in PDA during deposit_seller_native call,
+        // persist the MXE-encrypted seller identity for finalize
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.status.transition(OfferStatus::Open)?;
+        offer.seller_identity = EncryptedIdentity {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        };
+        emit!(DepositSellerNativeEvent {
+            acknowledged: 1,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "deposit_seller_spl")]
+    pub fn deposit_seller_spl_callback(
+        ctx: Context<DepositSellerSplCallback>,
+        output: ComputationOutputs<DepositSellerSplOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(DepositSellerSplOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        // Public data already stored in PDA during deposit_seller_spl call,
+        // persist the MXE-encrypted seller identity for finalize
+        let offer = &mut ctx.accounts.intrachain_offer;
+        offer.status.transition(OfferStatus::Open)?;
+        offer.seller_identity = EncryptedIdentity {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        };
+        emit!(DepositSellerSplEvent {
+            acknowledged: 1,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "finalize_intrachain_offer")]
+    pub fn finalize_intrachain_offer_callback(
+        ctx: Context<FinalizeIntrachainOfferCallback>,
+        output: ComputationOutputs<FinalizeIntrachainOfferOutput>,
+    ) -> Result<()> {
+        let matched = match output {
+            ComputationOutputs::Success(FinalizeIntrachainOfferOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let offer = &mut ctx.accounts.intrachain_offer;
+        if !matched {
+            // The seller escrow stays locked, the buyer's top-up goes back and a retry
+            // with the correct commitment funds it again
+            msg!("❌ Buyer identity claim does not match the seller commitment");
+            let buyer = ctx.accounts.buyer.to_account_info();
+            if offer.is_taker_native {
+                if let Some(buyer_vault) = &ctx.accounts.buyer_vault {
+                    drain_vault(&buyer_vault.to_account_info(), &buyer)?;
+                }
+            } else if ctx.accounts.buyer_token_vault.is_some() {
+                let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+                let leg = SplLeg::new(
+                    ctx.accounts.token_b_mint.as_deref(),
+                    ctx.accounts.buyer_token_vault.as_deref(),
+                    ctx.accounts.buyer_token_b_account.as_deref(),
+                    offer.token_b_mint,
+                    buyer_token_vault_address(&offer.key(), &buyer.key()),
+                    buyer.key(),
+                    0,
+                )?;
+                let bump = [ctx.bumps.escrow_authority];
+                leg.drain(
+                    token_program,
+                    buyer,
+                    ctx.accounts.escrow_authority.to_account_info(),
+                    &[b"escrow_authority", &bump],
+                    ctx.remaining_accounts,
+                )?;
+            }
+            emit!(FinalizeIntrachainOfferEvent {
+                acknowledged: 1,
+                offer_id: offer.id,
+                matched,
+                token_a_amount: 0,
+                token_b_amount: 0,
+            });
+            return Ok(());
+        }
+
+        msg!("✅ Finalize intrachain offer callback executed - identity verified via MPC");
+
+        // Identity verified, release both escrows for the requested slice in the same
+        // instruction unless the MPC result arrived after the deadline
+        require_before_deadline(offer.deadline)?;
+        offer.status.transition(OfferStatus::Matched)?;
+        let seller_key = ctx.accounts.maker.key();
+        let buyer_key = ctx.accounts.buyer.key();
+        offer.taker = Some(buyer_key);
+        // An earlier fill landing first may have shrunk what is left
+        let fill_amount = ctx.accounts.pending_fill.token_a_amount.min(offer.remaining_amount);
+        require!(fill_amount > 0, ErrorCode::InvalidFillAmount);
+        let token_b_owed = fill_price(offer, fill_amount)?;
+        let is_last_fill = fill_amount == offer.remaining_amount;
+        // Taker fee comes out of token A, maker fee out of token B
+        let config = &ctx.accounts.exchange_config;
+        let taker_fee = fee_amount(fill_amount, config.fees.intrachain_taker_bps)?;
+        let maker_fee = fee_amount(token_b_owed, config.fees.intrachain_maker_bps)?;
+        let token_a_treasury = config.fee_treasury(offer.token_a_mint, ctx.accounts.token_a_treasury.as_ref(), taker_fee)?;
+        let token_b_treasury = config.fee_treasury(offer.token_b_mint, ctx.accounts.token_b_treasury.as_ref(), maker_fee)?;
+        // Amounts actually received, SPL legs are net of any Token-2022 transfer fee
+        let token_a_amount = if offer.token_a_mint == Pubkey::default() {
+            transfer_lamports(
+                &ctx.accounts.seller_vault.to_account_info(),
+                &ctx.accounts.buyer.to_account_info(),
+                fill_amount - taker_fee,
+            )?;
+            if let Some(treasury) = &token_a_treasury {
+                transfer_lamports(&ctx.accounts.seller_vault.to_account_info(), treasury, taker_fee)?;
+            }
+            if is_last_fill {
+                // Vault rent goes back to the maker with the last slice
+                drain_vault(&ctx.accounts.seller_vault.to_account_info(), &ctx.accounts.maker.to_account_info())?;
+            }
+            fill_amount - taker_fee
+        } else {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            let leg = SplLeg::new(
+                ctx.accounts.token_a_mint.as_deref(),
+                ctx.accounts.offer_token_vault.as_deref(),
+                ctx.accounts.buyer_token_a_account.as_deref(),
+                offer.token_a_mint,
+                get_associated_token_address_with_program_id(&offer.key(), &offer.token_a_mint, &token_program.key()),
+                buyer_key,
+                fill_amount,
+            )?;
+            let id_bytes = offer.id.to_le_bytes();
+            let bump = [offer.bump];
+            let offer_seeds: &[&[u8]] = &[b"IntraChainoffer", seller_key.as_ref(), &id_bytes, &bump];
+            // Fee first, so draining on the last slice hands the taker everything left
+            if let Some(treasury) = &token_a_treasury {
+                leg.transfer_to(
+                    token_program,
+                    treasury.clone(),
+                    offer.to_account_info(),
+                    offer_seeds,
+                    ctx.remaining_accounts,
+                    taker_fee,
+                )?;
+            }
+            if is_last_fill {
+                let sent = leg.drain(
+                    token_program,
+                    ctx.accounts.maker.to_account_info(),
+                    offer.to_account_info(),
+                    offer_seeds,
+                    ctx.remaining_accounts,
+                )?;
+                sent.saturating_sub(transfer_fee(&leg.mint.to_account_info(), sent)?)
+            } else {
+                leg.transfer_net(
+                    token_program,
+                    offer.to_account_info(),
+                    offer_seeds,
+                    ctx.remaining_accounts,
+                    fill_amount - taker_fee,
+                )?
+            }
+        };
+        let token_b_amount = if offer.is_taker_native {
+            let buyer_vault = ctx.accounts.buyer_vault.as_ref().ok_or(ErrorCode::InvalidBuyerVault)?;
+            transfer_lamports(
+                &buyer_vault.to_account_info(),
+                &ctx.accounts.maker.to_account_info(),
+                token_b_owed - maker_fee,
+            )?;
+            if let Some(treasury) = &token_b_treasury {
+                transfer_lamports(&buyer_vault.to_account_info(), treasury, maker_fee)?;
+            }
+            // The top-up was priced on the rounded-up slice, hand back whatever this fill left over
+            let surplus = escrowed_lamports(&buyer_vault.to_account_info())?;
+            transfer_lamports(&buyer_vault.to_account_info(), &ctx.accounts.buyer.to_account_info(), surplus)?;
+            token_b_owed - maker_fee
+        } else {
+            let token_program = ctx.accounts.token_program.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+            let leg = SplLeg::new(
+                ctx.accounts.token_b_mint.as_deref(),
+                ctx.accounts.buyer_token_vault.as_deref(),
+                ctx.accounts.seller_token_b_account.as_deref(),
+                offer.token_b_mint,
+                buyer_token_vault_address(&offer.key(), &buyer_key),
+                seller_key,
+                token_b_owed,
+            )?;
+            let bump = [ctx.bumps.escrow_authority];
+            let escrow_seeds: &[&[u8]] = &[b"escrow_authority", &bump];
+            if let Some(treasury) = &token_b_treasury {
+                leg.transfer_to(
+                    token_program,
+                    treasury.clone(),
+                    ctx.accounts.escrow_authority.to_account_info(),
+                    escrow_seeds,
+                    ctx.remaining_accounts,
+                    maker_fee,
+                )?;
+            }
+            let received = leg.transfer(
+                token_program,
+                ctx.accounts.escrow_authority.to_account_info(),
+                escrow_seeds,
+                ctx.remaining_accounts,
+                token_b_owed - maker_fee,
+            )?;
+            // The top-up was priced on the rounded-up slice, hand back whatever this fill left over
+            let surplus = leg.vault.amount - token_b_owed;
+            if surplus > 0 {
+                let refund_to = ctx.accounts.buyer_token_b_account.as_ref().ok_or(ErrorCode::MissingTokenAccounts)?;
+                require_keys_eq!(refund_to.owner, buyer_key, ErrorCode::InvalidTokenRecipient);
+                leg.transfer_to(
+                    token_program,
+                    refund_to.to_account_info(),
+                    ctx.accounts.escrow_authority.to_account_info(),
+                    escrow_seeds,
+                    ctx.remaining_accounts,
+                    surplus,
+                )?;
+            }
+            received
+        };
+        offer.filled_amount = offer.filled_amount.checked_add(fill_amount).ok_or(ErrorCode::MathOverflow)?;
+        offer.remaining_amount -= fill_amount;
+
+        emit!(FinalizeIntrachainOfferEvent {
+            acknowledged: 1,
+            offer_id: offer.id,
+            matched,
+            token_a_amount,
+            token_b_amount,
+        });
+        emit!(OfferFilledEvent {
+            offer_id: offer.id,
+            taker: buyer_key,
+            token_a_amount,
+            token_b_amount,
+            filled_amount: offer.filled_amount,
+            remaining_amount: offer.remaining_amount,
+        });
+        emit_fee(offer.id, offer.token_a_mint, token_a_treasury.as_ref(), taker_fee);
+        emit_fee(offer.id, offer.token_b_mint, token_b_treasury.as_ref(), maker_fee);
+
+        if !is_last_fill {
+            msg!("🧩 Filled {} of offer {}, {} remaining", fill_amount, offer.id, offer.remaining_amount);
+            offer.status.transition(OfferStatus::Open)?;
+            return Ok(());
+        }
+
+        // Fully filled, settle and hand the offer rent back to its payer
+        offer.status.transition(OfferStatus::Settled)?;
+        msg!("✅ Offer {} fully filled and closed", offer.id);
+        let payer = ctx.accounts.payer.to_account_info();
+        ctx.accounts.intrachain_offer.close(payer)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "create_confidential_offer")]
+    pub fn create_confidential_offer_callback(
+        ctx: Context<CreateConfidentialOfferCallback>,
+        output: ComputationOutputs<CreateConfidentialOfferOutput>,
+    ) -> Result<()> {
+        let o = match output {
+            ComputationOutputs::Success(CreateConfidentialOfferOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let offer = &mut ctx.accounts.confidential_offer;
+        offer.status.transition(OfferStatus::Open)?;
+        offer.terms = EncryptedTerms {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        };
+        emit!(ConfidentialOfferCreatedEvent {
+            offer_id: offer.id,
+            maker: offer.maker,
+            token_a_mint: offer.token_a_mint,
+            token_b_mint: offer.token_b_mint,
+            deadline: offer.deadline,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "match_confidential_offer")]
+    pub fn match_confidential_offer_callback(
+        ctx: Context<MatchConfidentialOfferCallback>,
+        output: ComputationOutputs<MatchConfidentialOfferOutput>,
+    ) -> Result<()> {
+        for balance in [
+            &mut ctx.accounts.maker_token_a_balance,
+            &mut ctx.accounts.maker_token_b_balance,
+            &mut ctx.accounts.taker_token_a_balance,
+            &mut ctx.accounts.taker_token_b_balance,
+        ] {
+            balance.unlock(BalanceUpdate::Match)?;
+        }
+        let offer = &mut ctx.accounts.confidential_offer;
+        let taker = ctx.accounts.taker.to_account_info();
+        let o = match output {
+            ComputationOutputs::Success(MatchConfidentialOfferOutput { field_0 }) => field_0,
+            _ => {
+                // No balance moved and the taker is not at fault, the offer is free for the next bid
+                offer.taker = None;
+                transfer_lamports(&offer.to_account_info(), &taker, MATCH_BOND)?;
+                msg!("↩️ Match on confidential offer {} aborted", offer.id);
+                return Ok(());
+            }
+        };
+        let (taker_settlement, maker_settlement) = (o.field_1, o.field_2);
+
+        // A bid that crossed after the deadline settles nothing, the stored balances stay
+        let settled = o.field_0 && Clock::get()?.unix_timestamp <= offer.deadline;
+        let offer_filled = settled && o.field_8;
+        if settled {
+            offer.status.transition(OfferStatus::Matched)?;
+            // What is left of the ask stays open for the next taker
+            offer.terms = EncryptedTerms {
+                ciphertexts: o.field_7.ciphertexts,
+                nonce: o.field_7.nonce,
+            };
+            if offer_filled {
+                offer.status.transition(OfferStatus::Settled)?;
+            } else {
+                offer.status.transition(OfferStatus::Open)?;
+                offer.taker = None;
+            }
+            transfer_lamports(&offer.to_account_info(), &taker, MATCH_BOND)?;
+            for (balance, stored) in [
+                (&mut ctx.accounts.maker_token_a_balance, o.field_3),
+                (&mut ctx.accounts.maker_token_b_balance, o.field_4),
+                (&mut ctx.accounts.taker_token_a_balance, o.field_5),
+                (&mut ctx.accounts.taker_token_b_balance, o.field_6),
+            ] {
+                balance.balance = Some(EncryptedBalance {
+                    ciphertext: stored.ciphertexts[0],
+                    nonce: stored.nonce,
+                });
+            }
+            msg!("🤝 Confidential offer {} matched", offer.id);
+        } else {
+            offer.taker = None;
+            transfer_lamports(&offer.to_account_info(), &ctx.accounts.maker.to_account_info(), MATCH_BOND)?;
+        }
+
+        // Both sides learn the settlement amounts, nobody else learns more than the booleans
+        emit!(ConfidentialMatchEvent {
+            offer_id: offer.id,
+            taker: taker.key(),
+            matched: settled,
+            offer_filled,
+            taker_settlement: taker_settlement.ciphertexts,
+            taker_nonce: taker_settlement.nonce.to_le_bytes(),
+            maker_settlement: maker_settlement.ciphertexts,
+            maker_nonce: maker_settlement.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "place_sealed_bid")]
+    pub fn place_sealed_bid_callback(
+        ctx: Context<PlaceSealedBidCallback>,
+        output: ComputationOutputs<PlaceSealedBidOutput>,
+    ) -> Result<()> {
+        let auction = &mut ctx.accounts.sealed_auction;
+        let bidder = auction.pending_bidder.take().ok_or(ErrorCode::InvalidAuctionStatus)?;
+        let o = match output {
+            ComputationOutputs::Success(PlaceSealedBidOutput { field_0 }) => field_0,
+            _ => {
+                // The bid table is untouched, the bidder may try again or withdraw the collateral
+                msg!("↩️ Sealed bid on auction {} aborted", auction.id);
+                return Ok(());
+            }
+        };
+
+        auction.bids = Some(EncryptedBids {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        });
+        auction.bidders.push(bidder);
+        emit!(SealedBidPlacedEvent {
+            auction_id: auction.id,
+            bidder,
+            bid_count: auction.bidders.len() as u8,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "resolve_sealed_auction")]
+    pub fn resolve_sealed_auction_callback(
+        ctx: Context<ResolveSealedAuctionCallback>,
+        output: ComputationOutputs<ResolveSealedAuctionOutput>,
+    ) -> Result<()> {
+        let auction = &mut ctx.accounts.sealed_auction;
+        require!(auction.status == AuctionStatus::Resolving, ErrorCode::InvalidAuctionStatus);
+        let o = match output {
+            ComputationOutputs::Success(ResolveSealedAuctionOutput { field_0 }) => field_0,
+            _ => {
+                // Bidding stays closed by time, this only lets resolve be queued again
+                auction.status = AuctionStatus::Bidding;
+                msg!("↩️ Resolving sealed auction {} aborted", auction.id);
+                return Ok(());
+            }
+        };
+        let (found, slot, clearing_price) = (o.field_0, o.field_1, o.field_2);
+
+        if found {
+            let winner = *auction.bidders.get(slot as usize).ok_or(ErrorCode::InvalidAuctionStatus)?;
+            auction.winner = Some(winner);
+            auction.clearing_price = clearing_price;
+            auction.status = AuctionStatus::Awarded;
+            msg!("🏆 Sealed auction {} won at {} lamports", auction.id, clearing_price);
+        } else {
+            auction.status = AuctionStatus::Unsold;
+            msg!("📭 Sealed auction {} had no bid at or above the reserve", auction.id);
+        }
+        emit!(SealedAuctionResolvedEvent {
+            auction_id: auction.id,
+            winner: auction.winner,
+            clearing_price: auction.clearing_price,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "add_order")]
+    pub fn add_order_callback(
+        ctx: Context<AddOrderCallback>,
+        output: ComputationOutputs<AddOrderOutput>,
+    ) -> Result<()> {
+        let order_book = ctx.accounts.order_book.key();
+        let book = &mut ctx.accounts.order_book;
+        let Some(BookUpdate::AddOrder { slot }) = book.pending.take() else {
+            return err!(ErrorCode::InvalidBookUpdate);
+        };
+        let o = match output {
+            ComputationOutputs::Success(AddOrderOutput { field_0 }) => field_0,
+            _ => {
+                // The book is untouched, the slot stays reserved until its owner cancels for a refund
+                msg!("↩️ Adding an order to the book aborted");
+                return Ok(());
+            }
+        };
+
+        book.orders = Some(EncryptedBook {
+            ciphertexts: o.ciphertexts,
+            nonce: o.nonce,
+        });
+        let order = book.slots[slot as usize].as_mut().ok_or(ErrorCode::InvalidBookUpdate)?;
+        order.resting = true;
+        emit!(OrderAddedEvent {
+            order_book,
+            owner: order.owner,
+            slot,
+        });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "match_orders")]
+    pub fn match_orders_callback(
+        ctx: Context<MatchOrdersCallback>,
+        output: ComputationOutputs<MatchOrdersOutput>,
+    ) -> Result<()> {
+        let order_book = ctx.accounts.order_book.key();
+        let book = &mut ctx.accounts.order_book;
+        require!(book.pending.take() == Some(BookUpdate::Match), ErrorCode::InvalidBookUpdate);
+        let o = match output {
+            ComputationOutputs::Success(MatchOrdersOutput { field_0 }) => field_0,
+            _ => {
+                msg!("↩️ Matching the order book aborted");
+                return Ok(());
+            }
+        };
+        let (stored, fill_count, buy_slots, sell_slots, prices, sizes) =
+            (o.field_0, o.field_1, o.field_2, o.field_3, o.field_4, o.field_5);
+
+        // Settle each fill between the two slots' escrow. The circuit only rests orders
+        // their escrow covers and fills at or inside both limits, so neither side runs short;
+        // if it ever did, the callback fails rather than clamping the escrow at zero.
+        for k in 0..(fill_count as usize).min(MAX_BOOK_FILLS) {
+            let (b, s) = (buy_slots[k] as usize, sell_slots[k] as usize);
+            let (size, price) = (sizes[k], prices[k]);
+            let quote = price.checked_mul(size).ok_or(ErrorCode::MathOverflow)?;
+            let buy = book.slots[b].as_mut().ok_or(ErrorCode::InvalidBookUpdate)?;
+            buy.quote_escrow = buy.quote_escrow.checked_sub(quote).ok_or(ErrorCode::MathOverflow)?;
+            buy.base_escrow = buy.base_escrow.checked_add(size).ok_or(ErrorCode::MathOverflow)?;
+            let buyer = buy.owner;
+            let sell = book.slots[s].as_mut().ok_or(ErrorCode::InvalidBookUpdate)?;
+            sell.base_escrow = sell.base_escrow.checked_sub(size).ok_or(ErrorCode::MathOverflow)?;
+            sell.quote_escrow = sell.quote_escrow.checked_add(quote).ok_or(ErrorCode::MathOverflow)?;
+            let seller = sell.owner;
+            emit!(OrderFillEvent {
+                order_book,
+                buyer,
+                seller,
+                price,
+                size,
+            });
+        }
+        // Occupancy stays private, a filled order keeps its slot and proceeds until cancelled
+        book.orders = Some(EncryptedBook {
+            ciphertexts: stored.ciphertexts,
+            nonce: stored.nonce,
+        });
+        msg!("📊 Order book matched with {} fills", fill_count);
+        Ok(())
+    }
+}
+
+#[queue_computation_accounts("add_together", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct AddTogether<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+
+#[queue_computation_accounts("relay_offer_clone", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, chain_id: u64, vaa_hash: [u8; 32], ciphertext_external_seller_identity_hash: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct RelayOfferClone<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Solana key of the origin-chain seller, checked against the message payload. Its
+    /// signature covers the identity ciphertext, so a relayer cannot swap in its own
+    pub maker: Signer<'info>,
+    #[account(
+        seeds = [b"posted_vaa", vaa_hash.as_ref()],
+        bump,
+    )]
+    pub posted_vaa: Box<Account<'info, PostedVaa>>,
+    /// Route the message must have arrived over, checked by its bridge adapter
+    #[account(
+        seeds = [b"bridge_route", &chain_id.to_le_bytes()],
+        bump = bridge_route.bump,
+    )]
+    pub bridge_route: Box<Account<'info, BridgeRoute>>,
+    #[account(
+        seeds = [b"chain_config", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+    /// Replay guard, creation fails if this message was already relayed
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ConsumedMessage::INIT_SPACE,
+        seeds = [b"consumed_message", vaa_hash.as_ref()],
+        bump,
+    )]
+    pub consumed_message: Box<Account<'info, ConsumedMessage>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + InterchainOffer::INIT_SPACE,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    /// Mint the taker pays in, required unless `is_taker_native`
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+        constraint = !exchange_config.paused.relays @ ErrorCode::RelaysPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("confidential_deposit_native", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ConfidentialDepositNative<'info> {
+    /// Owner of the balance, funds the deposit
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ConfidentialBalance::INIT_SPACE,
+        seeds = [b"confidential_balance", payer.key().as_ref(), NATIVE_BALANCE_MINT.as_ref()],
+        bump,
+    )]
+    pub confidential_balance: Account<'info, ConfidentialBalance>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8,
+        seeds = [b"confidential_pool"],
+        bump,
+    )]
+    /// CHECK: Pooled vault holding the lamports behind every confidential balance
+    pub confidential_pool: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("confidential_withdraw_native", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ConfidentialWithdrawNative<'info> {
+    /// Owner of the balance, receives the withdrawal
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", payer.key().as_ref(), NATIVE_BALANCE_MINT.as_ref()],
+        bump = confidential_balance.bump,
+    )]
+    pub confidential_balance: Account<'info, ConfidentialBalance>,
+    #[account(
+        seeds = [b"confidential_pool"],
+        bump,
+    )]
+    /// CHECK: Pooled vault holding the lamports behind every confidential balance
+    pub confidential_pool: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_WITHDRAW_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("confidential_deposit_spl", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ConfidentialDepositSpl<'info> {
+    /// Owner of the balance, funds the deposit
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ConfidentialBalance::INIT_SPACE,
+        seeds = [b"confidential_balance", payer.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub confidential_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+        token::token_program = token_program,
+    )]
+    pub payer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Pooled vault holding the tokens behind every confidential balance of `mint`
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"confidential_pool_token", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow_authority,
+        token::token_program = token_program,
+    )]
+    pub pool_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for the confidential token pools
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("confidential_withdraw_spl", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ConfidentialWithdrawSpl<'info> {
+    /// Owner of the balance, receives the withdrawal
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", payer.key().as_ref(), mint.key().as_ref()],
+        bump = confidential_balance.bump,
+    )]
+    pub confidential_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        token::mint = mint,
+        token::authority = payer,
+        token::token_program = token_program,
+    )]
+    pub payer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Pooled vault holding the tokens behind every confidential balance of `mint`
+    #[account(
+        seeds = [b"confidential_pool_token", mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for the confidential token pools
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_WITHDRAW_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[queue_computation_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, htlc: Option<HtlcTerms>, ciphertext_seller_identity_hash: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct InterchainOriginEvmDepositSellerSpl<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Seller escrowing token A, may differ from the rent payer
+    pub maker: Signer<'info>,
+    /// Route settlement messages for this offer will take back to `chain_id`, HTLC offers need none
+    #[account(
+        seeds = [b"bridge_route", &chain_id.to_le_bytes()],
+        bump = bridge_route.bump,
+    )]
+    pub bridge_route: Option<Box<Account<'info, BridgeRoute>>>,
+    #[account(
+        seeds = [b"chain_config", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+        constraint = chain_config.enabled @ ErrorCode::ChainDisabled,
+    )]
+    pub chain_config: Box<Account<'info, ChainConfig>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + InterchainOffer::INIT_SPACE,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    pub token_a_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// Mint the taker pays in, required unless `is_taker_native`
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = interchain_offer,
+        associated_token::token_program = token_program,
+    )]
+    pub offer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("finalize_interchain_origin_evm_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, buyer_evm_address: [u8; 20], computation_offset: u64)]
+pub struct FinalizeInterchainOriginEvmOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    /// CHECK: Offer maker, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TakerEvmProof::INIT_SPACE,
+        seeds = [b"evm_proof", interchain_offer.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub evm_proof: Account<'info, TakerEvmProof>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, read for the secp256k1 precompile instruction
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("deposit_seller_native", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, min_fill_amount: u64, ciphertext_seller_identity_hash: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct DepositSellerNative<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Seller owning the offer, may differ from the rent payer
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + IntraChainOffer::INIT_SPACE,
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    /// Mint the taker pays in, required unless `is_taker_native`
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("deposit_seller_spl", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, min_fill_amount: u64, ciphertext_seller_identity_hash: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct DepositSellerSpl<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Seller escrowing token A, may differ from the rent payer
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + IntraChainOffer::INIT_SPACE,
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    pub token_a_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// Mint the taker pays in, required unless `is_taker_native`
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = token_a_mint,
+        token::authority = maker,
+        token::token_program = token_program,
+    )]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = intrachain_offer,
+        associated_token::token_program = token_program,
+    )]
+    pub offer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("finalize_intrachain_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, fill_amount: u64, ciphertext_buyer_identity_hash: [[u8; 32]; 4], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct FinalizeIntrachainOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    /// CHECK: Offer maker, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PendingFill::INIT_SPACE,
+        seeds = [b"pending_fill", intrachain_offer.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub pending_fill: Account<'info, PendingFill>,
+    #[account(
+        seeds = [b"seller_vault", maker.key().as_ref(), &id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding seller's token A
+    pub seller_vault: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8,
+        seeds = [b"buyer_vault", intrachain_offer.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault for the buyer's native token B, funded by this instruction
+    pub buyer_vault: Option<UncheckedAccount<'info>>,
+    // SPL legs, only passed when the offer escrows tokens on that side
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_a_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub offer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub buyer_token_a_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [b"buyer_token_vault", intrachain_offer.key().as_ref(), payer.key().as_ref()],
+        bump,
+        token::mint = token_b_mint,
+        token::authority = escrow_authority,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub buyer_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub seller_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for buyer token vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    /// CHECK: Token A treasury, forwarded to the callback when a taker fee applies
+    pub token_a_treasury: Option<UncheckedAccount<'info>>,
+    /// CHECK: Token B treasury, forwarded to the callback when a maker fee applies
+    pub token_b_treasury: Option<UncheckedAccount<'info>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+// === EXCHANGE CONFIG ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+pub struct InitializeExchange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized,
+    )]
+    pub program: Program<'info, crate::program::ConfidentialCrossChainExchange>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ExchangeConfig::INIT_SPACE,
+        seeds = [b"exchange_config"],
+        bump,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.pending_authority == Some(pending_authority.key()) @ ErrorCode::NotPendingAuthority,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+}
+
+// === CROSS-CHAIN MESSAGE ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+pub struct SetGuardianSet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GuardianSet::INIT_SPACE,
+        seeds = [b"guardian_set"],
+        bump,
+    )]
+    pub guardian_set: Account<'info, GuardianSet>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain_id: u64)]
+pub struct RegisterBridgeRoute<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + BridgeRoute::INIT_SPACE,
+        seeds = [b"bridge_route", &chain_id.to_le_bytes()],
+        bump,
+    )]
+    pub bridge_route: Account<'info, BridgeRoute>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain_id: u64)]
+pub struct SetChainConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ChainConfig::INIT_SPACE,
+        seeds = [b"chain_config", &chain_id.to_le_bytes()],
+        bump,
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(chain_id: u64)]
+pub struct SetChainEnabled<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+    #[account(
+        mut,
+        seeds = [b"chain_config", &chain_id.to_le_bytes()],
+        bump = chain_config.bump,
+    )]
+    pub chain_config: Account<'info, ChainConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(vaa_hash: [u8; 32])]
+pub struct PostVaa<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(seeds = [b"guardian_set"], bump = guardian_set.bump)]
+    pub guardian_set: Account<'info, GuardianSet>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PostedVaa::INIT_SPACE,
+        seeds = [b"posted_vaa", vaa_hash.as_ref()],
+        bump,
+    )]
+    pub posted_vaa: Account<'info, PostedVaa>,
+    pub system_program: Program<'info, System>,
+}
+
+// === ESCROW VAULT ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ExecuteInterchainSwap<'info> {
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + OutboundEmitter::INIT_SPACE,
+        seeds = [b"emitter"],
+        bump,
+    )]
+    pub outbound_emitter: Box<Account<'info, OutboundEmitter>>,
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + OutboundMessage::INIT_SPACE,
+        seeds = [b"outbound_message", &outbound_emitter.sequence.to_le_bytes()],
+        bump,
+    )]
+    pub outbound_message: Box<Account<'info, OutboundMessage>>,
+
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        constraint = interchain_offer.htlc.is_none() @ ErrorCode::HtlcOffer,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    /// Route the settlement message takes back to the offer's chain
+    #[account(
+        seeds = [b"bridge_route", &interchain_offer.chain_id.to_le_bytes()],
+        bump = bridge_route.bump,
+    )]
+    pub bridge_route: Box<Account<'info, BridgeRoute>>,
+    
+    #[account(mut)]
+    /// CHECK: Offer maker receiving token B, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+    
+    /// Taker recorded by the finalize callback, the only required signer
+    #[account(
+        mut,
+        constraint = interchain_offer.taker == Some(buyer.key()) @ ErrorCode::NotOfferTaker,
+    )]
+    pub buyer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"interchain_seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Native token A escrow of the interchain offer, never shared with intrachain vaults
+    pub seller_vault: UncheckedAccount<'info>,
+    
+    // SPL legs, only passed when the offer escrows or wants tokens on that side
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_a_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub offer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub buyer_token_a_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub buyer_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub seller_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    // Fee recipients, only passed when the matching fee is non-zero
+    #[account(mut)]
+    /// CHECK: Token A treasury, checked against the exchange config
+    pub token_a_treasury: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    /// CHECK: Token B treasury, checked against the exchange config
+    pub token_b_treasury: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ClaimHtlc<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(mut)]
+    /// CHECK: Offer maker receiving the vault rent, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the offer, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+
+    /// Taker whose identity claim the finalize callback verified
+    #[account(
+        constraint = interchain_offer.taker == Some(buyer.key()) @ ErrorCode::NotOfferTaker,
+    )]
+    pub buyer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Writable so withheld Token-2022 fees can be harvested before closing the vault
+    #[account(mut)]
+    pub token_a_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub offer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub buyer_token_a_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    // Fee recipient, only passed when the taker fee is non-zero
+    #[account(mut)]
+    /// CHECK: Token A treasury, checked against the exchange config
+    pub token_a_treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ReclaimHtlc<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(mut)]
+    /// CHECK: Offer maker receiving the refund, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the offer, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    /// Writable so withheld Token-2022 fees can be harvested before closing the vault
+    #[account(mut)]
+    pub token_a_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub offer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct DisputeInterchainOffer<'info> {
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", interchain_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        constraint = interchain_offer.htlc.is_none() @ ErrorCode::HtlcOffer,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    /// Maker or verified taker of the offer
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct ResolveInterchainDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"InterChainoffer", interchain_offer.maker.as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+/// Shared by `cancel_intrachain_offer` and `expire_intrachain_offer`, which differ only in
+/// who may call them and when
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RefundIntrachainOffer<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+
+    #[account(mut)]
+    /// CHECK: Offer maker receiving the refund, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the offer, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding seller's token A
+    pub seller_vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Buyer that funded a vault for this offer, if any
+    pub buyer: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    /// CHECK: Buyer vault, re-derived from `buyer` in the handler
+    pub buyer_vault: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// Writable so withheld Token-2022 fees can be harvested before closing the vault
+    #[account(mut)]
+    pub token_a_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub offer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub seller_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    /// Buyer token vault, re-derived from `buyer` in the handler
+    pub buyer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub buyer_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for buyer token vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    /// The maker cancelling, or anyone cranking an expired offer for the bounty
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundIntrachainOffer<'info> {
+    fn refund(
+        &self,
+        offer_id: u64,
+        escrow_authority_bump: u8,
+        hook_accounts: &[AccountInfo<'info>],
+        bounty: u64,
+    ) -> Result<EscrowRefunds> {
+        let offer = &self.intrachain_offer;
+        let seller_key = self.maker.key();
+        let id_bytes = offer_id.to_le_bytes();
+        let bump = [offer.bump];
+        refund_and_close(
+            &offer.to_account_info(),
+            &[b"IntraChainoffer", seller_key.as_ref(), &id_bytes, &bump],
+            &OfferEscrows {
+                token_a: offer.token_a_mint,
+                token_b: offer.token_b_mint,
+                is_taker_native: offer.is_taker_native,
+                seller_vault: &self.seller_vault,
+                token_program: self.token_program.as_ref(),
+                token_a_mint: self.token_a_mint.as_deref(),
+                offer_token_vault: self.offer_token_vault.as_deref(),
+                seller_token_account: self.seller_token_account.as_deref(),
+                buyer: self.buyer.as_ref(),
+                buyer_vault: self.buyer_vault.as_ref(),
+                token_b_mint: self.token_b_mint.as_deref(),
+                buyer_token_vault: self.buyer_token_vault.as_deref(),
+                buyer_token_b_account: self.buyer_token_b_account.as_deref(),
+                escrow_authority: &self.escrow_authority,
+                escrow_authority_bump,
+                hook_accounts,
+            },
+            &self.maker.to_account_info(),
+            &self.caller.to_account_info(),
+            bounty,
+        )
+    }
+}
+
+/// Shared by `cancel_interchain_offer` and `expire_interchain_offer`, which differ only in
+/// who may call them and when
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct RefundInterchainOffer<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"InterChainoffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = interchain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+        constraint = interchain_offer.htlc.is_none() @ ErrorCode::HtlcOffer,
+    )]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+
+    #[account(mut)]
+    /// CHECK: Offer maker receiving the refund, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the offer, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"interchain_seller_vault", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Native token A escrow of the interchain offer, never shared with intrachain vaults
+    pub seller_vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Buyer that funded a vault for this offer, if any
+    pub buyer: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    /// CHECK: Buyer vault, re-derived from `buyer` in the handler
+    pub buyer_vault: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// Writable so withheld Token-2022 fees can be harvested before closing the vault
+    #[account(mut)]
+    pub token_a_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    pub offer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub seller_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    /// Buyer token vault, re-derived from `buyer` in the handler
+    pub buyer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub buyer_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for buyer token vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    /// The maker cancelling, or anyone cranking an expired offer for the bounty
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundInterchainOffer<'info> {
+    fn refund(
+        &self,
+        offer_id: u64,
+        escrow_authority_bump: u8,
+        hook_accounts: &[AccountInfo<'info>],
+        bounty: u64,
+    ) -> Result<EscrowRefunds> {
+        let offer = &self.interchain_offer;
+        let seller_key = self.maker.key();
+        let id_bytes = offer_id.to_le_bytes();
+        let bump = [offer.bump];
+        refund_and_close(
+            &offer.to_account_info(),
+            &[b"InterChainoffer", seller_key.as_ref(), &id_bytes, &bump],
+            &OfferEscrows {
+                token_a: offer.token_a_mint,
+                token_b: offer.token_b_mint,
+                is_taker_native: offer.is_taker_native,
+                seller_vault: &self.seller_vault,
+                token_program: self.token_program.as_ref(),
+                token_a_mint: self.token_a_mint.as_deref(),
+                offer_token_vault: self.offer_token_vault.as_deref(),
+                seller_token_account: self.seller_token_account.as_deref(),
+                buyer: self.buyer.as_ref(),
+                buyer_vault: self.buyer_vault.as_ref(),
+                token_b_mint: self.token_b_mint.as_deref(),
+                buyer_token_vault: self.buyer_token_vault.as_deref(),
+                buyer_token_b_account: self.buyer_token_b_account.as_deref(),
+                escrow_authority: &self.escrow_authority,
+                escrow_authority_bump,
+                hook_accounts,
+            },
+            &self.maker.to_account_info(),
+            &self.caller.to_account_info(),
+            bounty,
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct DepositToSellerVault<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8,
+        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA
+    pub seller_vault: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct DepositToBuyerVault<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(owner = crate::ID @ ErrorCode::InvalidBuyerVault)]
+    /// CHECK: Intrachain or interchain offer the deposit is for, seeds the vault
+    pub offer: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8,
+        seeds = [b"buyer_vault", offer.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault PDA
+    pub buyer_vault: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct DepositToBuyerTokenVault<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(owner = crate::ID @ ErrorCode::InvalidBuyerVault)]
+    /// CHECK: Intrachain or interchain offer the deposit is for, seeds the vault
+    pub offer: UncheckedAccount<'info>,
+
+    pub token_b_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = token_b_mint,
+        token::authority = buyer,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [b"buyer_token_vault", offer.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        token::mint = token_b_mint,
+        token::authority = escrow_authority,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for buyer token vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBuyerVault<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// While the offer exists its callbacks may still spend the vault
+    #[account(constraint = offer.data_is_empty() @ ErrorCode::OfferStillOpen)]
+    /// CHECK: Closed offer the vaults were funded for, only seeds them
+    pub offer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_vault", offer.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding buyer's native token B
+    pub buyer_vault: UncheckedAccount<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(mut)]
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(mut)]
+    /// Buyer token vault, re-derived from `buyer` in the handler
+    pub buyer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub buyer_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for buyer token vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// === CONFIDENTIAL OFFER ACCOUNT CONTEXTS ===
+
+#[queue_computation_accounts("create_confidential_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, token_a_mint: Pubkey, token_b_mint: Pubkey, deadline: i64, ciphertext_terms: [[u8; 32]; 2], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct CreateConfidentialOffer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Seller owning the offer, may differ from the rent payer
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ConfidentialOffer::INIT_SPACE,
+        seeds = [b"ConfidentialOffer", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub confidential_offer: Account<'info, ConfidentialOffer>,
+    /// Maker's balance of token B, credited when the offer settles
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ConfidentialBalance::INIT_SPACE,
+        seeds = [b"confidential_balance", maker.key().as_ref(), token_b_mint.as_ref()],
+        bump,
+    )]
+    pub maker_token_b_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_CONFIDENTIAL_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("match_confidential_offer", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, ciphertext_bid: [[u8; 32]; 2], pub_key: [u8; 32], nonce: u128, maker_nonce: u128, computation_offset: u64)]
+pub struct MatchConfidentialOffer<'info> {
+    /// Taker submitting the bid, recorded on the offer while it is matched
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"ConfidentialOffer", confidential_offer.maker.as_ref(), &id.to_le_bytes()],
+        bump = confidential_offer.bump,
+        constraint = confidential_offer.maker != payer.key() @ ErrorCode::SelfMatch,
+    )]
+    pub confidential_offer: Account<'info, ConfidentialOffer>,
+    /// Maker's balance of token A, funds the offered leg
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", confidential_offer.maker.as_ref(), confidential_offer.token_a_mint.as_ref()],
+        bump = maker_token_a_balance.bump,
+    )]
+    pub maker_token_a_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", confidential_offer.maker.as_ref(), confidential_offer.token_b_mint.as_ref()],
+        bump = maker_token_b_balance.bump,
+    )]
+    pub maker_token_b_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ConfidentialBalance::INIT_SPACE,
+        seeds = [b"confidential_balance", payer.key().as_ref(), confidential_offer.token_a_mint.as_ref()],
+        bump,
+    )]
+    pub taker_token_a_balance: Box<Account<'info, ConfidentialBalance>>,
+    /// Taker's balance of token B, funds the price
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", payer.key().as_ref(), confidential_offer.token_b_mint.as_ref()],
+        bump = taker_token_b_balance.bump,
+    )]
+    pub taker_token_b_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_CONFIDENTIAL_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct CloseConfidentialOffer<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"ConfidentialOffer", maker.key().as_ref(), &offer_id.to_le_bytes()],
+        bump = confidential_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+    )]
+    pub confidential_offer: Account<'info, ConfidentialOffer>,
+
+    pub maker: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the offer, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+}
+
+
+// === SEALED-BID AUCTION ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreateSealedAuction<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Seller escrowing the lot, may differ from the rent payer
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SealedAuctionOffer::INIT_SPACE,
+        seeds = [b"SealedAuction", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8,
+        seeds = [b"auction_vault", maker.key().as_ref(), &id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the lot
+    pub auction_vault: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("place_sealed_bid", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, ciphertext_bid: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct PlaceSealedBid<'info> {
+    /// Buyer placing the bid and escrowing its collateral
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"SealedAuction", sealed_auction.maker.as_ref(), &id.to_le_bytes()],
+        bump = sealed_auction.bump,
+    )]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8,
+        seeds = [b"bid_vault", sealed_auction.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the bidder's collateral
+    pub bid_vault: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_PLACE_SEALED_BID)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[queue_computation_accounts("resolve_sealed_auction", payer)]
+#[derive(Accounts)]
+#[instruction(id: u64, computation_offset: u64)]
+pub struct ResolveSealedAuction<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"SealedAuction", sealed_auction.maker.as_ref(), &id.to_le_bytes()],
+        bump = sealed_auction.bump,
+    )]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RESOLVE_SEALED_AUCTION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(auction_id: u64)]
+pub struct SettleSealedAuction<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"SealedAuction", maker.key().as_ref(), &auction_id.to_le_bytes()],
+        bump = sealed_auction.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+        constraint = sealed_auction.winner == Some(winner.key()) @ ErrorCode::NotAuctionWinner,
+    )]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: Seller receiving the clearing price, checked against the auction
+    pub maker: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the auction, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction_vault", maker.key().as_ref(), &auction_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the lot
+    pub auction_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"bid_vault", sealed_auction.key().as_ref(), winner.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the winner's collateral, pays the clearing price
+    pub bid_vault: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+#[instruction(auction_id: u64)]
+pub struct CloseSealedAuction<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"SealedAuction", maker.key().as_ref(), &auction_id.to_le_bytes()],
+        bump = sealed_auction.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+    )]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the auction, refunded when it closes
+    pub payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction_vault", maker.key().as_ref(), &auction_id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the lot
+    pub auction_vault: UncheckedAccount<'info>,
+
+    /// Only needed to slash a winner who let the settlement deadline pass
+    #[account(
+        mut,
+        seeds = [b"bid_vault", sealed_auction.key().as_ref(), sealed_auction.winner.unwrap_or_default().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the winner's collateral
+    pub winner_bid_vault: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawBidCollateral<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    /// CHECK: Auction the bid was placed on, deserialized by the handler while it still exists
+    pub sealed_auction: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"bid_vault", sealed_auction.key().as_ref(), bidder.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding the bidder's collateral
+    pub bid_vault: UncheckedAccount<'info>,
+}
+
+
+// === DARK POOL ORDER BOOK ACCOUNT CONTEXTS ===
+
+#[derive(Accounts)]
+pub struct CreateOrderBook<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Account<'info, ExchangeConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + OrderBook::INIT_SPACE,
+        seeds = [b"order_book", base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    #[account(mint::token_program = token_program)]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = token_program)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// Base escrowed behind every order in the book
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"order_book_vault", order_book.key().as_ref(), base_mint.key().as_ref()],
+        bump,
+        token::mint = base_mint,
+        token::authority = escrow_authority,
+        token::token_program = token_program,
+    )]
+    pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Quote escrowed behind every order in the book
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"order_book_vault", order_book.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = escrow_authority,
+        token::token_program = token_program,
+    )]
+    pub quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for the order book vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+#[queue_computation_accounts("add_order", payer)]
+#[derive(Accounts)]
+#[instruction(base_amount: u64, quote_amount: u64, ciphertext_order: [[u8; 32]; 3], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
+pub struct AddOrder<'info> {
+    /// Trader resting the order
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"order_book", order_book.base_mint.as_ref(), order_book.quote_mint.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    #[account(address = order_book.base_mint @ ErrorCode::InvalidMint)]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = order_book.quote_mint @ ErrorCode::InvalidMint)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"order_book_vault", order_book.key().as_ref(), base_mint.key().as_ref()],
+        bump,
+    )]
+    pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"order_book_vault", order_book.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+    )]
+    pub quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = payer,
+        token::token_program = token_program,
+    )]
+    pub trader_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = payer,
+        token::token_program = token_program,
+    )]
+    pub trader_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_ORDER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.new_offers @ ErrorCode::NewOffersPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    /// The order's owner, or anyone once the order has outlived the book's `order_ttl`
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"order_book", order_book.base_mint.as_ref(), order_book.quote_mint.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    #[account(address = order_book.base_mint @ ErrorCode::InvalidMint)]
+    pub base_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = order_book.quote_mint @ ErrorCode::InvalidMint)]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"order_book_vault", order_book.key().as_ref(), base_mint.key().as_ref()],
+        bump,
+    )]
+    pub base_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"order_book_vault", order_book.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+    )]
+    pub quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Refund destinations, checked against the slot owner
+    #[account(mut)]
+    pub owner_base_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub owner_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for the order book vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+#[queue_computation_accounts("match_orders", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct MatchOrders<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"order_book", order_book.base_mint.as_ref(), order_book.quote_mint.as_ref()],
+        bump = order_book.bump,
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: mempool_account, checked by the arcium program.
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: executing_pool, checked by the arcium program.
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: computation_account, checked by the arcium program.
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_ORDERS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+}
+
+
+
+#[callback_accounts("add_together")]
+#[derive(Accounts)]
+pub struct AddTogetherCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+}
+
+#[callback_accounts("relay_offer_clone")]
+#[derive(Accounts)]
+pub struct RelayOfferCloneCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+}
+
+#[callback_accounts("confidential_deposit_native")]
+#[derive(Accounts)]
+pub struct ConfidentialDepositNativeCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub confidential_balance: Account<'info, ConfidentialBalance>,
+    #[account(
+        mut,
+        seeds = [b"confidential_pool"],
+        bump,
+    )]
+    /// CHECK: Pooled vault, refunds an aborted deposit
+    pub confidential_pool: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Balance owner, checked against the balance account
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("confidential_withdraw_native")]
+#[derive(Accounts)]
+pub struct ConfidentialWithdrawNativeCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_WITHDRAW_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub confidential_balance: Account<'info, ConfidentialBalance>,
+    #[account(
+        mut,
+        seeds = [b"confidential_pool"],
+        bump,
+    )]
+    /// CHECK: Pooled vault paying out the withdrawal
+    pub confidential_pool: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Balance owner, checked against the balance account
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("confidential_deposit_spl")]
+#[derive(Accounts)]
+pub struct ConfidentialDepositSplCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, has_one = mint @ ErrorCode::InvalidMint)]
+    pub confidential_balance: Account<'info, ConfidentialBalance>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// Pooled vault, refunds an aborted deposit
+    #[account(
+        mut,
+        seeds = [b"confidential_pool_token", mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, TokenAccount>,
+    /// Owner's token account, checked against the balance owner before any refund
+    #[account(mut)]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for the confidential token pools
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[callback_accounts("confidential_withdraw_spl")]
+#[derive(Accounts)]
+pub struct ConfidentialWithdrawSplCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_WITHDRAW_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, has_one = mint @ ErrorCode::InvalidMint)]
+    pub confidential_balance: Account<'info, ConfidentialBalance>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// Pooled vault paying out the withdrawal
+    #[account(
+        mut,
+        seeds = [b"confidential_pool_token", mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_token_vault: InterfaceAccount<'info, TokenAccount>,
+    /// Owner's token account, checked against the balance owner before any payout
+    #[account(mut)]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for the confidential token pools
+    pub escrow_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[callback_accounts("interchain_origin_evm_deposit_seller_spl")]
+#[derive(Accounts)]
+pub struct InterchainOriginEvmDepositSellerSplCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+}
+
+#[callback_accounts("finalize_interchain_origin_evm_offer")]
+#[derive(Accounts)]
+pub struct FinalizeInterchainOriginEvmOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub interchain_offer: Account<'info, InterchainOffer>,
+    #[account(mut)]
+    /// CHECK: Buyer that queued the finalize, recorded as the taker on a match
+    pub buyer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [b"evm_proof", interchain_offer.key().as_ref(), buyer.key().as_ref()],
+        bump = evm_proof.bump,
+    )]
+    pub evm_proof: Account<'info, TakerEvmProof>,
+}
+
+#[callback_accounts("deposit_seller_native")]
+#[derive(Accounts)]
+pub struct DepositSellerNativeCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+}
+
+#[callback_accounts("deposit_seller_spl")]
+#[derive(Accounts)]
+pub struct DepositSellerSplCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+}
+
+#[callback_accounts("finalize_intrachain_offer")]
+#[derive(Accounts)]
+pub struct FinalizeIntrachainOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"IntraChainoffer", maker.key().as_ref(), &intrachain_offer.id.to_le_bytes()],
+        bump = intrachain_offer.bump,
+        has_one = maker @ ErrorCode::NotOfferMaker,
+        has_one = payer @ ErrorCode::NotOfferPayer,
+    )]
+    pub intrachain_offer: Account<'info, IntraChainOffer>,
+    #[account(mut)]
+    /// CHECK: Seller receiving token B, checked against `maker` on the offer
+    pub maker: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Buyer receiving token A, bound to the buyer vault seeds
+    pub buyer: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Rent payer recorded on the offer, refunded when the last slice fills
+    pub payer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [b"pending_fill", intrachain_offer.key().as_ref(), buyer.key().as_ref()],
+        bump = pending_fill.bump,
+    )]
+    pub pending_fill: Account<'info, PendingFill>,
+    #[account(
+        mut,
+        seeds = [b"seller_vault", maker.key().as_ref(), &intrachain_offer.id.to_le_bytes()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding seller's token A
+    pub seller_vault: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"buyer_vault", intrachain_offer.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Escrow vault holding buyer's token B
+    pub buyer_vault: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub token_a_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub offer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub buyer_token_a_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_b_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub buyer_token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub seller_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Refund target for the token B escrow when the identity check fails
+    #[account(mut)]
+    pub buyer_token_b_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        seeds = [b"escrow_authority"],
+        bump,
+    )]
+    /// CHECK: PDA signing for buyer token vaults
+    pub escrow_authority: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = !exchange_config.paused.settlements @ ErrorCode::SettlementsPaused,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(mut)]
+    /// CHECK: Token A treasury, checked against the exchange config
+    pub token_a_treasury: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    /// CHECK: Token B treasury, checked against the exchange config
+    pub token_b_treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[callback_accounts("create_confidential_offer")]
+#[derive(Accounts)]
+pub struct CreateConfidentialOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CREATE_CONFIDENTIAL_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub confidential_offer: Account<'info, ConfidentialOffer>,
+}
+
+#[callback_accounts("match_confidential_offer")]
+#[derive(Accounts)]
+pub struct MatchConfidentialOfferCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_CONFIDENTIAL_OFFER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub confidential_offer: Account<'info, ConfidentialOffer>,
+    #[account(mut, constraint = confidential_offer.taker == Some(taker.key()) @ ErrorCode::Unauthorized)]
+    /// CHECK: Taker that queued the match, checked against the offer, gets the bond back
+    pub taker: UncheckedAccount<'info>,
+    #[account(mut, address = confidential_offer.maker)]
+    /// CHECK: Offer maker, collects the bond of a bid that does not settle
+    pub maker: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", confidential_offer.maker.as_ref(), confidential_offer.token_a_mint.as_ref()],
+        bump = maker_token_a_balance.bump,
+    )]
+    pub maker_token_a_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", confidential_offer.maker.as_ref(), confidential_offer.token_b_mint.as_ref()],
+        bump = maker_token_b_balance.bump,
+    )]
+    pub maker_token_b_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", taker.key().as_ref(), confidential_offer.token_a_mint.as_ref()],
+        bump = taker_token_a_balance.bump,
+    )]
+    pub taker_token_a_balance: Box<Account<'info, ConfidentialBalance>>,
+    #[account(
+        mut,
+        seeds = [b"confidential_balance", taker.key().as_ref(), confidential_offer.token_b_mint.as_ref()],
+        bump = taker_token_b_balance.bump,
+    )]
+    pub taker_token_b_balance: Box<Account<'info, ConfidentialBalance>>,
+}
+
+#[callback_accounts("place_sealed_bid")]
+#[derive(Accounts)]
+pub struct PlaceSealedBidCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_PLACE_SEALED_BID)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+}
+
+#[callback_accounts("resolve_sealed_auction")]
+#[derive(Accounts)]
+pub struct ResolveSealedAuctionCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RESOLVE_SEALED_AUCTION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub sealed_auction: Account<'info, SealedAuctionOffer>,
+}
+
+#[callback_accounts("add_order")]
+#[derive(Accounts)]
+pub struct AddOrderCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_ORDER)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+}
+
+#[callback_accounts("match_orders")]
+#[derive(Accounts)]
+pub struct MatchOrdersCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_MATCH_ORDERS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+}
+
+
+
+#[init_computation_definition_accounts("add_together", payer)]
+#[derive(Accounts)]
+pub struct InitAddTogetherCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("relay_offer_clone", payer)]
+#[derive(Accounts)]
+pub struct InitRelayOfferCloneCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("confidential_deposit_native", payer)]
+#[derive(Accounts)]
+pub struct InitConfidentialDepositNativeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("confidential_withdraw_native", payer)]
+#[derive(Accounts)]
+pub struct InitConfidentialWithdrawNativeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("confidential_deposit_spl", payer)]
+#[derive(Accounts)]
+pub struct InitConfidentialDepositSplCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("confidential_withdraw_spl", payer)]
+#[derive(Accounts)]
+pub struct InitConfidentialWithdrawSplCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
+#[derive(Accounts)]
+pub struct InitInterchainOriginEvmDepositSellerSplCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("finalize_interchain_origin_evm_offer", payer)]
+#[derive(Accounts)]
+pub struct InitFinalizeInterchainOriginEvmOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("deposit_seller_native", payer)]
+#[derive(Accounts)]
+pub struct InitDepositSellerNativeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("deposit_seller_spl", payer)]
+#[derive(Accounts)]
+pub struct InitDepositSellerSplCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
         mut,
-        address = derive_cluster_pda!(mxe_account)
+        address = derive_mxe_pda!()
     )]
-    pub cluster_account: Account<'info, Cluster>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("finalize_intrachain_offer", payer)]
+#[derive(Accounts)]
+pub struct InitFinalizeIntrachainOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
     #[account(
         mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+        address = derive_mxe_pda!()
     )]
-    pub pool_account: Account<'info, FeePool>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("create_confidential_offer", payer)]
+#[derive(Accounts)]
+pub struct InitCreateConfidentialOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
     )]
-    pub clock_account: Account<'info, ClockAccount>,
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("match_confidential_offer", payer)]
+#[derive(Accounts)]
+pub struct InitMatchConfidentialOfferCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("place_sealed_bid", payer)]
+#[derive(Accounts)]
+pub struct InitPlaceSealedBidCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("resolve_sealed_auction", payer)]
+#[derive(Accounts)]
+pub struct InitResolveSealedAuctionCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("add_order", payer)]
+#[derive(Accounts)]
+pub struct InitAddOrderCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("match_orders", payer)]
+#[derive(Accounts)]
+pub struct InitMatchOrdersCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [b"exchange_config"],
+        bump = exchange_config.bump,
+        constraint = exchange_config.authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub exchange_config: Box<Account<'info, ExchangeConfig>>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program.
+    /// Can't check it here as it's not initialized yet.
+    pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+
+#[event]
+pub struct SumEvent {
+    pub sum: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct RelayOfferClonedEvent {
+    pub acknowledged: u8,
+}
+
+#[event]
+pub struct ConfidentialDepositNativeEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    /// New balance encrypted to the owner
+    pub balance: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct ConfidentialWithdrawNativeEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    /// False when the balance did not cover `amount`, nothing was paid out
+    pub withdrawn: bool,
+    /// New balance encrypted to the owner
+    pub balance: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct ConfidentialDepositSplEvent {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    /// Tokens credited, net of any transfer fee on the way into the pool
+    pub amount: u64,
+    /// New balance encrypted to the owner
+    pub balance: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct ConfidentialWithdrawSplEvent {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// False when the balance did not cover `amount`, nothing was paid out
+    pub withdrawn: bool,
+    /// New balance encrypted to the owner
+    pub balance: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct InterchainOriginEvmDepositSellerSplEvent {
+    pub acknowledged: u8,
+}
+
+#[event]
+pub struct FinalizeInterchainOriginEvmOfferEvent {
+    pub acknowledged: u8,
+    pub offer_id: u64,
+    pub matched: bool,
+}
+
+#[event]
+pub struct DepositSellerNativeEvent {
+    pub acknowledged: u8,
+}
+
+#[event]
+pub struct DepositSellerSplEvent {
+    pub acknowledged: u8,
+}
+
+#[event]
+pub struct OfferCancelledEvent {
+    pub offer_id: u64,
+    pub refunded: u64,
+    pub refunded_tokens: u64,
+    pub buyer_refund: u64,
+    pub buyer_token_refund: u64,
+}
+
+#[event]
+pub struct BuyerVaultWithdrawnEvent {
+    pub offer: Pubkey,
+    pub buyer: Pubkey,
+    pub refunded: u64,
+    pub refunded_tokens: u64,
+}
+
+#[event]
+pub struct OfferExpiredEvent {
+    pub offer_id: u64,
+    pub seller_refund: u64,
+    pub buyer_refund: u64,
+    pub seller_token_refund: u64,
+    pub buyer_token_refund: u64,
+    pub bounty: u64,
+}
+
+#[event]
+pub struct FinalizeIntrachainOfferEvent {
+    pub acknowledged: u8,
+    pub offer_id: u64,
+    pub matched: bool,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
+
+#[event]
+pub struct PauseUpdatedEvent {
+    pub new_offers: bool,
+    pub settlements: bool,
+    pub relays: bool,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub previous: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct OutboundMessageEvent {
+    pub emitter: Pubkey,
+    pub sequence: u64,
+    pub bridge: BridgeAdapter,
+    pub payload: Vec<u8>,
+}
+
+#[event]
+pub struct HtlcClaimedEvent {
+    pub offer_id: u64,
+    pub taker: Pubkey,
+    pub preimage: [u8; 32],
+    pub token_a_amount: u64,
+}
+
+#[event]
+pub struct OfferDisputedEvent {
+    pub offer_id: u64,
+    pub raised_by: Pubkey,
+}
+
+#[event]
+pub struct OfferDisputeResolvedEvent {
+    pub offer_id: u64,
+    /// True when settlement may continue, false when the maker is refunded
+    pub released: bool,
+}
+
+#[event]
+pub struct HtlcReclaimedEvent {
+    pub offer_id: u64,
+    pub refunded_tokens: u64,
+}
+
+#[event]
+pub struct FeeCollectedEvent {
+    pub offer_id: u64,
+    pub mint: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct OfferFilledEvent {
+    pub offer_id: u64,
+    pub taker: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub filled_amount: u64,
+    pub remaining_amount: u64,
+}
+
+#[event]
+pub struct ConfidentialOfferCreatedEvent {
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub deadline: i64,
+}
+
+/// Settlement amounts encrypted once to the taker and once to the maker, zero when unmatched
+#[event]
+pub struct ConfidentialMatchEvent {
+    pub offer_id: u64,
+    pub taker: Pubkey,
+    /// True when the legs changed hands, false if the bid did not cross, a balance fell
+    /// short or the deadline passed before the callback
+    pub matched: bool,
+    /// True when the match used up the ask and settled the offer
+    pub offer_filled: bool,
+    pub taker_settlement: [[u8; 32]; 2],
+    pub taker_nonce: [u8; 16],
+    pub maker_settlement: [[u8; 32]; 2],
+    pub maker_nonce: [u8; 16],
+}
+
+#[event]
+pub struct SealedAuctionCreatedEvent {
+    pub auction_id: u64,
+    pub maker: Pubkey,
+    pub lot_amount: u64,
+    pub reserve_price: u64,
+    pub bid_collateral: u64,
+    pub bidding_ends_at: i64,
+}
+
+/// Who bid is public, what they bid is not
+#[event]
+pub struct SealedBidPlacedEvent {
+    pub auction_id: u64,
+    pub bidder: Pubkey,
+    pub bid_count: u8,
+}
+
+#[event]
+pub struct SealedAuctionResolvedEvent {
+    pub auction_id: u64,
+    /// `None` when no bid met the reserve
+    pub winner: Option<Pubkey>,
+    pub clearing_price: u64,
+}
+
+#[event]
+pub struct SealedAuctionSettledEvent {
+    pub auction_id: u64,
+    pub winner: Pubkey,
+    pub clearing_price: u64,
+    pub lot_amount: u64,
+}
+
+#[event]
+pub struct SealedAuctionSlashedEvent {
+    pub auction_id: u64,
+    pub winner: Pubkey,
+    /// Collateral paid to the maker because the winner never settled
+    pub slashed: u64,
+}
+
+#[event]
+pub struct BidCollateralWithdrawnEvent {
+    pub auction: Pubkey,
+    pub bidder: Pubkey,
+    pub refunded: u64,
 }
 
+/// Who rests an order is public, its side, price and size are not
+#[event]
+pub struct OrderAddedEvent {
+    pub order_book: Pubkey,
+    pub owner: Pubkey,
+    pub slot: u8,
+}
 
-#[queue_computation_accounts("relay_offer_clone", payer)]
-#[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, ciphertext_external_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct RelayOfferClone<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1,
-        seeds = [b"InterChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
-    )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+#[event]
+pub struct OrderCancelledEvent {
+    pub order_book: Pubkey,
+    pub owner: Pubkey,
+    pub slot: u8,
+    pub base_refund: u64,
+    pub quote_refund: u64,
+    /// Removed by someone other than the owner after the order expired
+    pub evicted: bool,
 }
 
-#[queue_computation_accounts("confidential_deposit_native", payer)]
-#[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct ConfidentialDepositNative<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+#[event]
+pub struct OrderFillEvent {
+    pub order_book: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    /// Midpoint of the two limit prices, quote units per base unit
+    pub price: u64,
+    /// Base units filled
+    pub size: u64,
+}
+
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The computation was aborted")]
+    AbortedComputation,
+    #[msg("Cluster not set")]
+    ClusterNotSet,
+    #[msg("Escrow vault does not hold the offered amount")]
+    InsufficientEscrow,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Seller identity has not been stored by the deposit callback yet")]
+    SellerIdentityPending,
+    #[msg("Offer is not open for takers")]
+    OfferNotOpen,
+    #[msg("Offer has no verified taker to settle with")]
+    OfferNotMatched,
+    #[msg("Offer is not waiting for a seller identity")]
+    OfferNotAwaitingIdentity,
+    #[msg("Offer has already been initialized")]
+    OfferAlreadyInitialized,
+    #[msg("Offer is already settled, cancelled or expired")]
+    OfferClosed,
+    #[msg("Illegal offer status transition")]
+    InvalidOfferStatus,
+    #[msg("Offer is disputed, waiting for the exchange authority")]
+    OfferDisputed,
+    #[msg("Offer is not disputed")]
+    OfferNotDisputed,
+    #[msg("Only the offer's maker or taker can do this")]
+    NotOfferParty,
+    #[msg("Offer deadline has passed")]
+    OfferExpired,
+    #[msg("Offer deadline has not passed yet")]
+    OfferNotExpired,
+    #[msg("Buyer and buyer vault must be passed together and match")]
+    InvalidBuyerVault,
+    #[msg("Offer still exists, its escrows are released through the offer")]
+    OfferStillOpen,
+    #[msg("Token accounts required for an SPL leg were not passed")]
+    MissingTokenAccounts,
+    #[msg("Mint does not match the one recorded on the offer")]
+    InvalidMint,
+    #[msg("Token vault does not belong to this offer")]
+    InvalidTokenVault,
+    #[msg("Token account is not owned by the counterparty")]
+    InvalidTokenRecipient,
+    #[msg("Non-transferable mints cannot be escrowed")]
+    NonTransferableMint,
+    #[msg("Mints with a permanent delegate cannot be escrowed")]
+    PermanentDelegateMint,
+    #[msg("Only the verified taker can settle this offer")]
+    NotOfferTaker,
+    #[msg("Account is not the maker recorded on the offer")]
+    NotOfferMaker,
+    #[msg("Account is not the rent payer recorded on the offer")]
+    NotOfferPayer,
+    #[msg("Fill amount is zero or exceeds what the offer has left")]
+    InvalidFillAmount,
+    #[msg("Fill is below the maker's minimum fill size")]
+    FillBelowMinimum,
+    #[msg("Signer is not the exchange authority")]
+    Unauthorized,
+    #[msg("Signer is not the proposed exchange authority")]
+    NotPendingAuthority,
+    #[msg("New offers are paused")]
+    NewOffersPaused,
+    #[msg("Settlements are paused")]
+    SettlementsPaused,
+    #[msg("Cross-chain relays are paused")]
+    RelaysPaused,
+    #[msg("Guardian set is empty, too large or not newer than the current one")]
+    InvalidGuardianSet,
+    #[msg("Not enough guardian signatures for quorum")]
+    NoGuardianQuorum,
+    #[msg("Guardian signature is invalid or out of order")]
+    InvalidGuardianSignature,
+    #[msg("VAA body or offer payload is malformed or does not match the instruction")]
+    InvalidVaaPayload,
+    #[msg("Message emitter does not match the chain's bridge route")]
+    UnknownEmitter,
+    #[msg("Bridge route cannot verify inbound messages")]
+    UnsupportedBridge,
+    #[msg("Message or offer does not meet the route's finality requirement")]
+    InsufficientFinality,
+    #[msg("Chain config has too many tokens or an empty size range")]
+    InvalidChainConfig,
+    #[msg("Offers for this chain are disabled")]
+    ChainDisabled,
+    #[msg("Token is not allowed for this chain")]
+    TokenNotAllowed,
+    #[msg("Offer size is outside the chain's limits")]
+    OfferSizeOutOfRange,
+    #[msg("Non-HTLC interchain offers need a bridge route")]
+    MissingBridgeRoute,
+    #[msg("HTLC timelock ends before the offer deadline")]
+    InvalidHtlcTerms,
+    #[msg("HTLC offers settle only through claim_htlc or reclaim_htlc")]
+    HtlcOffer,
+    #[msg("Offer has no hashlock")]
+    NotHtlcOffer,
+    #[msg("Preimage does not match the hashlock")]
+    InvalidPreimage,
+    #[msg("No secp256k1 instruction precedes this one")]
+    MissingEvmProof,
+    #[msg("secp256k1 instruction does not prove the EVM address over the offer message")]
+    InvalidEvmProof,
+    #[msg("Fee exceeds the protocol cap")]
+    FeeTooHigh,
+    #[msg("Too many treasuries in the exchange config")]
+    TooManyTreasuries,
+    #[msg("No treasury configured or passed for this fee")]
+    MissingTreasury,
+    #[msg("Treasury does not match the exchange config")]
+    InvalidTreasury,
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("A computation on this confidential balance is still pending")]
+    BalanceUpdatePending,
+    #[msg("Callback does not match the pending balance update")]
+    InvalidBalanceUpdate,
+    #[msg("Nothing has been deposited to this confidential balance yet")]
+    NoConfidentialBalance,
+    #[msg("Token A and token B must be different mints")]
+    SameMint,
+    #[msg("Makers cannot take their own offer")]
+    SelfMatch,
+    #[msg("A match on this offer is still pending")]
+    MatchPending,
+    #[msg("Settlement deadline must come after the bidding window")]
+    InvalidAuctionWindow,
+    #[msg("Auction is not in the right state for this action")]
+    InvalidAuctionStatus,
+    #[msg("Bidding window has closed")]
+    BiddingClosed,
+    #[msg("Bidding window is still open")]
+    BiddingOpen,
+    #[msg("A computation on this auction is still pending")]
+    AuctionComputationPending,
+    #[msg("Auction has no bid slots left")]
+    AuctionFull,
+    #[msg("Bidder already has a bid on this auction")]
+    AlreadyBid,
+    #[msg("Auction has no bids to resolve")]
+    NoBids,
+    #[msg("Only the winning bidder can settle this auction")]
+    NotAuctionWinner,
+    #[msg("Auction has bids or a winner that can still settle")]
+    AuctionNotClosable,
+    #[msg("Bid collateral must cover the reserve price")]
+    CollateralBelowReserve,
+    #[msg("Bid collateral may still be needed to settle the auction")]
+    CollateralLocked,
+    #[msg("The winner's bid vault is required to slash an unsettled auction")]
+    MissingBidVault,
+    #[msg("Order book has no free slots")]
+    BookFull,
+    #[msg("A computation on this order book is still pending")]
+    BookUpdatePending,
+    #[msg("Callback does not match the pending order book update")]
+    InvalidBookUpdate,
+    #[msg("Order book has no orders to match")]
+    EmptyOrderBook,
+    #[msg("Order slot is empty")]
+    EmptyOrderSlot,
+    #[msg("Only the owner can cancel an order before it expires")]
+    OrderNotExpired,
+    #[msg("Order time to live must be positive")]
+    InvalidOrderTtl,
+}
+
+/// Moves lamports out of a program-owned escrow vault.
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_balance = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::InsufficientEscrow)?;
+    let to_balance = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// Accounts the confidential balance callbacks update and pay out to
+fn balance_callback_accounts(balance: Pubkey, pool: Pubkey, owner: Pubkey) -> [CallbackAccount; 3] {
+    [balance, pool, owner].map(|pubkey| CallbackAccount {
+        pubkey,
+        is_writable: true,
+    })
+}
+
+/// Accounts the confidential token balance callbacks update and pay out of
+fn token_balance_callback_accounts(
+    balance: Pubkey,
+    mint: Pubkey,
+    pool_token_vault: Pubkey,
+    owner_token_account: Pubkey,
+    escrow_authority: Pubkey,
+    token_program: Pubkey,
+) -> [CallbackAccount; 6] {
+    [
+        (balance, true),
+        (mint, false),
+        (pool_token_vault, true),
+        (owner_token_account, true),
+        (escrow_authority, false),
+        (token_program, false),
+    ]
+    .map(|(pubkey, is_writable)| CallbackAccount { pubkey, is_writable })
+}
+
+fn require_before_deadline(deadline: i64) -> Result<()> {
+    require!(Clock::get()?.unix_timestamp <= deadline, ErrorCode::OfferExpired);
+    Ok(())
+}
+
+fn require_after_deadline(deadline: i64) -> Result<()> {
+    require!(Clock::get()?.unix_timestamp > deadline, ErrorCode::OfferNotExpired);
+    Ok(())
+}
+
+/// Escrows of an offer being cancelled or expired, borrowed from either offer kind's context.
+struct OfferEscrows<'a, 'info> {
+    /// Token A and B of the offer, the default key for native legs
+    token_a: Pubkey,
+    token_b: Pubkey,
+    is_taker_native: bool,
+    seller_vault: &'a UncheckedAccount<'info>,
+    token_program: Option<&'a Interface<'info, TokenInterface>>,
+    token_a_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    offer_token_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    seller_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    buyer: Option<&'a UncheckedAccount<'info>>,
+    buyer_vault: Option<&'a UncheckedAccount<'info>>,
+    token_b_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    buyer_token_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    buyer_token_b_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    escrow_authority: &'a UncheckedAccount<'info>,
+    escrow_authority_bump: u8,
+    hook_accounts: &'a [AccountInfo<'info>],
+}
+
+/// What `refund_and_close` returned to each party.
+struct EscrowRefunds {
+    seller_refund: u64,
+    seller_token_refund: u64,
+    buyer_refund: u64,
+    buyer_token_refund: u64,
+    bounty: u64,
+}
+
+impl EscrowRefunds {
+    fn cancelled(&self, offer_id: u64) -> OfferCancelledEvent {
+        OfferCancelledEvent {
+            offer_id,
+            refunded: self.seller_refund,
+            refunded_tokens: self.seller_token_refund,
+            buyer_refund: self.buyer_refund,
+            buyer_token_refund: self.buyer_token_refund,
+        }
+    }
+
+    fn expired(&self, offer_id: u64) -> OfferExpiredEvent {
+        OfferExpiredEvent {
+            offer_id,
+            seller_refund: self.seller_refund,
+            buyer_refund: self.buyer_refund,
+            seller_token_refund: self.seller_token_refund,
+            buyer_token_refund: self.buyer_token_refund,
+            bounty: self.bounty,
+        }
+    }
+}
+
+/// Returns every escrow of an offer to whoever funded it, vault rent included, and pays up
+/// to `bounty` lamports of the offer's rent to `caller`. The offer account itself is closed
+/// by the instruction's `close` constraint.
+fn refund_and_close<'info>(
+    offer: &AccountInfo<'info>,
+    offer_seeds: &[&[u8]],
+    escrows: &OfferEscrows<'_, 'info>,
+    recipient: &AccountInfo<'info>,
+    caller: &AccountInfo<'info>,
+    bounty: u64,
+) -> Result<EscrowRefunds> {
+    let seller_refund = drain_vault(&escrows.seller_vault.to_account_info(), recipient)?;
+
+    let seller_token_refund = if escrows.token_a == Pubkey::default() {
+        0
+    } else {
+        let token_program = escrows.token_program.ok_or(ErrorCode::MissingTokenAccounts)?;
+        let leg = SplLeg::new(
+            escrows.token_a_mint,
+            escrows.offer_token_vault,
+            escrows.seller_token_account,
+            escrows.token_a,
+            get_associated_token_address_with_program_id(&offer.key(), &escrows.token_a, &token_program.key()),
+            recipient.key(),
+            0,
+        )?;
+        leg.drain(
+            token_program,
+            recipient.clone(),
+            offer.clone(),
+            offer_seeds,
+            escrows.hook_accounts,
+        )?
+    };
+
+    // A buyer that already funded its leg gets it back with the offer, any other
+    // buyer reclaims theirs with `withdraw_buyer_vault` once the offer is closed
+    let buyer_refund = refund_buyer_vault(&offer.key(), escrows.buyer, escrows.buyer_vault)?;
+    let buyer_token_refund = match (escrows.buyer, escrows.buyer_token_vault) {
+        (Some(buyer), Some(_)) if !escrows.is_taker_native => {
+            let token_program = escrows.token_program.ok_or(ErrorCode::MissingTokenAccounts)?;
+            let leg = SplLeg::new(
+                escrows.token_b_mint,
+                escrows.buyer_token_vault,
+                escrows.buyer_token_b_account,
+                escrows.token_b,
+                buyer_token_vault_address(&offer.key(), &buyer.key()),
+                buyer.key(),
+                0,
+            )?;
+            let bump = [escrows.escrow_authority_bump];
+            leg.drain(
+                token_program,
+                buyer.to_account_info(),
+                escrows.escrow_authority.to_account_info(),
+                &[b"escrow_authority", &bump],
+                escrows.hook_accounts,
+            )?
+        }
+        (_, None) => 0,
+        _ => return err!(ErrorCode::InvalidBuyerVault),
+    };
+
+    let bounty = offer.lamports().min(bounty);
+    if bounty > 0 {
+        transfer_lamports(offer, caller, bounty)?;
+    }
+
+    Ok(EscrowRefunds {
+        seller_refund,
+        seller_token_refund,
+        buyer_refund,
+        buyer_token_refund,
+        bounty,
+    })
+}
+
+/// Returns a buyer's native escrow for `offer`, re-deriving the vault from the buyer.
+fn refund_buyer_vault<'info>(
+    offer: &Pubkey,
+    buyer: Option<&UncheckedAccount<'info>>,
+    buyer_vault: Option<&UncheckedAccount<'info>>,
+) -> Result<u64> {
+    match (buyer, buyer_vault) {
+        (Some(buyer), Some(buyer_vault)) => {
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"buyer_vault", offer.as_ref(), buyer.key().as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(buyer_vault.key(), expected, ErrorCode::InvalidBuyerVault);
+            drain_vault(buyer_vault, buyer)
+        }
+        (None, None) => Ok(0),
+        _ => err!(ErrorCode::InvalidBuyerVault),
+    }
+}
+
+/// Lamports a native vault PDA holds beyond its own rent-exempt minimum.
+fn escrowed_lamports(vault: &AccountInfo) -> Result<u64> {
+    let rent = Rent::get()?.minimum_balance(vault.data_len());
+    Ok(vault.lamports().saturating_sub(rent))
+}
+
+/// Empties a vault PDA, including its rent, so the runtime reclaims the account.
+fn drain_vault(vault: &AccountInfo, to: &AccountInfo) -> Result<u64> {
+    let amount = vault.lamports();
+    transfer_lamports(vault, to, amount)?;
+    Ok(amount)
+}
+
+/// What a buyer signs with their EVM key to take `offer` as `taker`: readable text behind
+/// the EIP-191 prefix, so any wallet can produce it with `personal_sign`.
+fn evm_ownership_message(offer: &Pubkey, taker: &Pubkey) -> Vec<u8> {
+    let text = format!("{}\nProgram: {}\nOffer: {}\nTaker: {}", EVM_PROOF_DOMAIN, crate::ID, offer, taker);
+    format!("\x19Ethereum Signed Message:\n{}{}", text.len(), text).into_bytes()
+}
+
+/// Interchain identity commitment of an EVM address: keccak256 of the address left-padded
+/// to 256 bits, split into four little-endian limbs like every other commitment
+fn evm_identity_commitment(address: &[u8; 20]) -> [u64; 4] {
+    let mut padded = [0u8; 32];
+    padded[12..].copy_from_slice(address);
+    let digest = keccak::hash(&padded).to_bytes();
+    std::array::from_fn(|i| {
+        let mut limb = [0u8; 8];
+        limb.copy_from_slice(&digest[i * 8..i * 8 + 8]);
+        u64::from_le_bytes(limb)
+    })
+}
+
+/// Checks that the instruction before the current one is a secp256k1 precompile call
+/// verifying one signature by `evm_address` over `message`, with all data inline.
+fn verify_evm_signature(instructions: &AccountInfo, evm_address: &[u8; 20], message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, ErrorCode::MissingEvmProof);
+    let index = current - 1;
+    let ix = load_instruction_at_checked(index as usize, instructions)?;
+    require_keys_eq!(ix.program_id, secp256k1_program::ID, ErrorCode::MissingEvmProof);
+
+    // count u8 | signature_offset u16 | signature_ix u8 | address_offset u16 | address_ix u8
+    // | message_offset u16 | message_size u16 | message_ix u8, all little-endian
+    let data = &ix.data;
+    require!(data.len() >= 12 && data[0] == 1, ErrorCode::InvalidEvmProof);
+    require!(
+        [data[3], data[6], data[11]].iter().all(|i| *i as u16 == index),
+        ErrorCode::InvalidEvmProof
+    );
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+    let (address_offset, message_offset, message_size) = (read_u16(4), read_u16(7), read_u16(9));
+    let signed_address = data.get(address_offset..address_offset + 20);
+    let signed_message = data.get(message_offset..message_offset + message_size);
+    require!(
+        signed_address == Some(&evm_address[..]) && signed_message == Some(message),
+        ErrorCode::InvalidEvmProof
+    );
+    Ok(())
+}
+
+/// Protocol fee of `bps` on `amount`, rounded down in the payer's favour.
+fn fee_amount(amount: u64, bps: u16) -> Result<u64> {
+    let fee = (amount as u128) * (bps as u128) / (BPS_DENOMINATOR as u128);
+    u64::try_from(fee).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+fn emit_fee(offer_id: u64, mint: Pubkey, treasury: Option<&AccountInfo>, amount: u64) {
+    if let Some(treasury) = treasury {
+        emit!(FeeCollectedEvent {
+            offer_id,
+            mint,
+            treasury: treasury.key(),
+            amount,
+        });
+    }
+}
+
+/// `amount * numerator / denominator`, rounded up so takers never underpay a slice.
+fn pro_rata_ceil(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    require!(denominator > 0, ErrorCode::InvalidFillAmount);
+    let scaled = (amount as u128) * (numerator as u128);
+    u64::try_from(scaled.div_ceil(denominator as u128)).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+/// Token B owed for the next `fill_amount` of token A. Priced on the cumulative fill,
+/// so the slices always add up to exactly `token_b_wanted_amount`.
+fn fill_price(offer: &IntraChainOffer, fill_amount: u64) -> Result<u64> {
+    let filled = offer.filled_amount.checked_add(fill_amount).ok_or(ErrorCode::MathOverflow)?;
+    let paid_before = pro_rata_ceil(offer.filled_amount, offer.token_b_wanted_amount, offer.token_a_offered_amount)?;
+    let paid_after = pro_rata_ceil(filled, offer.token_b_wanted_amount, offer.token_a_offered_amount)?;
+    Ok(paid_after - paid_before)
+}
+
+/// `token_b_mint` recorded on a new offer: the passed mint, or default for native takers.
+fn taker_mint(token_b_mint: Option<&InterfaceAccount<Mint>>, is_taker_native: bool) -> Result<Pubkey> {
+    match (is_taker_native, token_b_mint) {
+        (true, _) => Ok(Pubkey::default()),
+        (false, Some(mint)) => {
+            require_escrowable_mint(&mint.to_account_info())?;
+            Ok(mint.key())
+        }
+        (false, None) => err!(ErrorCode::MissingTokenAccounts),
+    }
+}
+
+/// Buyer token vaults are keyed by the offer account, so offers of different makers never share one
+fn buyer_token_vault_address(offer: &Pubkey, buyer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"buyer_token_vault", offer.as_ref(), buyer.as_ref()],
+        &crate::ID,
+    )
+    .0
+}
+
+/// Callbacks take a fixed account list, so absent optional accounts are passed as the program id.
+fn optional_callback_account(pubkey: Option<Pubkey>, is_writable: bool) -> CallbackAccount {
+    match pubkey {
+        Some(pubkey) => CallbackAccount { pubkey, is_writable },
+        None => CallbackAccount {
+            pubkey: crate::ID,
+            is_writable: false,
+        },
+    }
+}
+
+/// Rejects Token-2022 mints whose extensions let someone else move or freeze escrowed tokens.
+fn require_escrowable_mint(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    require!(
+        state.get_extension::<NonTransferable>().is_err(),
+        ErrorCode::NonTransferableMint
+    );
+    if let Ok(extension) = state.get_extension::<PermanentDelegate>() {
+        require!(
+            Option::<Pubkey>::from(extension.delegate).is_none(),
+            ErrorCode::PermanentDelegateMint
+        );
+    }
+    Ok(())
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(state.get_extension::<TransferFeeConfig>().ok().copied())
+}
+
+/// Transfer fee Token-2022 withholds from `amount` this epoch, zero for fee-less mints.
+fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    match transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or_else(|| error!(ErrorCode::MathOverflow)),
+        None => Ok(0),
+    }
+}
+
+/// Fee to add on top of `net` so the recipient ends up with exactly `net`.
+fn inverse_transfer_fee(mint: &AccountInfo, net: u64) -> Result<u64> {
+    match transfer_fee_config(mint)? {
+        Some(config) => config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, net)
+            .ok_or_else(|| error!(ErrorCode::MathOverflow)),
+        None => Ok(0),
+    }
+}
+
+/// What a vault holding `vault_amount` can deliver once the outbound transfer fee is withheld.
+fn deliverable_amount(mint: &AccountInfo, vault_amount: u64) -> Result<u64> {
+    Ok(vault_amount - transfer_fee(mint, vault_amount)?)
+}
+
+/// Validated accounts of one SPL escrow leg: the vault and who it pays out to.
+struct SplLeg<'a, 'info> {
+    mint: &'a InterfaceAccount<'info, Mint>,
+    vault: &'a InterfaceAccount<'info, TokenAccount>,
+    recipient: &'a InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'a, 'info> SplLeg<'a, 'info> {
+    fn new(
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        recipient: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+        expected_mint: Pubkey,
+        expected_vault: Pubkey,
+        recipient_owner: Pubkey,
+        amount: u64,
+    ) -> Result<Self> {
+        let (Some(mint), Some(vault), Some(recipient)) = (mint, vault, recipient) else {
+            return err!(ErrorCode::MissingTokenAccounts);
+        };
+        require_keys_eq!(mint.key(), expected_mint, ErrorCode::InvalidMint);
+        require_keys_eq!(vault.key(), expected_vault, ErrorCode::InvalidTokenVault);
+        require_keys_eq!(recipient.mint, expected_mint, ErrorCode::InvalidMint);
+        require_keys_eq!(recipient.owner, recipient_owner, ErrorCode::InvalidTokenRecipient);
+        require!(vault.amount >= amount, ErrorCode::InsufficientEscrow);
+        Ok(Self { mint, vault, recipient })
+    }
+
+    /// PDA-signed `transfer_checked` out of the vault, forwarding transfer-hook accounts.
+    /// Returns what the recipient receives after any transfer fee.
+    fn transfer(
+        &self,
+        token_program: &Interface<'info, TokenInterface>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[u8]],
+        hook_accounts: &[AccountInfo<'info>],
+        amount: u64,
+    ) -> Result<u64> {
+        self.transfer_to(
+            token_program,
+            self.recipient.to_account_info(),
+            authority,
+            signer_seeds,
+            hook_accounts,
+            amount,
+        )
+    }
+
+    /// Same as `transfer`, grossed up by the transfer fee so the recipient receives exactly `net`
+    fn transfer_net(
+        &self,
+        token_program: &Interface<'info, TokenInterface>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[u8]],
+        hook_accounts: &[AccountInfo<'info>],
+        net: u64,
+    ) -> Result<u64> {
+        let gross = net
+            .checked_add(inverse_transfer_fee(&self.mint.to_account_info(), net)?)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.transfer(token_program, authority, signer_seeds, hook_accounts, gross)
+    }
+
+    /// Same as `transfer`, to a token account other than the leg's recipient
+    fn transfer_to(
+        &self,
+        token_program: &Interface<'info, TokenInterface>,
+        recipient: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[u8]],
+        hook_accounts: &[AccountInfo<'info>],
+        amount: u64,
+    ) -> Result<u64> {
+        spl_token_2022::onchain::invoke_transfer_checked(
+            &token_program.key(),
+            self.vault.to_account_info(),
+            self.mint.to_account_info(),
+            recipient,
+            authority,
+            hook_accounts,
+            amount,
+            self.mint.decimals,
+            &[signer_seeds],
+        )?;
+        let fee = transfer_fee(&self.mint.to_account_info(), amount)?;
+        Ok(amount.saturating_sub(fee))
+    }
+
+    /// Empties the vault into the recipient and closes it, sending the rent to `rent_to`.
+    /// Returns the amount that left the vault.
+    fn drain(
+        &self,
+        token_program: &Interface<'info, TokenInterface>,
+        rent_to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[u8]],
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<u64> {
+        let amount = self.vault.amount;
+        if amount > 0 {
+            self.transfer(token_program, authority.clone(), signer_seeds, hook_accounts, amount)?;
+        }
+        // Fees withheld on the vault block CloseAccount until harvested to the mint
+        if transfer_fee_config(&self.mint.to_account_info())?.is_some() {
+            harvest_withheld_tokens_to_mint(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    HarvestWithheldTokensToMint {
+                        token_program_id: token_program.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                    },
+                ),
+                vec![self.vault.to_account_info()],
+            )?;
+        }
+        token_interface::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                destination: rent_to,
+                authority,
+            },
+            &[signer_seeds],
+        ))?;
+        Ok(amount)
+    }
+}
+
+// PDA account structures for on-chain state (matching original Anchor program)
+#[account]
+#[derive(InitSpace)]
+pub struct IntraChainOffer {
+    pub id: u64,
+    /// Seller that owns the offer and receives token B, seeds the offer PDA
+    pub maker: Pubkey,
+    /// Account that funded the offer rent, refunded when the offer closes
+    pub payer: Pubkey,
+    /// Buyer whose identity claim was verified by MPC, the latest filler on partial fills
+    pub taker: Option<Pubkey>,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    /// Smallest slice of token A a taker may fill, except for the final slice
+    pub min_fill_amount: u64,
+    /// Token A already delivered to takers
+    pub filled_amount: u64,
+    /// Token A still available, the offer closes when it reaches zero
+    pub remaining_amount: u64,
+    pub is_taker_native: bool,
+    /// Mint escrowed by the seller, `Pubkey::default()` for native SOL
+    pub token_a_mint: Pubkey,
+    /// Mint paid by the taker, `Pubkey::default()` when `is_taker_native`
+    pub token_b_mint: Pubkey,
+    pub deadline: i64,
+    pub bump: u8,
+    /// Seller identity commitment, MXE-encrypted by the deposit callback
+    pub seller_identity: EncryptedIdentity,
+    pub status: OfferStatus,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct InterchainOffer {
+    pub id: u64,
+    /// Seller that owns the offer and receives token B, seeds the offer PDA
+    pub maker: Pubkey,
+    /// Account that funded the offer rent, refunded when the offer closes
+    pub payer: Pubkey,
+    /// Buyer whose identity claim was verified by MPC, set on match
+    pub taker: Option<Pubkey>,
+    /// EVM address the taker proved control of, set on match
+    pub taker_evm_address: Option<[u8; 20]>,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_taker_native: bool,
+    /// Mint escrowed by the seller, `Pubkey::default()` for native SOL
+    pub token_a_mint: Pubkey,
+    /// Token A is escrowed on the origin chain and released there by the settlement message
+    pub origin_escrowed: bool,
+    /// Mint paid by the taker, `Pubkey::default()` when `is_taker_native`
+    pub token_b_mint: Pubkey,
+    pub chain_id: u64,
+    pub deadline: i64,
+    pub bump: u8,
+    /// Seller identity commitment, MXE-encrypted by the relay/deposit callback. A taker
+    /// matches it with the keccak256 commitment of the EVM address it proves
+    pub seller_identity: EncryptedIdentity,
+    pub status: OfferStatus,
+    /// Set for bridgeless atomic swaps, which settle only via `claim_htlc` or `reclaim_htlc`
+    pub htlc: Option<HtlcTerms>,
+}
+
+/// Shielded balance of one mint at `[b"confidential_balance", owner, mint]`, backed by that
+/// mint's pool vault. Lamports live under `NATIVE_BALANCE_MINT` in the lamport pool
+#[account]
+#[derive(InitSpace)]
+pub struct ConfidentialBalance {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    /// MXE-encrypted amount, `None` until the first deposit lands
+    pub balance: Option<EncryptedBalance>,
+    /// Update queued to MPC, further updates wait for its callback
+    pub pending: Option<BalanceUpdate>,
+    pub bump: u8,
+}
+
+impl ConfidentialBalance {
+    /// Holds the balance for `update` until its callback, one update at a time
+    pub fn lock(&mut self, update: BalanceUpdate) -> Result<()> {
+        require!(self.pending.is_none(), ErrorCode::BalanceUpdatePending);
+        self.pending = Some(update);
+        Ok(())
+    }
+
+    /// Releases the hold taken for `update`, rejecting a callback it was not taken for
+    pub fn unlock(&mut self, update: BalanceUpdate) -> Result<()> {
+        require!(self.pending.take() == Some(update), ErrorCode::InvalidBalanceUpdate);
+        Ok(())
+    }
 }
 
-#[queue_computation_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
-#[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, chain_id: u64, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct InterchainOriginEvmDepositSellerSpl<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 8 + 1,
-        seeds = [b"InterChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
-    )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BalanceUpdate {
+    /// Amount already moved into the pool, refunded if the computation aborts
+    Deposit(u64),
+    /// Amount to pay out if the balance covers it
+    Withdraw(u64),
+    /// One of the four legs of a confidential offer match
+    Match,
 }
 
-#[queue_computation_accounts("finalize_interchain_origin_evm_offer", payer)]
-#[derive(Accounts)]
-#[instruction(id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct FinalizeInterchainOriginEvmOffer<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+/// Offer whose amounts only the MXE can read, matched by `match_confidential_offer`
+#[account]
+#[derive(InitSpace)]
+pub struct ConfidentialOffer {
+    pub id: u64,
+    /// Seller that owns the offer, seeds the offer PDA
+    pub maker: Pubkey,
+    /// Account that funded the offer rent, refunded when the offer closes
+    pub payer: Pubkey,
+    /// Taker whose bid is being matched, kept once it settles the offer
+    pub taker: Option<Pubkey>,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub deadline: i64,
+    /// x25519 key the maker's copy of each match result is encrypted to
+    pub maker_encryption_key: [u8; 32],
+    /// Token A offered and token B wanted, MXE-encrypted by the create callback
+    pub terms: EncryptedTerms,
+    pub status: OfferStatus,
+    pub bump: u8,
 }
 
-#[queue_computation_accounts("deposit_seller_native", payer)]
-#[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct DepositSellerNative<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 1,
-        seeds = [b"IntraChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
-    )]
-    pub intrachain_offer: Account<'info, IntraChainOffer>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+/// Blind auction of a native SOL lot at `[b"SealedAuction", maker, id]`
+#[account]
+#[derive(InitSpace)]
+pub struct SealedAuctionOffer {
+    pub id: u64,
+    /// Seller that escrowed the lot and receives the clearing price
+    pub maker: Pubkey,
+    /// Account that funded the auction rent, refunded when it closes
+    pub payer: Pubkey,
+    /// Lamports escrowed in the auction vault
+    pub lot_amount: u64,
+    /// Lowest bid that can win
+    pub reserve_price: u64,
+    /// Lamports every bidder escrows, one tier for all so it reveals nothing per bid.
+    /// Bids above it count as the tier
+    pub bid_collateral: u64,
+    pub bidding_ends_at: i64,
+    /// The winner must settle by this time, after which the maker may reclaim the lot and
+    /// the winner's collateral
+    pub settle_deadline: i64,
+    /// Buyers in bid order, slot `i` of `bids` belongs to `bidders[i]`
+    #[max_len(8)]
+    pub bidders: Vec<Pubkey>,
+    /// MXE-encrypted bid table, `None` until the first bid lands
+    pub bids: Option<EncryptedBids>,
+    /// Bidder whose bid computation is in flight
+    pub pending_bidder: Option<Pubkey>,
+    pub winner: Option<Pubkey>,
+    /// Winning bid, revealed by the resolve callback
+    pub clearing_price: u64,
+    pub status: AuctionStatus,
+    pub bump: u8,
 }
 
-#[queue_computation_accounts("deposit_seller_spl", payer)]
-#[derive(Accounts)]
-#[instruction(id: u64, token_b_wanted_amount: u64, token_a_offered_amount: u64, is_taker_native: bool, deadline: i64, ciphertext_seller_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct DepositSellerSpl<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + 8 + 8 + 8 + 1 + 8 + 1,
-        seeds = [b"IntraChainoffer", payer.key().as_ref(), &id.to_le_bytes()],
-        bump
-    )]
-    pub intrachain_offer: Account<'info, IntraChainOffer>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug, InitSpace)]
+pub enum AuctionStatus {
+    /// Taking bids until `bidding_ends_at`
+    #[default]
+    Bidding,
+    /// Winner computation queued
+    Resolving,
+    /// Winner revealed, waiting for them to settle
+    Awarded,
+    /// No bid met the reserve
+    Unsold,
+    /// Lot delivered and clearing price paid
+    Settled,
+}
+
+/// Dark pool for one market at `[b"order_book", base_mint, quote_mint]`, escrow held in
+/// `[b"order_book_vault", order_book, mint]` vaults
+#[account]
+#[derive(InitSpace)]
+pub struct OrderBook {
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    /// Order in each slot, `None` for a free slot
+    pub slots: [Option<BookSlot>; MAX_BOOK_ORDERS],
+    /// MXE-encrypted side, price and size per slot, `None` until the first order lands
+    pub orders: Option<EncryptedBook>,
+    /// Update queued to MPC, further updates wait for its callback
+    pub pending: Option<BookUpdate>,
+    /// Seconds after which anyone may evict an order and refund its owner
+    pub order_ttl: i64,
+    pub bump: u8,
 }
 
-#[queue_computation_accounts("finalize_intrachain_offer", payer)]
-#[derive(Accounts)]
-#[instruction(id: u64, ciphertext_buyer_identity_hash: [u8; 32], pub_key: [u8; 32], nonce: u128, computation_offset: u64)]
-pub struct FinalizeIntrachainOffer<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
-    )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(
-        mut,
-        address = derive_mempool_pda!()
-    )]
-    /// CHECK: mempool_account, checked by the arcium program.
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_execpool_pda!()
-    )]
-    /// CHECK: executing_pool, checked by the arcium program.
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(
-        mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: computation_account, checked by the arcium program.
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(
-        mut,
-        address = derive_cluster_pda!(mxe_account)
-    )]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(
-        mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
-    )]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
+/// Public side of one resting order, its escrow tracks fills so a cancel refunds what is owed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct BookSlot {
+    pub owner: Pubkey,
+    pub base_escrow: u64,
+    pub quote_escrow: u64,
+    pub placed_at: i64,
+    /// Set once the order is in the encrypted book, only resting orders are matched
+    pub resting: bool,
 }
 
-// === ESCROW VAULT ACCOUNT CONTEXTS ===
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BookUpdate {
+    /// Order being written into `slot`, already reserved for its owner
+    AddOrder { slot: u8 },
+    /// Book being crossed
+    Match,
+}
 
-#[derive(Accounts)]
-#[instruction(offer_id: u64)]
-pub struct ExecuteIntrachainSwap<'info> {
-    #[account(
-        seeds = [b"IntraChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump = intrachain_offer.bump,
-    )]
-    pub intrachain_offer: Account<'info, IntraChainOffer>,
-    
-    #[account(mut)]
-    pub seller: Signer<'info>,
-    
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-    
-    #[account(
-        mut,
-        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
-    )]
-    /// CHECK: Escrow vault holding seller's token A
-    pub seller_vault: UncheckedAccount<'info>,
-    
-    #[account(
-        mut,
-        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
-    )]
-    /// CHECK: Escrow vault holding buyer's token B
-    pub buyer_vault: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+/// Hashlock and timelock of an HTLC offer, mirrored by the taker's lock on the origin chain.
+/// Timelocks nest: offer deadline <= origin-chain lock expiry < `timelock`, so the maker must
+/// reveal the secret on the origin chain while the taker can still use it here.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct HtlcTerms {
+    /// sha256 of the secret, the hash EVM HTLC contracts check via precompile
+    pub hashlock: [u8; 32],
+    /// Claims close and the maker can reclaim after this time
+    pub timelock: i64,
 }
 
-#[derive(Accounts)]
-#[instruction(offer_id: u64)]
-pub struct ExecuteInterchainSwap<'info> {
-    #[account(
-        seeds = [b"InterChainoffer", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump = interchain_offer.bump,
-    )]
-    pub interchain_offer: Account<'info, InterchainOffer>,
-    
-    #[account(mut)]
-    pub seller: Signer<'info>,
-    
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-    
-    #[account(
-        mut,
-        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
-    )]
-    /// CHECK: Escrow vault holding seller's token A
-    pub seller_vault: UncheckedAccount<'info>,
-    
-    #[account(
-        mut,
-        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
-    )]
-    /// CHECK: Escrow vault holding buyer's token B
-    pub buyer_vault: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+impl HtlcTerms {
+    /// The taker must be able to claim for at least as long as the offer can be matched.
+    /// The origin-chain lock sits between the two and is not visible here, takers check
+    /// its expiry against `timelock` before locking token B
+    fn validate(&self, deadline: i64) -> Result<()> {
+        require!(self.timelock >= deadline, ErrorCode::InvalidHtlcTerms);
+        Ok(())
+    }
 }
 
-#[derive(Accounts)]
-#[instruction(offer_id: u64)]
-pub struct DepositToSellerVault<'info> {
-    #[account(mut)]
-    pub seller: Signer<'info>,
-    
-    #[account(
-        init_if_needed,
-        payer = seller,
-        space = 8,
-        seeds = [b"seller_vault", seller.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
-    )]
-    /// CHECK: Escrow vault PDA
-    pub seller_vault: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+/// Global exchange settings, a single PDA at `[b"exchange_config"]`
+#[account]
+#[derive(InitSpace)]
+pub struct ExchangeConfig {
+    pub authority: Pubkey,
+    /// Proposed authority, takes over once it calls `accept_authority`
+    pub pending_authority: Option<Pubkey>,
+    pub paused: PauseFlags,
+    pub fees: FeeSchedule,
+    /// Fee recipient per asset, `Pubkey::default()` as the mint for native SOL
+    #[max_len(MAX_TREASURIES)]
+    pub treasuries: Vec<AssetTreasury>,
+    pub bump: u8,
 }
 
-#[derive(Accounts)]
-#[instruction(offer_id: u64)]
-pub struct DepositToBuyerVault<'info> {
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-    
-    #[account(
-        init_if_needed,
-        payer = buyer,
-        space = 8,
-        seeds = [b"buyer_vault", buyer.key().as_ref(), &offer_id.to_le_bytes()],
-        bump,
-    )]
-    /// CHECK: Escrow vault PDA
-    pub buyer_vault: UncheckedAccount<'info>,
-    
-    pub system_program: Program<'info, System>,
+impl ExchangeConfig {
+    fn set(&mut self, fees: FeeSchedule, treasuries: Vec<AssetTreasury>) -> Result<()> {
+        fees.validate()?;
+        require!(treasuries.len() <= MAX_TREASURIES, ErrorCode::TooManyTreasuries);
+        self.fees = fees;
+        self.treasuries = treasuries;
+        Ok(())
+    }
+
+    /// Treasury account for a fee in `mint`, `None` when there is no fee to collect
+    fn fee_treasury<'info>(
+        &self,
+        mint: Pubkey,
+        account: Option<&UncheckedAccount<'info>>,
+        fee: u64,
+    ) -> Result<Option<AccountInfo<'info>>> {
+        if fee == 0 {
+            return Ok(None);
+        }
+        let expected = self
+            .treasuries
+            .iter()
+            .find(|t| t.mint == mint)
+            .map(|t| t.treasury)
+            .ok_or(ErrorCode::MissingTreasury)?;
+        let account = account.ok_or(ErrorCode::MissingTreasury)?;
+        require_keys_eq!(account.key(), expected, ErrorCode::InvalidTreasury);
+        Ok(Some(account.to_account_info()))
+    }
 }
 
+/// Compiled circuit hosted off-chain, pinned by its hash
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OffChainCircuit {
+    pub source: String,
+    pub hash: [u8; 32],
+}
 
+impl OffChainCircuit {
+    fn into_source(self) -> CircuitSource {
+        CircuitSource::OffChain(OffChainCircuitSource {
+            source: self.source,
+            hash: self.hash,
+        })
+    }
+}
 
-#[callback_accounts("add_together")]
-#[derive(Accounts)]
-pub struct AddTogetherCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ADD_TOGETHER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+/// Kill switches checked by the account constraints of every gated instruction.
+/// Cancel and expiry stay available so funds can always leave the escrows.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct PauseFlags {
+    /// Offer creation and seller escrow deposits
+    pub new_offers: bool,
+    /// Finalize, taker deposits and swap execution
+    pub settlements: bool,
+    /// Offers relayed from other chains
+    pub relays: bool,
 }
 
-#[callback_accounts("relay_offer_clone")]
-#[derive(Accounts)]
-pub struct RelayOfferCloneCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_RELAY_OFFER_CLONE)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+/// Protocol fees in basis points, charged at settlement
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct FeeSchedule {
+    pub intrachain_maker_bps: u16,
+    pub intrachain_taker_bps: u16,
+    pub interchain_maker_bps: u16,
+    pub interchain_taker_bps: u16,
 }
 
-#[callback_accounts("confidential_deposit_native")]
-#[derive(Accounts)]
-pub struct ConfidentialDepositNativeCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CONFIDENTIAL_DEPOSIT_NATIVE)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+impl FeeSchedule {
+    fn validate(&self) -> Result<()> {
+        require!(
+            [
+                self.intrachain_maker_bps,
+                self.intrachain_taker_bps,
+                self.interchain_maker_bps,
+                self.interchain_taker_bps,
+            ]
+            .iter()
+            .all(|bps| *bps <= MAX_FEE_BPS),
+            ErrorCode::FeeTooHigh
+        );
+        Ok(())
+    }
 }
 
-#[callback_accounts("interchain_origin_evm_deposit_seller_spl")]
-#[derive(Accounts)]
-pub struct InterchainOriginEvmDepositSellerSplCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INTERCHAIN_ORIGIN_EVM_DEPOSIT_SELLER_SPL)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+/// Where fees in `mint` are paid: a wallet for native SOL, a token account otherwise
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct AssetTreasury {
+    pub mint: Pubkey,
+    pub treasury: Pubkey,
 }
 
-#[callback_accounts("finalize_interchain_origin_evm_offer")]
-#[derive(Accounts)]
-pub struct FinalizeInterchainOriginEvmOfferCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTERCHAIN_ORIGIN_EVM_OFFER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+/// Guardian Ethereum addresses whose signatures make a VAA valid
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianSet {
+    pub index: u32,
+    #[max_len(19)]
+    pub keys: Vec<[u8; 20]>,
+    pub bump: u8,
+}
+
+impl GuardianSet {
+    /// More than two thirds of the guardians must sign
+    fn quorum(&self) -> usize {
+        self.keys.len() * 2 / 3 + 1
+    }
+
+    /// Checks a quorum of signatures over `message`, guardian indices strictly ascending
+    fn verify(&self, message: &[u8; 32], signatures: &[GuardianSignature]) -> Result<()> {
+        require!(signatures.len() >= self.quorum(), ErrorCode::NoGuardianQuorum);
+        let mut previous: Option<u8> = None;
+        for signature in signatures {
+            if let Some(previous) = previous {
+                require!(signature.index > previous, ErrorCode::InvalidGuardianSignature);
+            }
+            previous = Some(signature.index);
+
+            let expected = self
+                .keys
+                .get(signature.index as usize)
+                .ok_or(ErrorCode::InvalidGuardianSignature)?;
+            // Accept both raw (0/1) and Ethereum-style (27/28) recovery ids
+            let recovery_id = match signature.signature[64] {
+                v @ 0..=1 => v,
+                v @ 27..=28 => v - 27,
+                _ => return err!(ErrorCode::InvalidGuardianSignature),
+            };
+            let recovered = secp256k1_recover(message, recovery_id, &signature.signature[..64])
+                .map_err(|_| error!(ErrorCode::InvalidGuardianSignature))?;
+            let address = keccak::hash(&recovered.to_bytes()).to_bytes();
+            require!(address[12..] == expected[..], ErrorCode::InvalidGuardianSignature);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GuardianSignature {
+    /// Position of the signer in the guardian set
+    pub index: u8,
+    /// `r || s || v` secp256k1 signature
+    pub signature: [u8; 65],
+}
+
+/// Bridges an interchain route can be registered with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BridgeAdapter {
+    /// Guardian-signed VAAs inbound, Wormhole payloads outbound
+    Wormhole,
+    /// Outbound only, inbound packets have no on-chain verifier here yet
+    LayerZero,
+}
+
+impl BridgeAdapter {
+    /// Checks that a posted message came from `route`'s emitter with the required finality
+    fn verify_inbound(&self, route: &BridgeRoute, vaa: &PostedVaa) -> Result<()> {
+        match self {
+            BridgeAdapter::Wormhole => {
+                require!(
+                    vaa.emitter_chain as u32 == route.bridge_chain_id
+                        && vaa.emitter_address == route.emitter,
+                    ErrorCode::UnknownEmitter
+                );
+                // Consistency levels are not ordered (1 finalized, 200 instant, 201 safe), match exactly
+                require!(
+                    vaa.consistency_level == route.consistency_level,
+                    ErrorCode::InsufficientFinality
+                );
+                Ok(())
+            }
+            BridgeAdapter::LayerZero => err!(ErrorCode::UnsupportedBridge),
+        }
+    }
+
+    /// Payload of an outbound settlement in this bridge's format
+    fn encode(&self, message: &SettlementMessage) -> Vec<u8> {
+        match self {
+            BridgeAdapter::Wormhole => WormholeEncoder::encode(message),
+            BridgeAdapter::LayerZero => LayerZeroEncoder::encode(message),
+        }
+    }
+}
+
+/// How offers for one chain travel: bridge, origin contract and finality requirements
+#[account]
+#[derive(InitSpace)]
+pub struct BridgeRoute {
+    /// Chain id stored on interchain offers
+    pub chain_id: u64,
+    pub bridge: BridgeAdapter,
+    /// The bridge's own id for the chain, a Wormhole chain id or a LayerZero endpoint id
+    pub bridge_chain_id: u32,
+    /// Origin-chain contract, left-padded to 32 bytes
+    pub emitter: [u8; 32],
+    /// Consistency level inbound messages must be published with
+    pub consistency_level: u8,
+    /// Time the bridge needs to deliver a settlement back to the chain
+    pub finality_seconds: u32,
+    pub bump: u8,
+}
+
+impl BridgeRoute {
+    /// An offer must stay live long enough for its settlement to reach the origin chain
+    fn require_settlement_window(&self, deadline: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            deadline >= now.saturating_add(self.finality_seconds as i64),
+            ErrorCode::InsufficientFinality
+        );
+        Ok(())
+    }
+}
+
+/// A chain interchain offers may reference, a PDA per chain id
+#[account]
+#[derive(InitSpace)]
+pub struct ChainConfig {
+    pub chain_id: u64,
+    /// keccak256 of the chain's display name
+    pub name_hash: [u8; 32],
+    pub native_decimals: u8,
+    /// Token A addresses accepted for this chain, Solana mints for locally escrowed
+    /// offers and left-padded origin-chain addresses for relayed ones. Empty allows any.
+    #[max_len(16)]
+    pub allowed_tokens: Vec<[u8; 32]>,
+    /// Bounds on token A offered, inclusive
+    pub min_offer_size: u64,
+    pub max_offer_size: u64,
+    pub enabled: bool,
+    pub bump: u8,
+}
+
+impl ChainConfig {
+    fn check_offer(&self, token: &[u8; 32], amount: u64) -> Result<()> {
+        require!(
+            self.allowed_tokens.is_empty() || self.allowed_tokens.contains(token),
+            ErrorCode::TokenNotAllowed
+        );
+        require!(
+            (self.min_offer_size..=self.max_offer_size).contains(&amount),
+            ErrorCode::OfferSizeOutOfRange
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ChainParams {
+    pub name_hash: [u8; 32],
+    pub native_decimals: u8,
+    pub allowed_tokens: Vec<[u8; 32]>,
+    pub min_offer_size: u64,
+    pub max_offer_size: u64,
+    pub enabled: bool,
 }
 
-#[callback_accounts("deposit_seller_native")]
-#[derive(Accounts)]
-pub struct DepositSellerNativeCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_NATIVE)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+impl ChainParams {
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.allowed_tokens.len() <= MAX_CHAIN_TOKENS
+                && self.min_offer_size <= self.max_offer_size,
+            ErrorCode::InvalidChainConfig
+        );
+        Ok(())
+    }
 }
 
-#[callback_accounts("deposit_seller_spl")]
-#[derive(Accounts)]
-pub struct DepositSellerSplCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_DEPOSIT_SELLER_SPL)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+/// VAA whose guardian signatures were verified by `post_vaa`
+#[account]
+#[derive(InitSpace)]
+pub struct PostedVaa {
+    pub guardian_set_index: u32,
+    /// keccak256 of the VAA body
+    pub hash: [u8; 32],
+    pub timestamp: u32,
+    pub nonce: u32,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub sequence: u64,
+    pub consistency_level: u8,
+    #[max_len(256)]
+    pub payload: Vec<u8>,
 }
 
-#[callback_accounts("finalize_intrachain_offer")]
-#[derive(Accounts)]
-pub struct FinalizeIntrachainOfferCallback<'info> {
-    pub arcium_program: Program<'info, Arcium>,
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_FINALIZE_INTRACHAIN_OFFER)
-    )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
+impl PostedVaa {
+    /// Wormhole body layout, big-endian:
+    /// timestamp u32 | nonce u32 | emitter_chain u16 | emitter_address [32] | sequence u64 | consistency u8 | payload
+    fn parse_body(&mut self, body: &[u8]) -> Result<()> {
+        const HEADER_LEN: usize = 51;
+        require!(body.len() >= HEADER_LEN, ErrorCode::InvalidVaaPayload);
+        let payload = &body[HEADER_LEN..];
+        require!(payload.len() <= MAX_VAA_PAYLOAD, ErrorCode::InvalidVaaPayload);
+        self.timestamp = u32::from_be_bytes(body[0..4].try_into().unwrap());
+        self.nonce = u32::from_be_bytes(body[4..8].try_into().unwrap());
+        self.emitter_chain = u16::from_be_bytes(body[8..10].try_into().unwrap());
+        self.emitter_address = body[10..42].try_into().unwrap();
+        self.sequence = u64::from_be_bytes(body[42..50].try_into().unwrap());
+        self.consistency_level = body[50];
+        self.payload = payload.to_vec();
+        Ok(())
+    }
 }
 
+/// Marks a VAA as relayed, its existence blocks replays
+#[account]
+#[derive(InitSpace)]
+pub struct ConsumedMessage {
+    pub vaa_hash: [u8; 32],
+    pub consumed_at: i64,
+}
 
+/// Offer published by the origin-chain contract, big-endian:
+/// payload_id u8 (= 1) | id u64 | token_a_offered u64 | token_b_wanted u64 | is_taker_native u8 | deadline i64 | maker [32] | token_a [32]
+pub struct OfferPayload {
+    pub id: u64,
+    pub token_a_offered_amount: u64,
+    pub token_b_wanted_amount: u64,
+    pub is_taker_native: bool,
+    pub deadline: i64,
+    pub maker: Pubkey,
+    /// Origin-chain address of token A, left-padded
+    pub token_a: [u8; 32],
+}
 
-#[init_computation_definition_accounts("add_together", payer)]
-#[derive(Accounts)]
-pub struct InitAddTogetherCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+impl OfferPayload {
+    const PAYLOAD_ID: u8 = 1;
+    const LEN: usize = 98;
+
+    fn decode(payload: &[u8]) -> Result<Self> {
+        require!(
+            payload.len() == Self::LEN && payload[0] == Self::PAYLOAD_ID,
+            ErrorCode::InvalidVaaPayload
+        );
+        let is_taker_native = match payload[25] {
+            0 => false,
+            1 => true,
+            _ => return err!(ErrorCode::InvalidVaaPayload),
+        };
+        Ok(Self {
+            id: u64::from_be_bytes(payload[1..9].try_into().unwrap()),
+            token_a_offered_amount: u64::from_be_bytes(payload[9..17].try_into().unwrap()),
+            token_b_wanted_amount: u64::from_be_bytes(payload[17..25].try_into().unwrap()),
+            is_taker_native,
+            deadline: i64::from_be_bytes(payload[26..34].try_into().unwrap()),
+            maker: Pubkey::new_from_array(payload[34..66].try_into().unwrap()),
+            token_a: payload[66..98].try_into().unwrap(),
+        })
+    }
 }
 
-#[init_computation_definition_accounts("relay_offer_clone", payer)]
-#[derive(Accounts)]
-pub struct InitRelayOfferCloneCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// Program-wide outbound emitter, a single PDA at `[b"emitter"]`
+#[account]
+#[derive(InitSpace)]
+pub struct OutboundEmitter {
+    /// Sequence the next outbound message will carry
+    pub sequence: u64,
+    pub bump: u8,
 }
 
-#[init_computation_definition_accounts("confidential_deposit_native", payer)]
-#[derive(Accounts)]
-pub struct InitConfidentialDepositNativeCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// Settlement notice for the origin chain, picked up by relayers
+#[account]
+#[derive(InitSpace)]
+pub struct OutboundMessage {
+    pub emitter: Pubkey,
+    pub sequence: u64,
+    pub bridge: BridgeAdapter,
+    pub offer_id: u64,
+    #[max_len(256)]
+    pub payload: Vec<u8>,
+    pub posted_at: i64,
 }
 
-#[init_computation_definition_accounts("interchain_origin_evm_deposit_seller_spl", payer)]
-#[derive(Accounts)]
-pub struct InitInterchainOriginEvmDepositSellerSplCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// Bridge-neutral content of an outbound settlement
+pub struct SettlementMessage {
+    pub offer_id: u64,
+    /// Bridge id of the chain the message is delivered to
+    pub destination_chain: u32,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub encrypted_taker_destination: [u8; 32],
+    pub destination_nonce: u128,
+    pub emitter: Pubkey,
+    pub sequence: u64,
 }
 
-#[init_computation_definition_accounts("finalize_interchain_origin_evm_offer", payer)]
-#[derive(Accounts)]
-pub struct InitFinalizeInterchainOriginEvmOfferCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// Turns a settlement into the payload a given bridge delivers
+pub trait SettlementEncoder {
+    fn encode(message: &SettlementMessage) -> Vec<u8>;
 }
 
-#[init_computation_definition_accounts("deposit_seller_native", payer)]
-#[derive(Accounts)]
-pub struct InitDepositSellerNativeCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// Wormhole payload, big-endian. Emitter and sequence travel in the VAA envelope:
+/// payload_id u8 (= 2) | offer_id u64 | chain u16 | token_a u64 | token_b u64 | destination [32] | nonce u128
+pub struct WormholeEncoder;
+
+impl SettlementEncoder for WormholeEncoder {
+    fn encode(message: &SettlementMessage) -> Vec<u8> {
+        const PAYLOAD_ID: u8 = 2;
+        let mut out = Vec::with_capacity(75);
+        out.push(PAYLOAD_ID);
+        out.extend_from_slice(&message.offer_id.to_be_bytes());
+        out.extend_from_slice(&(message.destination_chain as u16).to_be_bytes());
+        out.extend_from_slice(&message.token_a_amount.to_be_bytes());
+        out.extend_from_slice(&message.token_b_amount.to_be_bytes());
+        out.extend_from_slice(&message.encrypted_taker_destination);
+        out.extend_from_slice(&message.destination_nonce.to_be_bytes());
+        out
+    }
 }
 
-#[init_computation_definition_accounts("deposit_seller_spl", payer)]
-#[derive(Accounts)]
-pub struct InitDepositSellerSplCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+/// LayerZero v2 packet header followed by an ABI-encoded body of 32-byte words:
+/// nonce u64 | src_eid u32 | sender [32] | dst_eid u32 | offer_id | token_a | token_b | destination | nonce
+pub struct LayerZeroEncoder;
+
+impl LayerZeroEncoder {
+    /// LayerZero endpoint id of Solana mainnet
+    const SOLANA_EID: u32 = 30168;
+
+    fn abi_word(out: &mut Vec<u8>, value: &[u8]) {
+        out.resize(out.len() + 32 - value.len(), 0);
+        out.extend_from_slice(value);
+    }
 }
 
-#[init_computation_definition_accounts("finalize_intrachain_offer", payer)]
-#[derive(Accounts)]
-pub struct InitFinalizeIntrachainOfferCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    #[account(
-        mut,
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Box<Account<'info, MXEAccount>>,
-    #[account(mut)]
-    /// CHECK: comp_def_account, checked by arcium program.
-    /// Can't check it here as it's not initialized yet.
-    pub comp_def_account: UncheckedAccount<'info>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub system_program: Program<'info, System>,
+impl SettlementEncoder for LayerZeroEncoder {
+    fn encode(message: &SettlementMessage) -> Vec<u8> {
+        let mut out = Vec::with_capacity(208);
+        out.extend_from_slice(&message.sequence.to_be_bytes());
+        out.extend_from_slice(&Self::SOLANA_EID.to_be_bytes());
+        out.extend_from_slice(message.emitter.as_ref());
+        out.extend_from_slice(&message.destination_chain.to_be_bytes());
+        Self::abi_word(&mut out, &message.offer_id.to_be_bytes());
+        Self::abi_word(&mut out, &message.token_a_amount.to_be_bytes());
+        Self::abi_word(&mut out, &message.token_b_amount.to_be_bytes());
+        Self::abi_word(&mut out, &message.encrypted_taker_destination);
+        Self::abi_word(&mut out, &message.destination_nonce.to_be_bytes());
+        out
+    }
 }
 
+/// Slice of an intrachain offer requested by a taker, consumed by the finalize callback
+#[account]
+#[derive(InitSpace)]
+pub struct PendingFill {
+    pub token_a_amount: u64,
+    pub bump: u8,
+}
 
-#[event]
-pub struct SumEvent {
-    pub sum: [u8; 32],
-    pub nonce: [u8; 16],
+/// EVM address a taker proved in finalize, consumed by the finalize callback
+#[account]
+#[derive(InitSpace)]
+pub struct TakerEvmProof {
+    pub evm_address: [u8; 20],
+    pub bump: u8,
 }
 
-#[event]
-pub struct RelayOfferClonedEvent {
-    pub acknowledged: u8,
+/// MXE-owned ciphertext written by a callback, readable only inside later computations
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EncryptedIdentity {
+    /// 256-bit commitment as four encrypted u64 limbs, little-endian limb order
+    pub ciphertexts: [[u8; 32]; 4],
+    pub nonce: u128,
 }
 
-#[event]
-pub struct ConfidentialDepositNativeEvent {
-    pub processed_amount: [u8; 32],
-    pub nonce: [u8; 16],
+impl EncryptedIdentity {
+    /// Arguments for an `Enc<Mxe, SellerIdentity>` circuit parameter
+    pub fn to_arguments(&self) -> Vec<Argument> {
+        let mut args = vec![Argument::PlaintextU128(self.nonce)];
+        args.extend(identity_arguments(&self.ciphertexts));
+        args
+    }
 }
 
-#[event]
-pub struct InterchainOriginEvmDepositSellerSplEvent {
-    pub acknowledged: u8,
+/// MXE-owned encrypted u64 balance
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EncryptedBalance {
+    pub ciphertext: [u8; 32],
+    pub nonce: u128,
 }
 
-#[event]
-pub struct FinalizeInterchainOriginEvmOfferEvent {
-    pub acknowledged: u8,
+impl EncryptedBalance {
+    /// Arguments for an `Enc<Mxe, Balance>` circuit parameter. A missing balance is
+    /// passed as zeroes, which the circuits ignore when told the balance is new
+    pub fn arguments(balance: Option<&Self>) -> [Argument; 2] {
+        let balance = balance.copied().unwrap_or_default();
+        [
+            Argument::PlaintextU128(balance.nonce),
+            Argument::EncryptedU64(balance.ciphertext),
+        ]
+    }
 }
 
-#[event]
-pub struct DepositSellerNativeEvent {
-    pub acknowledged: u8,
+/// MXE-owned bid table of a sealed auction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EncryptedBids {
+    pub ciphertexts: [[u8; 32]; MAX_AUCTION_BIDS],
+    pub nonce: u128,
 }
 
-#[event]
-pub struct DepositSellerSplEvent {
-    pub acknowledged: u8,
+impl EncryptedBids {
+    /// Arguments for an `Enc<Mxe, AuctionBids>` circuit parameter, zeroes before the first bid
+    pub fn arguments(bids: Option<&Self>) -> Vec<Argument> {
+        let bids = bids.copied().unwrap_or_default();
+        let mut args = vec![Argument::PlaintextU128(bids.nonce)];
+        args.extend(bids.ciphertexts.iter().map(|c| Argument::EncryptedU64(*c)));
+        args
+    }
 }
 
-#[event]
-pub struct FinalizeIntrachainOfferEvent {
-    pub acknowledged: u8,
+/// MXE-owned order book, every slot's side then every price then every size
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct EncryptedBook {
+    pub ciphertexts: [[u8; 32]; 3 * MAX_BOOK_ORDERS],
+    pub nonce: u128,
 }
 
+impl EncryptedBook {
+    /// Arguments for an `Enc<Mxe, Book>` circuit parameter, zeroes before the first order
+    pub fn arguments(book: Option<&Self>) -> Vec<Argument> {
+        let (ciphertexts, nonce) = match book {
+            Some(book) => (book.ciphertexts, book.nonce),
+            None => ([[0; 32]; 3 * MAX_BOOK_ORDERS], 0),
+        };
+        let mut args = vec![Argument::PlaintextU128(nonce)];
+        args.extend(ciphertexts.iter().enumerate().map(|(i, c)| {
+            if i < MAX_BOOK_ORDERS {
+                Argument::EncryptedBool(*c)
+            } else {
+                Argument::EncryptedU64(*c)
+            }
+        }));
+        args
+    }
+}
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("The computation was aborted")]
-    AbortedComputation,
-    #[msg("Cluster not set")]
-    ClusterNotSet,
+/// MXE-owned offer amounts, token A offered then token B wanted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct EncryptedTerms {
+    pub ciphertexts: [[u8; 32]; 2],
+    pub nonce: u128,
 }
 
-// PDA account structures for on-chain state (matching original Anchor program)
-#[account]
-pub struct IntraChainOffer {
-    pub id: u64,
-    pub token_a_offered_amount: u64,
-    pub token_b_wanted_amount: u64,
-    pub is_taker_native: bool,
-    pub deadline: i64,
-    pub bump: u8,
+impl EncryptedTerms {
+    /// Arguments for an `Enc<Mxe, OfferTerms>` circuit parameter
+    pub fn to_arguments(&self) -> Vec<Argument> {
+        let mut args = vec![Argument::PlaintextU128(self.nonce)];
+        args.extend(self.ciphertexts.iter().map(|c| Argument::EncryptedU64(*c)));
+        args
+    }
 }
 
-#[account]
-pub struct InterchainOffer {
-    pub id: u64,
-    pub token_a_offered_amount: u64,
-    pub token_b_wanted_amount: u64,
-    pub is_taker_native: bool,
-    pub chain_id: u64,
-    pub deadline: i64,
-    pub bump: u8,
-}
\ No newline at end of file
+/// One encrypted argument per limb of a 256-bit identity commitment
+fn identity_arguments(ciphertexts: &[[u8; 32]; 4]) -> impl Iterator<Item = Argument> + '_ {
+    ciphertexts.iter().map(|limb| Argument::EncryptedU64(*limb))
+}
+
+/// Lifecycle of an offer, advanced only through `OfferStatus::transition`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, Debug, InitSpace)]
+pub enum OfferStatus {
+    /// Account allocated, seller identity computation not queued yet
+    #[default]
+    Created,
+    /// Seller identity computation queued, waiting for its callback
+    IdentityPending,
+    /// Seller identity stored, accepting takers
+    Open,
+    /// A taker's identity was verified by MPC, settlement outstanding.
+    /// Partially filled intrachain offers return to `Open` afterwards
+    Matched,
+    /// Both escrows released to the counterparties
+    Settled,
+    /// Withdrawn by the seller before a match
+    Cancelled,
+    /// Deadline passed without settlement, or a dispute was resolved for the maker.
+    /// The refund may still be outstanding until `expire_interchain_offer` runs
+    Expired,
+    /// A party contested a matched interchain offer, frozen until the exchange authority
+    /// releases it back to `Matched` or expires it
+    Disputed,
+}
+
+impl OfferStatus {
+    /// Moves to `next`, rejecting any edge not in the offer lifecycle
+    pub fn transition(&mut self, next: OfferStatus) -> Result<()> {
+        use OfferStatus::*;
+        match (*self, next) {
+            (Created, IdentityPending)
+            | (IdentityPending, Open)
+            | (Open, Matched)
+            | (Matched, Open)
+            | (Matched, Settled)
+            | (IdentityPending | Open, Cancelled)
+            | (IdentityPending | Open | Matched, Expired)
+            | (Matched, Disputed)
+            | (Disputed, Matched | Expired) => {
+                *self = next;
+                Ok(())
+            }
+            (Settled | Cancelled | Expired, _) => err!(ErrorCode::OfferClosed),
+            (Disputed, _) => err!(ErrorCode::OfferDisputed),
+            (_, IdentityPending) => err!(ErrorCode::OfferAlreadyInitialized),
+            (_, Open) => err!(ErrorCode::OfferNotAwaitingIdentity),
+            (IdentityPending, Matched) => err!(ErrorCode::SellerIdentityPending),
+            (_, Matched | Cancelled) => err!(ErrorCode::OfferNotOpen),
+            (_, Settled) => err!(ErrorCode::OfferNotMatched),
+            _ => err!(ErrorCode::InvalidOfferStatus),
+        }
+    }
+
+    /// Takers may only be matched against an open offer
+    pub fn require_open(&self) -> Result<()> {
+        match self {
+            OfferStatus::Open => Ok(()),
+            OfferStatus::IdentityPending => err!(ErrorCode::SellerIdentityPending),
+            OfferStatus::Settled | OfferStatus::Cancelled | OfferStatus::Expired => {
+                err!(ErrorCode::OfferClosed)
+            }
+            _ => err!(ErrorCode::OfferNotOpen),
+        }
+    }
+}
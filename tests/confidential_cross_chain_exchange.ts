@@ -1,6 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { PublicKey } from "@solana/web3.js";
+import { PublicKey, Secp256k1Program } from "@solana/web3.js";
+import {
+  ExtensionType,
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { ConfidentialCrossChainExchange } from "../target/types/confidential_cross_chain_exchange";
 import { randomBytes, createHash } from "crypto";
 import {
@@ -21,6 +35,8 @@ import {
   getComputationAccAddress,
   x25519,
 } from "@arcium-hq/client";
+import { secp256k1 } from "@noble/curves/secp256k1";
+import { keccak_256 } from "@noble/hashes/sha3";
 import * as fs from "fs";
 import * as os from "os";
 import { expect } from "chai";
@@ -59,16 +75,26 @@ describe("ConfidentialCrossChainExchange", () => {
     return v;
   };
 
-  const keccakOrSha3ToU64 = (data: Uint8Array): bigint => {
-    // Prefer sha3-256 if available. Node >= 20 supports 'sha3-256'. Fallback to 'sha256'.
-    let digest: Buffer;
-    try {
-      digest = createHash("sha3-256").update(Buffer.from(data)).digest();
-    } catch {
-      digest = createHash("sha256").update(Buffer.from(data)).digest();
-    }
-    const first8 = digest.subarray(0, 8);
-    return first8.readBigUInt64LE();
+  // 256-bit identity commitments travel as four little-endian u64 limbs
+  const hashToLimbs = (digest: Buffer): bigint[] =>
+    [0, 8, 16, 24].map((offset) => digest.readBigUInt64LE(offset));
+
+  const identityHashLimbs = (data: Uint8Array): bigint[] =>
+    hashToLimbs(Buffer.from(keccak_256(data)));
+
+  // Interchain commitments are keccak256 of the taker's EVM address left-padded to 256 bits
+  const evmIdentityLimbs = (address: Buffer): bigint[] =>
+    identityHashLimbs(Buffer.concat([Buffer.alloc(12), address]));
+
+  // EIP-191 `personal_sign` message proving an EVM key takes `offer` as `taker`
+  const evmOwnershipMessage = (offer: PublicKey, taker: PublicKey) => {
+    const text = [
+      "Take an offer on the confidential cross-chain exchange",
+      `Program: ${program.programId.toBase58()}`,
+      `Offer: ${offer.toBase58()}`,
+      `Taker: ${taker.toBase58()}`,
+    ].join("\n");
+    return Buffer.from(`\x19Ethereum Signed Message:\n${Buffer.byteLength(text)}${text}`);
   };
 
   const toHexU64 = (n: bigint | anchor.BN) => {
@@ -92,14 +118,299 @@ describe("ConfidentialCrossChainExchange", () => {
     return PublicKey.findProgramAddressSync([seed, owner.toBuffer(), idLe], programId)[0];
   };
 
+  const deriveVaultPda = (programId: PublicKey, seed: string, owner: PublicKey, id: anchor.BN) => {
+    const idLe = Buffer.from(id.toArrayLike(Buffer, "le", 8));
+    return PublicKey.findProgramAddressSync([Buffer.from(seed), owner.toBuffer(), idLe], programId)[0];
+  };
+
+  // Buyer escrows are keyed by the offer account, so makers reusing an id never share one
+  const deriveBuyerVaultPda = (programId: PublicKey, seed: string, offer: PublicKey, buyer: PublicKey) =>
+    PublicKey.findProgramAddressSync([Buffer.from(seed), offer.toBuffer(), buyer.toBuffer()], programId)[0];
+
+  // Confidential balances are per owner and mint, lamports sit under the wrapped SOL mint
+  const deriveConfidentialBalancePda = (owner: PublicKey, mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("confidential_balance"), owner.toBuffer(), mint.toBuffer()],
+      program.programId
+    )[0];
+
+  // Creates a 6-decimal mint and funds the wallet's associated token account with `amount`
+  const createFundedMint = async (amount: anchor.BN) => {
+    const payer = (provider.wallet as any).payer as anchor.web3.Keypair;
+    const mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+    const tokenAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      payer,
+      mint,
+      payer.publicKey
+    );
+    await mintTo(provider.connection, payer, mint, tokenAccount.address, payer, BigInt(amount.toString()));
+    return { mint, tokenAccount: tokenAccount.address };
+  };
+
+  // Locally generated guardian standing in for the bridge's guardian network
+  const guardianKey = secp256k1.utils.randomPrivateKey();
+  const evmAddress = (key: Uint8Array) =>
+    Buffer.from(keccak_256(secp256k1.getPublicKey(key, false).subarray(1))).subarray(12);
+  const guardianAddress = evmAddress(guardianKey);
+  // EVM key of the taker the relayed offer's seller commits to
+  const takerEvmKey = secp256k1.utils.randomPrivateKey();
+  const takerEvmAddress = evmAddress(takerEvmKey);
+  // Guardian set currently stored on chain, rotated by the guardian verification test
+  let guardianSetIndex = 0;
+  let guardianKeys = [guardianKey];
+  const ORIGIN_CHAIN = 2;
+  const ORIGIN_CHAIN_ID = new anchor.BN(1);
+  const originEmitter = Buffer.alloc(32, 0xee);
+  const originToken = Buffer.alloc(32, 0xaa);
+  const bridgeRoute = PublicKey.findProgramAddressSync(
+    [Buffer.from("bridge_route"), ORIGIN_CHAIN_ID.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  )[0];
+  const chainConfig = PublicKey.findProgramAddressSync(
+    [Buffer.from("chain_config"), ORIGIN_CHAIN_ID.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  )[0];
+
+  // Offers for the origin chain travel over Wormhole, published with finalized consistency,
+  // and the chain accepts any token A between 1 and 10^14 base units
+  const registerOriginRoute = async () => {
+    const authority = (provider.wallet as any).payer;
+    await program.methods
+      .registerBridgeRoute(ORIGIN_CHAIN_ID, { wormhole: {} }, ORIGIN_CHAIN, Array.from(originEmitter), 1, 60)
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig, bridgeRoute })
+      .rpc({ commitment: "confirmed" });
+    await program.methods
+      .setChainConfig(ORIGIN_CHAIN_ID, {
+        nameHash: Array.from(keccak_256(Buffer.from("ethereum"))),
+        nativeDecimals: 18,
+        allowedTokens: [],
+        minOfferSize: new anchor.BN(1),
+        maxOfferSize: new anchor.BN("100000000000000"),
+        enabled: true,
+      })
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig, chainConfig })
+      .rpc({ commitment: "confirmed" });
+  };
+
+  // Wormhole VAA body carrying an offer payload, all fields big-endian
+  const buildOfferVaaBody = (
+    sequence: number,
+    offer: { id: anchor.BN; tokenAOffered: anchor.BN; tokenBWanted: anchor.BN; isTakerNative: boolean; deadline: anchor.BN; maker: PublicKey },
+    emitter: Buffer = originEmitter,
+    consistencyLevel = 1
+  ) => {
+    const header = Buffer.alloc(51);
+    header.writeUInt32BE(Math.floor(Date.now() / 1000), 0);
+    header.writeUInt32BE(0, 4);
+    header.writeUInt16BE(ORIGIN_CHAIN, 8);
+    emitter.copy(header, 10);
+    header.writeBigUInt64BE(BigInt(sequence), 42);
+    header.writeUInt8(consistencyLevel, 50);
+    const payload = Buffer.concat([
+      Buffer.from([1]),
+      offer.id.toArrayLike(Buffer, "be", 8),
+      offer.tokenAOffered.toArrayLike(Buffer, "be", 8),
+      offer.tokenBWanted.toArrayLike(Buffer, "be", 8),
+      Buffer.from([offer.isTakerNative ? 1 : 0]),
+      offer.deadline.toArrayLike(Buffer, "be", 8),
+      offer.maker.toBuffer(),
+      originToken,
+    ]);
+    return Buffer.concat([header, payload]);
+  };
+
+  // Guardians sign keccak256(keccak256(body)), the single hash names the message
+  const signVaa = (body: Buffer, signers: { index: number; key: Uint8Array }[]) => {
+    const digest = keccak_256(keccak_256(body));
+    return signers.map(({ index, key }) => {
+      const sig = secp256k1.sign(digest, key);
+      const signature = Buffer.concat([Buffer.from(sig.toCompactRawBytes()), Buffer.from([sig.recovery])]);
+      return { index, signature: Array.from(signature) };
+    });
+  };
+
+  const postVaaTx = (body: Buffer, signatures: { index: number; signature: number[] }[], setIndex = guardianSetIndex) => {
+    const vaaHash = Buffer.from(keccak_256(body));
+    const postedVaa = PublicKey.findProgramAddressSync(
+      [Buffer.from("posted_vaa"), vaaHash],
+      program.programId
+    )[0];
+    const tx = program.methods
+      .postVaa(Array.from(vaaHash), setIndex, signatures, body)
+      .accountsPartial({ postedVaa })
+      .rpc({ commitment: "confirmed" });
+    return { vaaHash, postedVaa, tx };
+  };
+
+  // Every guardian of the current set signs, which always meets quorum
+  const postOfferVaa = async (body: Buffer) => {
+    const { vaaHash, postedVaa, tx } = postVaaTx(
+      body,
+      signVaa(body, guardianKeys.map((key, index) => ({ index, key })))
+    );
+    await tx;
+    return { vaaHash, postedVaa };
+  };
+
+  // Awaits `tx` and checks it failed with the program error `code`
+  const expectAnchorError = async (tx: Promise<unknown>, code: string) => {
+    let error: any = null;
+    try {
+      await tx;
+    } catch (e) {
+      error = e;
+    }
+    expect(error, `expected ${code}`).to.not.equal(null);
+    expect(error.error?.errorCode?.code ?? error.toString()).to.equal(code);
+  };
+
   const getSignPdaAccAddress = (programId: PublicKey) => {
     const seed = Buffer.from("SignerAccount");
     return PublicKey.findProgramAddressSync([seed], programId)[0];
   };
 
+  const exchangeConfig = PublicKey.findProgramAddressSync(
+    [Buffer.from("exchange_config")],
+    program.programId
+  )[0];
+  const zeroFees = {
+    intrachainMakerBps: 0,
+    intrachainTakerBps: 0,
+    interchainMakerBps: 0,
+    interchainTakerBps: 0,
+  };
+
+  it("Initializes the exchange config with a capped fee schedule!", async () => {
+    const authority = (provider.wallet as any).payer;
+    // Only the upgrade authority recorded in the program data account may create the config
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    const squatter = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(squatter.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+    let squatted = true;
+    try {
+      await program.methods
+        .initializeExchange(zeroFees, [])
+        .accountsPartial({ authority: squatter.publicKey, programData, exchangeConfig })
+        .signers([squatter])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      squatted = false;
+    }
+    expect(squatted).to.equal(false);
+
+    await program.methods
+      .initializeExchange(zeroFees, [])
+      .accountsPartial({ authority: authority.publicKey, programData, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+
+    const config = await (program.account as any).exchangeConfig.fetch(exchangeConfig);
+    expect(config.authority.toBase58()).to.equal(authority.publicKey.toBase58());
+    expect(config.fees.intrachainTakerBps).to.equal(0);
+
+    // Fees above the hard cap are rejected
+    let rejected = false;
+    try {
+      await program.methods
+        .updateConfig({ ...zeroFees, interchainMakerBps: 101 }, [])
+        .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      rejected = true;
+    }
+    expect(rejected).to.equal(true);
+
+    // Only the authority may update the config
+    const stranger = anchor.web3.Keypair.generate();
+    rejected = false;
+    try {
+      await program.methods
+        .updateConfig(zeroFees, [])
+        .accountsPartial({ authority: stranger.publicKey, exchangeConfig })
+        .signers([stranger])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      rejected = true;
+    }
+    expect(rejected).to.equal(true);
+  });
+
+  it("Pause switch halts gated instructions and authority moves in two steps!", async () => {
+    const authority = (provider.wallet as any).payer;
+    const offerId = new anchor.BN(11111);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", authority.publicKey, offerId);
+
+    await program.methods
+      .setPaused({ newOffers: true, settlements: false, relays: false })
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+
+    let rejected = false;
+    try {
+      await program.methods
+        .depositToSellerVault(offerId, new anchor.BN(1_000_000))
+        .accountsPartial({ seller: authority.publicKey, sellerVault, exchangeConfig })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      rejected = true;
+    }
+    expect(rejected).to.equal(true);
+
+    await program.methods
+      .setPaused({ newOffers: false, settlements: false, relays: false })
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+
+    // Hand the exchange to a new authority and back, each side signing its own step
+    const successor = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(successor.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+
+    await program.methods
+      .proposeAuthority(successor.publicKey)
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+    await program.methods
+      .acceptAuthority()
+      .accountsPartial({ pendingAuthority: successor.publicKey, exchangeConfig })
+      .signers([successor])
+      .rpc({ commitment: "confirmed" });
+    let config = await (program.account as any).exchangeConfig.fetch(exchangeConfig);
+    expect(config.authority.toBase58()).to.equal(successor.publicKey.toBase58());
+    expect(config.pendingAuthority).to.equal(null);
+
+    await program.methods
+      .proposeAuthority(authority.publicKey)
+      .accountsPartial({ authority: successor.publicKey, exchangeConfig })
+      .signers([successor])
+      .rpc({ commitment: "confirmed" });
+    await program.methods
+      .acceptAuthority()
+      .accountsPartial({ pendingAuthority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+    config = await (program.account as any).exchangeConfig.fetch(exchangeConfig);
+    expect(config.authority.toBase58()).to.equal(authority.publicKey.toBase58());
+  });
+
   it("Is initialized!", async () => {
     const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
 
+    // Comp defs follow the exchange authority, a stranger cannot register a circuit first
+    const stranger = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(stranger.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+    let registered = true;
+    try {
+      await initAddTogetherCompDef(program, stranger, false, false);
+    } catch (e) {
+      registered = false;
+    }
+    expect(registered).to.equal(false);
+
     console.log("Initializing add together computation definition");
     const initATSig = await initAddTogetherCompDef(
       program,
@@ -210,13 +521,28 @@ describe("ConfidentialCrossChainExchange", () => {
     const tokenBWanted = new anchor.BN(3_000_000_000); // 3000 USDC (assuming 6-9 decimals in test)
     const tokenAOffered = new anchor.BN(10_000_000_000); // 10 SOL in lamports for example
     const isTakerNative = true;
-    const chainId = new anchor.BN(1);
     const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
 
-    // Confidential identity hash (external seller)
-    const extSellerHashU64 = keccakOrSha3ToU64(publicKey);
+    // Trust the local guardian and the origin-chain offer contract, then post its message
+    const authority = (provider.wallet as any).payer;
+    await program.methods
+      .setGuardianSet(0, [Array.from(guardianAddress)])
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+    await registerOriginRoute();
+    const { vaaHash, postedVaa } = await postOfferVaa(
+      buildOfferVaaBody(1, { id, tokenAOffered, tokenBWanted, isTakerNative, deadline, maker: authority.publicKey })
+    );
+    const consumedMessage = PublicKey.findProgramAddressSync(
+      [Buffer.from("consumed_message"), vaaHash],
+      program.programId
+    )[0];
+
+    // Confidential identity hash (external seller), committing to the taker's EVM address
+    // so the finalize test can prove the same address
+    const extSellerHashLimbs = evmIdentityLimbs(takerEvmAddress);
     const idNonce = randomBytes(16);
-    const idCiphertext = cipher.encrypt([extSellerHashU64], idNonce);
+    const idCiphertext = cipher.encrypt(extSellerHashLimbs, idNonce);
 
     const relayEventPromise = awaitEvent("relayOfferClonedEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
@@ -232,27 +558,68 @@ describe("ConfidentialCrossChainExchange", () => {
       tokenBWanted: tokenBWanted.toString(),
       tokenAOffered: tokenAOffered.toString(),
       isTakerNative,
-      chainId: chainId.toString(),
       deadline: deadline.toString(),
     });
-    console.log('External seller identity hash (u64):', toHexU64(extSellerHashU64));
+    console.log('External seller identity hash limbs:', extSellerHashLimbs.map(toHexU64));
     console.log('Computation offset (BN hex):', computationOffset.toString('hex'));
 
+    // A disabled chain rejects relays until the authority switches it back on
+    const authorityKey = (provider.wallet as any).payer.publicKey;
+    await program.methods
+      .setChainEnabled(ORIGIN_CHAIN_ID, false)
+      .accountsPartial({ authority: authorityKey, exchangeConfig, chainConfig })
+      .rpc({ commitment: "confirmed" });
+    const disabledOffset = new anchor.BN(randomBytes(8), "hex");
+    let relayedWhileDisabled = true;
+    try {
+      await program.methods
+        .relayOfferClone(
+          id,
+          ORIGIN_CHAIN_ID,
+          Array.from(vaaHash),
+          idCiphertext.map((c) => Array.from(c)),
+          Array.from(publicKey),
+          new anchor.BN(deserializeLE(idNonce).toString()),
+          disabledOffset
+        )
+        .accountsPartial({
+          payer: authorityKey,
+          maker: authorityKey,
+          postedVaa,
+          bridgeRoute,
+          chainConfig,
+          consumedMessage,
+          interchainOffer,
+          computationAccount: getComputationAccAddress(program.programId, disabledOffset),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      relayedWhileDisabled = false;
+    }
+    expect(relayedWhileDisabled).to.equal(false);
+    await program.methods
+      .setChainEnabled(ORIGIN_CHAIN_ID, true)
+      .accountsPartial({ authority: authorityKey, exchangeConfig, chainConfig })
+      .rpc({ commitment: "confirmed" });
+
     const queueSig = await program.methods
       .relayOfferClone(
         id,
-        tokenBWanted,
-        tokenAOffered,
-        isTakerNative,
-        chainId,
-        deadline,
-        Array.from(idCiphertext[0]),
+        ORIGIN_CHAIN_ID,
+        Array.from(vaaHash),
+        idCiphertext.map((c) => Array.from(c)),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(idNonce).toString()),
         computationOffset
       )
       .accounts({
         payer: (provider.wallet as any).payer.publicKey,
+        maker: (provider.wallet as any).payer.publicKey,
+        postedVaa,
+        bridgeRoute,
+        chainConfig,
+        consumedMessage,
         interchainOffer,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
@@ -282,6 +649,249 @@ describe("ConfidentialCrossChainExchange", () => {
     const relayEvent = await relayEventPromise as any;
     console.log('RelayOfferClonedEvent:', relayEvent);
     expect(relayEvent.acknowledged).to.equal(1);
+
+    // The callback persists the MXE-encrypted seller identity on the offer
+    const fetched = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(Buffer.from(fetched.sellerIdentity.ciphertexts[0]).equals(Buffer.alloc(32))).to.equal(false);
+    expect(fetched.sellerIdentity.nonce.isZero()).to.equal(false);
+    // Business fields come from the verified message
+    expect(fetched.chainId.eq(ORIGIN_CHAIN_ID)).to.be.true;
+    expect(fetched.tokenAOfferedAmount.toString()).to.equal(tokenAOffered.toString());
+  });
+
+  it("Consumed-message guard rejects a replay even after the cloned offer is gone!", async () => {
+    const authority = (provider.wallet as any).payer;
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const id = new anchor.BN(12399);
+    const { vaaHash, postedVaa } = await postOfferVaa(
+      buildOfferVaaBody(2, {
+        id,
+        tokenAOffered: new anchor.BN(1_000_000),
+        tokenBWanted: new anchor.BN(500_000),
+        isTakerNative: true,
+        deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+        maker: authority.publicKey,
+      })
+    );
+    const consumedMessage = PublicKey.findProgramAddressSync(
+      [Buffer.from("consumed_message"), vaaHash],
+      program.programId
+    )[0];
+    const interchainOffer = deriveInterchainOfferPda(program.programId, authority.publicKey, id);
+    const idNonce = randomBytes(16);
+    const idCiphertext = cipher.encrypt(identityHashLimbs(authority.publicKey.toBytes()), idNonce);
+
+    const relay = (offset: anchor.BN) =>
+      program.methods
+        .relayOfferClone(
+          id,
+          ORIGIN_CHAIN_ID,
+          Array.from(vaaHash),
+          idCiphertext.map((c) => Array.from(c)),
+          Array.from(publicKey),
+          new anchor.BN(deserializeLE(idNonce).toString()),
+          offset
+        )
+        .accountsPartial({
+          payer: authority.publicKey,
+          maker: authority.publicKey,
+          postedVaa,
+          bridgeRoute,
+          chainConfig,
+          consumedMessage,
+          interchainOffer,
+          computationAccount: getComputationAccAddress(program.programId, offset),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    await relay(computationOffset);
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    // Close the clone so its PDA can no longer be what stops a second relay
+    await program.methods
+      .cancelInterchainOffer(id)
+      .accountsPartial({ interchainOffer, maker: authority.publicKey, payer: authority.publicKey, caller: authority.publicKey })
+      .rpc({ commitment: "confirmed" });
+    expect(await provider.connection.getAccountInfo(interchainOffer)).to.equal(null);
+
+    let logs: string[] = [];
+    try {
+      await relay(new anchor.BN(randomBytes(8), "hex"));
+    } catch (e) {
+      logs = (e as any).logs ?? [];
+    }
+    // Only the consumed-message account is already in use
+    expect(logs.some((l) => l.includes(consumedMessage.toBase58()) && l.includes("already in use"))).to.equal(true);
+    expect(await provider.connection.getAccountInfo(interchainOffer)).to.equal(null);
+  });
+
+  it("Guardian verification enforces quorum, signers, index order and the route!", async () => {
+    const authority = (provider.wallet as any).payer;
+    const offer = {
+      id: new anchor.BN(12400),
+      tokenAOffered: new anchor.BN(1_000_000),
+      tokenBWanted: new anchor.BN(500_000),
+      isTakerNative: true,
+      deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+      maker: authority.publicKey,
+    };
+
+    // Four guardians need a quorum of three
+    const keys = [guardianKey, ...[0, 1, 2].map(() => secp256k1.utils.randomPrivateKey())];
+    await program.methods
+      .setGuardianSet(1, keys.map((k) => Array.from(evmAddress(k))))
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+    guardianSetIndex = 1;
+    guardianKeys = keys;
+
+    const body = buildOfferVaaBody(10, offer);
+    const sign = (indices: number[], keyIndices = indices) =>
+      signVaa(body, indices.map((index, i) => ({ index, key: keys[keyIndices[i]] })));
+
+    await expectAnchorError(postVaaTx(body, sign([0, 1])).tx, "NoGuardianQuorum");
+    const outsider = secp256k1.utils.randomPrivateKey();
+    const forged = signVaa(body, [{ index: 0, key: keys[0] }, { index: 1, key: keys[1] }, { index: 2, key: outsider }]);
+    await expectAnchorError(postVaaTx(body, forged).tx, "InvalidGuardianSignature");
+    await expectAnchorError(postVaaTx(body, sign([0, 0, 1])).tx, "InvalidGuardianSignature");
+    await expectAnchorError(postVaaTx(body, sign([1, 0, 2])).tx, "InvalidGuardianSignature");
+    await expectAnchorError(postVaaTx(body, sign([0, 1, 2]), 0).tx, "InvalidGuardianSet");
+
+    // A signed message still has to come from the route's emitter at its consistency level
+    const relayFails = async (vaaBody: Buffer, code: string) => {
+      const { vaaHash, postedVaa } = await postOfferVaa(vaaBody);
+      const offset = new anchor.BN(randomBytes(8), "hex");
+      await expectAnchorError(
+        program.methods
+          .relayOfferClone(
+            offer.id,
+            ORIGIN_CHAIN_ID,
+            Array.from(vaaHash),
+            [0, 1, 2, 3].map(() => Array.from(randomBytes(32))),
+            Array.from(randomBytes(32)),
+            new anchor.BN(deserializeLE(randomBytes(16)).toString()),
+            offset
+          )
+          .accountsPartial({
+            payer: authority.publicKey,
+            maker: authority.publicKey,
+            postedVaa,
+            bridgeRoute,
+            chainConfig,
+            consumedMessage: PublicKey.findProgramAddressSync(
+              [Buffer.from("consumed_message"), vaaHash],
+              program.programId
+            )[0],
+            interchainOffer: deriveInterchainOfferPda(program.programId, authority.publicKey, offer.id),
+            computationAccount: getComputationAccAddress(program.programId, offset),
+            clusterAccount: arciumEnv.arciumClusterPubkey,
+          })
+          .rpc({ commitment: "confirmed" }),
+        code
+      );
+    };
+    await relayFails(buildOfferVaaBody(11, offer, Buffer.alloc(32, 0xdd)), "UnknownEmitter");
+    await relayFails(buildOfferVaaBody(12, offer, originEmitter, 200), "InsufficientFinality");
+
+    // A strictly ascending quorum of the current set is accepted
+    const { tx } = postVaaTx(body, sign([0, 2, 3]));
+    await tx;
+  });
+
+  it("A third-party relayer cannot clone an offer under its own identity!", async () => {
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const maker = anchor.web3.Keypair.generate();
+    const relayer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(relayer.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+
+    const id = new anchor.BN(12401);
+    const { vaaHash, postedVaa } = await postOfferVaa(
+      buildOfferVaaBody(20, {
+        id,
+        tokenAOffered: new anchor.BN(1_000_000),
+        tokenBWanted: new anchor.BN(500_000),
+        isTakerNative: true,
+        deadline: new anchor.BN(Math.floor(Date.now() / 1000) + 600),
+        maker: maker.publicKey,
+      })
+    );
+    const interchainOffer = deriveInterchainOfferPda(program.programId, maker.publicKey, id);
+    const relay = (identity: bigint[], offset: anchor.BN, signers: anchor.web3.Keypair[]) => {
+      const idNonce = randomBytes(16);
+      return program.methods
+        .relayOfferClone(
+          id,
+          ORIGIN_CHAIN_ID,
+          Array.from(vaaHash),
+          cipher.encrypt(identity, idNonce).map((c) => Array.from(c)),
+          Array.from(publicKey),
+          new anchor.BN(deserializeLE(idNonce).toString()),
+          offset
+        )
+        .accountsPartial({
+          payer: relayer.publicKey,
+          maker: maker.publicKey,
+          postedVaa,
+          bridgeRoute,
+          chainConfig,
+          consumedMessage: PublicKey.findProgramAddressSync(
+            [Buffer.from("consumed_message"), vaaHash],
+            program.programId
+          )[0],
+          interchainOffer,
+          computationAccount: getComputationAccAddress(program.programId, offset),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers(signers)
+        .rpc({ commitment: "confirmed" });
+    };
+
+    // The relayer commits the offer to its own EVM address without the maker's signature
+    const relayerIdentity = evmIdentityLimbs(evmAddress(secp256k1.utils.randomPrivateKey()));
+    let error: any = null;
+    try {
+      await relay(relayerIdentity, new anchor.BN(randomBytes(8), "hex"), [relayer]);
+    } catch (e) {
+      error = e;
+    }
+    expect(error).to.not.equal(null);
+    expect(error.toString()).to.match(/signature/i);
+    expect(await provider.connection.getAccountInfo(interchainOffer)).to.equal(null);
+
+    // The relayer can still pay for the relay the maker signs
+    const offset = new anchor.BN(randomBytes(8), "hex");
+    await relay(evmIdentityLimbs(takerEvmAddress), offset, [relayer, maker]);
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      offset,
+      program.programId,
+      "confirmed"
+    );
+    const fetched = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(fetched.maker.toBase58()).to.equal(maker.publicKey.toBase58());
+    expect(fetched.payer.toBase58()).to.equal(relayer.publicKey.toBase58());
   });
 
   it("Confidential deposit native works!", async () => {
@@ -312,29 +922,29 @@ describe("ConfidentialCrossChainExchange", () => {
     const sharedSecret = x25519.getSharedSecret(privateKey, mxePublicKey);
     const cipher = new RescueCipher(sharedSecret);
 
-    const amount = BigInt(1000);
-    const plaintext = [amount];
-
+    // The deposit moves real lamports, only the resulting balance is encrypted
+    const amount = BigInt(1_000_000);
     const nonce = randomBytes(16);
-    const ciphertext = cipher.encrypt(plaintext, nonce);
+    const [confidentialPool] = PublicKey.findProgramAddressSync(
+      [Buffer.from("confidential_pool")],
+      program.programId
+    );
 
     const depositEventPromise = awaitEvent("confidentialDepositNativeEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
-
     console.log("amount:", amount.toString());
     console.log("nonce:", nonce.toString("hex"));
-    console.log("ciphertext:", ciphertext[0]);
     console.log("publicKey:", publicKey);
-    console.log("plaintext:", plaintext);
     const queueSig = await program.methods
       .confidentialDepositNative(
         computationOffset,
-        Array.from(ciphertext[0]),
+        new anchor.BN(amount.toString()),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(nonce).toString())
       )
       .accountsPartial({
+        confidentialBalance: deriveConfidentialBalancePda(provider.wallet.publicKey, NATIVE_MINT),
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -351,6 +961,7 @@ describe("ConfidentialCrossChainExchange", () => {
       })
       .rpc({ skipPreflight: true, commitment: "confirmed" });
     console.log("Queue sig is ", queueSig);
+    const poolBalance = await provider.connection.getBalance(confidentialPool);
 
     const finalizeSig = await awaitComputationFinalization(
       provider as anchor.AnchorProvider,
@@ -360,15 +971,82 @@ describe("ConfidentialCrossChainExchange", () => {
     );
     console.log("Finalize sig is ", finalizeSig);
 
+    // A first deposit starts the encrypted balance from zero
     const depositEvent = await depositEventPromise;
+    expect(depositEvent.amount.toString()).to.equal(amount.toString());
     const decrypted = (cipher as any).decrypt(
-      [depositEvent.processedAmount] as any,
+      [depositEvent.balance] as any,
       depositEvent.nonce as any
     )[0];
 
-    console.log("Decrypted amount:", decrypted.toString());
-    console.log("Expected amount:", amount.toString());
+    console.log("Decrypted balance:", decrypted.toString());
+    console.log("Expected balance:", amount.toString());
     expect(decrypted).to.equal(amount);
+    expect(poolBalance).to.be.greaterThanOrEqual(Number(amount));
+  });
+
+  it("Confidential withdraw native pays out only what the encrypted balance covers!", async () => {
+    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+    const user = (provider.wallet as any).payer;
+
+    await initConfidentialWithdrawNativeCompDef(program, owner, false, false);
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    // Balance left by the deposit test
+    const deposited = BigInt(1_000_000);
+    const withdraw = async (amount: bigint) => {
+      const nonce = randomBytes(16);
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      const withdrawEventPromise = awaitEvent("confidentialWithdrawNativeEvent");
+      await program.methods
+        .confidentialWithdrawNative(
+          computationOffset,
+          new anchor.BN(amount.toString()),
+          Array.from(publicKey),
+          new anchor.BN(deserializeLE(nonce).toString())
+        )
+        .accountsPartial({
+          payer: user.publicKey,
+          confidentialBalance: deriveConfidentialBalancePda(user.publicKey, NATIVE_MINT),
+          signPdaAccount: getSignPdaAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("confidential_withdraw_native")).readUInt32LE()
+          ),
+        })
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      await awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        computationOffset,
+        program.programId,
+        "confirmed"
+      );
+      const event = await withdrawEventPromise as any;
+      const balance = cipher.decrypt([event.balance], Uint8Array.from(event.nonce))[0];
+      return { withdrawn: event.withdrawn as boolean, balance };
+    };
+
+    // Covered: paid out and debited
+    const partial = await withdraw(BigInt(400_000));
+    expect(partial.withdrawn).to.equal(true);
+    expect(partial.balance).to.equal(deposited - BigInt(400_000));
+
+    // Not covered: nothing moves and the balance is unchanged
+    const excessive = await withdraw(BigInt(10_000_000));
+    expect(excessive.withdrawn).to.equal(false);
+    expect(excessive.balance).to.equal(deposited - BigInt(400_000));
   });
 
   it("Interchain origin EVM deposit seller SPL works!", async () => {
@@ -403,13 +1081,14 @@ describe("ConfidentialCrossChainExchange", () => {
     const tokenBWanted = new anchor.BN(3_000_000_000);
     const tokenAOffered = new anchor.BN(10_000_000_000);
     const isTakerNative = true;
-    const chainId = new anchor.BN(1);
+    const chainId = ORIGIN_CHAIN_ID;
+    await registerOriginRoute();
     const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
 
     // Confidential seller identity
-    const sellerHashU64 = keccakOrSha3ToU64(publicKey);
+    const sellerHashLimbs = identityHashLimbs(publicKey);
     const nonce = randomBytes(16);
-    const sellerCiphertext = cipher.encrypt([sellerHashU64], nonce);
+    const sellerCiphertext = cipher.encrypt(sellerHashLimbs, nonce);
 
     const depositEventPromise = awaitEvent("interchainOriginEvmDepositSellerSplEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
@@ -420,6 +1099,10 @@ describe("ConfidentialCrossChainExchange", () => {
       id
     );
     console.log('InterchainOffer PDA:', interchainOffer.toBase58());
+
+    // Seller's token A is escrowed in the offer-owned associated token account
+    const { mint: tokenAMint, tokenAccount: sellerTokenAccount } = await createFundedMint(tokenAOffered);
+    const offerTokenVault = getAssociatedTokenAddressSync(tokenAMint, interchainOffer, true);
     console.log('Interchain deposit public inputs:', {
       id: id.toString(),
       tokenBWanted: tokenBWanted.toString(),
@@ -428,7 +1111,7 @@ describe("ConfidentialCrossChainExchange", () => {
       chainId: chainId.toString(),
       deadline: deadline.toString(),
     });
-    console.log('Seller identity hash (u64):', toHexU64(sellerHashU64));
+    console.log('Seller identity hash limbs:', sellerHashLimbs.map(toHexU64));
     console.log('Computation offset (BN hex):', computationOffset.toString('hex'));
 
     const queueSig = await program.methods
@@ -439,14 +1122,21 @@ describe("ConfidentialCrossChainExchange", () => {
         isTakerNative,
         chainId,
         deadline,
-        Array.from(sellerCiphertext[0]),
+        null,
+        sellerCiphertext.map((c) => Array.from(c)),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(nonce).toString()),
         computationOffset
       )
       .accounts({
         payer: (provider.wallet as any).payer.publicKey,
+        maker: (provider.wallet as any).payer.publicKey,
+        bridgeRoute,
+        chainConfig,
         interchainOffer,
+        tokenAMint,
+        sellerTokenAccount,
+        offerTokenVault,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -483,107 +1173,321 @@ describe("ConfidentialCrossChainExchange", () => {
     expect(fetched.tokenBWantedAmount.toString()).to.equal(tokenBWanted.toString());
     expect(fetched.tokenAOfferedAmount.toString()).to.equal(tokenAOffered.toString());
     expect(Boolean(fetched.isTakerNative)).to.equal(isTakerNative);
-  });
+    expect(fetched.tokenAMint.toBase58()).to.equal(tokenAMint.toBase58());
+    expect(fetched.tokenBMint.toBase58()).to.equal(PublicKey.default.toBase58());
 
-  it("Finalize interchain origin EVM offer works!", async () => {
-    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+    const vault = await getAccount(provider.connection, offerTokenVault);
+    expect(vault.amount.toString()).to.equal(tokenAOffered.toString());
+  });
 
-    console.log("Initializing finalize interchain origin EVM offer computation definition");
-    const initFIEOSig = await initFinalizeInterchainOriginEvmOfferCompDef(
-      program,
-      owner,
-      false,
-      false
-    );
-    console.log(
-      "Finalize interchain origin EVM offer computation definition initialized with signature",
-      initFIEOSig
-    );
+  it("HTLC offer needs no bridge, only settles by hashlock and is reclaimed after its timelock!", async () => {
+    const seller = (provider.wallet as any).payer;
 
     const mxePublicKey = await getMXEPublicKeyWithRetry(
       provider as anchor.AnchorProvider,
       program.programId
     );
-
-    console.log("MXE x25519 pubkey is", mxePublicKey);
-
     const privateKey = x25519.utils.randomSecretKey();
     const publicKey = x25519.getPublicKey(privateKey);
-
-    const sharedSecret = x25519.getSharedSecret(privateKey, mxePublicKey);
-    const cipher = new RescueCipher(sharedSecret);
-
-  const id = new anchor.BN(12345);
-  const buyerHashU64 = keccakOrSha3ToU64(publicKey);
-  const nonce = randomBytes(16);
-  const buyerCiphertext = cipher.encrypt([buyerHashU64], nonce);
-  console.log('Finalize interchain public input id:', id.toString());
-  console.log('Buyer identity hash (u64):', toHexU64(buyerHashU64));
-
-    const finalizeEventPromise = awaitEvent("finalizeInterchainOriginEvmOfferEvent");
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const id = new anchor.BN(23457);
+    const tokenAOffered = new anchor.BN(5_000_000_000);
+    const now = Math.floor(Date.now() / 1000);
+    const deadline = new anchor.BN(now + 4);
+    const secret = randomBytes(32);
+    const htlc = {
+      hashlock: Array.from(createHash("sha256").update(secret).digest()),
+      timelock: new anchor.BN(now + 6),
+    };
+    const nonce = randomBytes(16);
+    const sellerCiphertext = cipher.encrypt(identityHashLimbs(publicKey), nonce);
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
-    const queueSig = await program.methods
-      .finalizeInterchainOriginEvmOffer(
+    const interchainOffer = deriveInterchainOfferPda(program.programId, seller.publicKey, id);
+    const { mint: tokenAMint, tokenAccount: sellerTokenAccount } = await createFundedMint(tokenAOffered);
+    const offerTokenVault = getAssociatedTokenAddressSync(tokenAMint, interchainOffer, true);
+
+    // No bridge route is passed, the hashlock replaces the settlement message
+    await program.methods
+      .interchainOriginEvmDepositSellerSpl(
         id,
-        Array.from(buyerCiphertext[0]),
+        new anchor.BN(3_000_000_000),
+        tokenAOffered,
+        true,
+        ORIGIN_CHAIN_ID,
+        deadline,
+        htlc,
+        sellerCiphertext.map((c) => Array.from(c)),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(nonce).toString()),
         computationOffset
       )
-      .accountsPartial({
-        payer: provider.wallet.publicKey,
+      .accounts({
+        payer: seller.publicKey,
+        maker: seller.publicKey,
+        bridgeRoute: null,
+        chainConfig,
+        interchainOffer,
+        tokenAMint,
+        sellerTokenAccount,
+        offerTokenVault,
         signPdaAccount: getSignPdaAccAddress(program.programId),
-        computationAccount: getComputationAccAddress(
-          program.programId,
-          computationOffset
-        ),
+        computationAccount: getComputationAccAddress(program.programId, computationOffset),
         clusterAccount: arciumEnv.arciumClusterPubkey,
         mxeAccount: getMXEAccAddress(program.programId),
         mempoolAccount: getMempoolAccAddress(program.programId),
         executingPool: getExecutingPoolAccAddress(program.programId),
         compDefAccount: getCompDefAccAddress(
           program.programId,
-          Buffer.from(getCompDefAccOffset("finalize_interchain_origin_evm_offer")).readUInt32LE()
+          Buffer.from(getCompDefAccOffset("interchain_origin_evm_deposit_seller_spl")).readUInt32LE()
         ),
-      })
+      } as any)
       .rpc({ skipPreflight: true, commitment: "confirmed" });
-    console.log("Queue sig is ", queueSig);
-
-    const finalizeSig = await awaitComputationFinalization(
+    await awaitComputationFinalization(
       provider as anchor.AnchorProvider,
       computationOffset,
       program.programId,
       "confirmed"
     );
-    console.log("Finalize sig is ", finalizeSig);
 
-    const finalizeEvent = await finalizeEventPromise as any;
-    console.log('FinalizeInterchainOriginEvmOfferEvent:', finalizeEvent);
-    expect(finalizeEvent.acknowledged).to.equal(1);
+    const fetched = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(Buffer.from(fetched.htlc.hashlock).equals(Buffer.from(htlc.hashlock))).to.be.true;
+
+    // The seller cannot pull token A early, and without a verified taker nobody can claim
+    let cancelled = true;
+    try {
+      await program.methods
+        .cancelInterchainOffer(id)
+        .accountsPartial({ interchainOffer, maker: seller.publicKey, payer: seller.publicKey, caller: seller.publicKey, tokenAMint, offerTokenVault, sellerTokenAccount })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      cancelled = false;
+    }
+    expect(cancelled).to.equal(false);
+    let claimed = true;
+    try {
+      await program.methods
+        .claimHtlc(id, Array.from(secret))
+        .accountsPartial({
+          interchainOffer,
+          maker: seller.publicKey,
+          payer: seller.publicKey,
+          buyer: seller.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenAMint,
+          offerTokenVault,
+          buyerTokenAAccount: sellerTokenAccount,
+          tokenATreasury: null,
+        })
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      claimed = false;
+    }
+    expect(claimed).to.equal(false);
+
+    // Past the timelock anyone can return token A to the maker
+    await new Promise((r) => setTimeout(r, 8000));
+    const reclaimEventPromise = awaitEvent("htlcReclaimedEvent");
+    await program.methods
+      .reclaimHtlc(id)
+      .accountsPartial({
+        interchainOffer,
+        maker: seller.publicKey,
+        payer: seller.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenAMint,
+        offerTokenVault,
+        sellerTokenAccount,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const reclaimEvent = await reclaimEventPromise as any;
+    expect(reclaimEvent.refundedTokens.toString()).to.equal(tokenAOffered.toString());
+    const sellerTokens = await getAccount(provider.connection, sellerTokenAccount);
+    expect(sellerTokens.amount.toString()).to.equal(tokenAOffered.toString());
+    expect(await provider.connection.getAccountInfo(offerTokenVault)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(interchainOffer)).to.equal(null);
   });
 
-  it("Deposit seller native works!", async () => {
+  it("Finalize interchain origin EVM offer works!", async () => {
     const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
 
-    console.log("Initializing deposit seller native computation definition");
-    const initDSNSig = await initDepositSellerNativeCompDef(
+    console.log("Initializing finalize interchain origin EVM offer computation definition");
+    const initFIEOSig = await initFinalizeInterchainOriginEvmOfferCompDef(
       program,
       owner,
       false,
       false
     );
     console.log(
-      "Deposit seller native computation definition initialized with signature",
-      initDSNSig
+      "Finalize interchain origin EVM offer computation definition initialized with signature",
+      initFIEOSig
     );
 
-    const mxePublicKey = await getMXEPublicKeyWithRetry(
-      provider as anchor.AnchorProvider,
+    const id = new anchor.BN(12345); // same as relay_offer_clone above
+    console.log('Finalize interchain public input id:', id.toString());
+
+    // The buyer's EVM key signs the offer and the Solana taker with `personal_sign`, checked by
+    // the secp256k1 precompile. The proven address itself is what MPC compares to the commitment
+    const interchainOfferKey = deriveInterchainOfferPda(program.programId, provider.wallet.publicKey, id);
+    const ownershipMessage = evmOwnershipMessage(interchainOfferKey, provider.wallet.publicKey);
+    const finalizeWith = async (evmKey: Uint8Array) => {
+      const ownershipSig = secp256k1.sign(keccak_256(ownershipMessage), evmKey);
+      const evmProofIx = Secp256k1Program.createInstructionWithEthAddress({
+        ethAddress: evmAddress(evmKey),
+        message: ownershipMessage,
+        signature: Buffer.from(ownershipSig.toCompactRawBytes()),
+        recoveryId: ownershipSig.recovery,
+      });
+
+      const finalizeEventPromise = awaitEvent("finalizeInterchainOriginEvmOfferEvent");
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      const queueSig = await program.methods
+        .finalizeInterchainOriginEvmOffer(
+          id,
+          Array.from(evmAddress(evmKey)),
+          computationOffset
+        )
+        .accountsPartial({
+          payer: provider.wallet.publicKey,
+          interchainOffer: interchainOfferKey,
+          maker: provider.wallet.publicKey,
+          signPdaAccount: getSignPdaAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(
+            program.programId,
+            computationOffset
+          ),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("finalize_interchain_origin_evm_offer")).readUInt32LE()
+          ),
+        })
+        .preInstructions([evmProofIx])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      console.log("Queue sig is ", queueSig);
+
+      const finalizeSig = await awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        computationOffset,
+        program.programId,
+        "confirmed"
+      );
+      console.log("Finalize sig is ", finalizeSig);
+      const finalizeEvent = await finalizeEventPromise as any;
+      console.log('FinalizeInterchainOriginEvmOfferEvent:', finalizeEvent);
+      expect(finalizeEvent.acknowledged).to.equal(1);
+      return finalizeEvent.matched;
+    };
+
+    // Proving control of some other address is valid, but it is not the committed one
+    expect(await finalizeWith(secp256k1.utils.randomPrivateKey())).to.equal(false);
+    expect(await finalizeWith(takerEvmKey)).to.equal(true);
+    const buyerEvmAddress = takerEvmAddress;
+
+    // Verified taker is bound to the offer and settles it alone
+    const interchainOffer = deriveInterchainOfferPda(program.programId, provider.wallet.publicKey, id);
+    const fetched = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(fetched.status).to.deep.equal({ matched: {} });
+    expect(fetched.taker.toBase58()).to.equal(provider.wallet.publicKey.toBase58());
+    expect(Buffer.from(fetched.takerEvmAddress).equals(buyerEvmAddress)).to.be.true;
+
+    expect(fetched.originEscrowed).to.equal(true);
+
+    // A party can freeze the matched offer, only the exchange authority lifts the dispute
+    const stranger = anchor.web3.Keypair.generate();
+    const dispute = (caller: anchor.web3.Keypair) =>
+      program.methods
+        .disputeInterchainOffer(id)
+        .accountsPartial({ interchainOffer, caller: caller.publicKey })
+        .signers([caller])
+        .rpc({ commitment: "confirmed" });
+    const resolveDispute = (authority: anchor.web3.Keypair, release: boolean) =>
+      program.methods
+        .resolveInterchainDispute(id, release)
+        .accountsPartial({ interchainOffer, authority: authority.publicKey })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+    const wallet = (provider.wallet as any).payer as anchor.web3.Keypair;
+    await expectAnchorError(dispute(stranger), "NotOfferParty");
+    const disputedEventPromise = awaitEvent("offerDisputedEvent");
+    await dispute(wallet);
+    expect(((await disputedEventPromise) as any).raisedBy.toBase58()).to.equal(wallet.publicKey.toBase58());
+    expect((await (program.account as any).interchainOffer.fetch(interchainOffer)).status).to.deep.equal({ disputed: {} });
+    await expectAnchorError(resolveDispute(stranger, true), "Unauthorized");
+    await resolveDispute(wallet, true);
+    expect((await (program.account as any).interchainOffer.fetch(interchainOffer)).status).to.deep.equal({ matched: {} });
+
+    // An intrachain escrow under the same maker and id must not pay out the relayed offer
+    const intrachainVault = deriveVaultPda(program.programId, "seller_vault", provider.wallet.publicKey, id);
+    await program.methods
+      .depositToSellerVault(id, fetched.tokenAOfferedAmount)
+      .accountsPartial({ seller: provider.wallet.publicKey, sellerVault: intrachainVault })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const intrachainVaultBefore = await provider.connection.getBalance(intrachainVault);
+    const sellerVault = deriveVaultPda(program.programId, "interchain_seller_vault", provider.wallet.publicKey, id);
+
+    // Settlement posts the next sequenced outbound message for the origin chain
+    const [outboundEmitter] = PublicKey.findProgramAddressSync([Buffer.from("emitter")], program.programId);
+    const emitterInfo = await provider.connection.getAccountInfo(outboundEmitter);
+    const sequence = emitterInfo
+      ? (await (program.account as any).outboundEmitter.fetch(outboundEmitter)).sequence
+      : new anchor.BN(0);
+    const [outboundMessage] = PublicKey.findProgramAddressSync(
+      [Buffer.from("outbound_message"), sequence.toArrayLike(Buffer, "le", 8)],
       program.programId
     );
-
-    console.log("MXE x25519 pubkey is", mxePublicKey);
+    await program.methods
+      .executeInterchainSwap(id, Array.from(randomBytes(32)), new anchor.BN(deserializeLE(randomBytes(16)).toString()))
+      .accountsPartial({
+        interchainOffer,
+        maker: provider.wallet.publicKey,
+        buyer: provider.wallet.publicKey,
+        sellerVault,
+        bridgeRoute,
+        outboundMessage,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const settled = await (program.account as any).interchainOffer.fetch(interchainOffer);
+    expect(settled.status).to.deep.equal({ settled: {} });
+    // Token A is released on the origin chain, the Solana escrows are untouched
+    expect(await provider.connection.getBalance(intrachainVault)).to.equal(intrachainVaultBefore);
+
+    const message = await (program.account as any).outboundMessage.fetch(outboundMessage);
+    expect(message.sequence.eq(sequence)).to.be.true;
+    expect(message.offerId.eq(id)).to.be.true;
+    expect(message.bridge).to.deep.equal({ wormhole: {} });
+    expect(message.payload.length).to.equal(75);
+    const emitter = await (program.account as any).outboundEmitter.fetch(outboundEmitter);
+    expect(emitter.sequence.eq(sequence.addn(1))).to.be.true;
+  });
+
+  it("Deposit seller native works!", async () => {
+    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+
+    console.log("Initializing deposit seller native computation definition");
+    const initDSNSig = await initDepositSellerNativeCompDef(
+      program,
+      owner,
+      false,
+      false
+    );
+    console.log(
+      "Deposit seller native computation definition initialized with signature",
+      initDSNSig
+    );
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+
+    console.log("MXE x25519 pubkey is", mxePublicKey);
 
     const privateKey = x25519.utils.randomSecretKey();
     const publicKey = x25519.getPublicKey(privateKey);
@@ -598,9 +1502,10 @@ describe("ConfidentialCrossChainExchange", () => {
     const isTakerNative = true;
     const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
 
-    const sellerHashU64 = keccakOrSha3ToU64(publicKey);
+    // Derived from the payer wallet so the finalize test can present the same commitment
+    const sellerHashLimbs = identityHashLimbs((provider.wallet as any).payer.publicKey.toBytes());
     const nonce = randomBytes(16);
-    const sellerCiphertext = cipher.encrypt([sellerHashU64], nonce);
+    const sellerCiphertext = cipher.encrypt(sellerHashLimbs, nonce);
 
     const depositEventPromise = awaitEvent("depositSellerNativeEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
@@ -618,7 +1523,7 @@ describe("ConfidentialCrossChainExchange", () => {
       isTakerNative,
       deadline: deadline.toString(),
     });
-    console.log('Seller identity hash (u64):', toHexU64(sellerHashU64));
+    console.log('Seller identity hash limbs:', sellerHashLimbs.map(toHexU64));
     console.log('Computation offset (BN hex):', computationOffset.toString('hex'));
 
     const queueSig = await program.methods
@@ -628,13 +1533,15 @@ describe("ConfidentialCrossChainExchange", () => {
         tokenAOffered,
         isTakerNative,
         deadline,
-        Array.from(sellerCiphertext[0]),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(nonce).toString()),
         computationOffset
       )
       .accounts({
         payer: (provider.wallet as any).payer.publicKey,
+        maker: (provider.wallet as any).payer.publicKey,
         intrachainOffer,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
@@ -664,6 +1571,10 @@ describe("ConfidentialCrossChainExchange", () => {
     const depositEvent = await depositEventPromise as any;
     console.log('DepositSellerNativeEvent:', depositEvent);
     expect(depositEvent.acknowledged).to.equal(1);
+
+    // The callback stored the seller identity and opened the offer for takers
+    const fetched = await (program.account as any).intraChainOffer.fetch(intrachainOffer);
+    expect(fetched.status).to.deep.equal({ open: {} });
   });
 
   it("Deposit seller SPL works!", async () => {
@@ -700,9 +1611,9 @@ describe("ConfidentialCrossChainExchange", () => {
     const isTakerNative = false;
     const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
 
-    const sellerHashU64 = keccakOrSha3ToU64(publicKey);
+    const sellerHashLimbs = identityHashLimbs(publicKey);
     const nonce = randomBytes(16);
-    const sellerCiphertext = cipher.encrypt([sellerHashU64], nonce);
+    const sellerCiphertext = cipher.encrypt(sellerHashLimbs, nonce);
 
     const depositEventPromise = awaitEvent("depositSellerSplEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
@@ -713,6 +1624,11 @@ describe("ConfidentialCrossChainExchange", () => {
       id
     );
     console.log('IntrachainOffer PDA (SPL):', intrachainOffer.toBase58());
+
+    // Token A is escrowed at creation, the taker will pay in token B
+    const { mint: tokenAMint, tokenAccount: sellerTokenAccount } = await createFundedMint(tokenAOffered);
+    const { mint: tokenBMint } = await createFundedMint(tokenBWanted);
+    const offerTokenVault = getAssociatedTokenAddressSync(tokenAMint, intrachainOffer, true);
     console.log('Deposit seller SPL public inputs:', {
       id: id.toString(),
       tokenBWanted: tokenBWanted.toString(),
@@ -720,7 +1636,7 @@ describe("ConfidentialCrossChainExchange", () => {
       isTakerNative,
       deadline: deadline.toString(),
     });
-    console.log('Seller identity hash (u64):', toHexU64(sellerHashU64));
+    console.log('Seller identity hash limbs:', sellerHashLimbs.map(toHexU64));
     console.log('Computation offset (BN hex):', computationOffset.toString('hex'));
 
     const queueSig = await program.methods
@@ -730,14 +1646,20 @@ describe("ConfidentialCrossChainExchange", () => {
         tokenAOffered,
         isTakerNative,
         deadline,
-        Array.from(sellerCiphertext[0]),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(nonce).toString()),
         computationOffset
       )
       .accounts({
         payer: (provider.wallet as any).payer.publicKey,
+        maker: (provider.wallet as any).payer.publicKey,
         intrachainOffer,
+        tokenAMint,
+        tokenBMint,
+        sellerTokenAccount,
+        offerTokenVault,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -766,6 +1688,13 @@ describe("ConfidentialCrossChainExchange", () => {
     const depositEvent = await depositEventPromise as any;
     console.log('DepositSellerSplEvent:', depositEvent);
     expect(depositEvent.acknowledged).to.equal(1);
+
+    const fetched = await (program.account as any).intraChainOffer.fetch(intrachainOffer);
+    expect(fetched.tokenAMint.toBase58()).to.equal(tokenAMint.toBase58());
+    expect(fetched.tokenBMint.toBase58()).to.equal(tokenBMint.toBase58());
+
+    const vault = await getAccount(provider.connection, offerTokenVault);
+    expect(vault.amount.toString()).to.equal(tokenAOffered.toString());
   });
 
   it("Finalize intrachain offer works!", async () => {
@@ -794,23 +1723,44 @@ describe("ConfidentialCrossChainExchange", () => {
     const cipher = new RescueCipher(sharedSecret);
 
     const id = new anchor.BN(34567); // same as deposit_seller_native above
-    const buyerHashU64 = keccakOrSha3ToU64(publicKey);
+    const tokenBWanted = new anchor.BN(5_000_000_000);
+    const tokenAOffered = new anchor.BN(10_000_000_000);
+    // The buyer presents the seller commitment shared off-chain
+    const buyerHashLimbs = identityHashLimbs((provider.wallet as any).payer.publicKey.toBytes());
     const nonce = randomBytes(16);
-    const buyerCiphertext = cipher.encrypt([buyerHashU64], nonce);
+    const buyerCiphertext = cipher.encrypt(buyerHashLimbs, nonce);
+
+    // The seller escrow must be funded first, the buyer funds its leg in finalize itself
+    const payer = (provider.wallet as any).payer;
+    const intrachainOffer = deriveIntrachainOfferPda(program.programId, payer.publicKey, id);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", payer.publicKey, id);
+    const buyerVault = deriveBuyerVaultPda(program.programId, "buyer_vault", intrachainOffer, payer.publicKey);
+
+    await program.methods
+      .depositToSellerVault(id, tokenAOffered)
+      .accountsPartial({ seller: payer.publicKey, sellerVault })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    expect(await provider.connection.getAccountInfo(buyerVault)).to.equal(null);
 
     const finalizeEventPromise = awaitEvent("finalizeIntrachainOfferEvent");
+    const filledEventPromise = awaitEvent("offerFilledEvent");
     const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
     const queueSig = await program.methods
       .finalizeIntrachainOffer(
         id,
-        Array.from(buyerCiphertext[0]),
+        tokenAOffered,
+        buyerCiphertext.map((c) => Array.from(c)),
         Array.from(publicKey),
         new anchor.BN(deserializeLE(nonce).toString()),
         computationOffset
       )
       .accountsPartial({
         payer: provider.wallet.publicKey,
+        intrachainOffer,
+        maker: payer.publicKey,
+        sellerVault,
+        buyerVault,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
           program.programId,
@@ -839,6 +1789,24 @@ describe("ConfidentialCrossChainExchange", () => {
     const finalizeEvent = await finalizeEventPromise as any;
     console.log('FinalizeIntrachainOfferEvent:', finalizeEvent);
     expect(finalizeEvent.acknowledged).to.equal(1);
+    expect(finalizeEvent.matched).to.equal(true);
+    expect(finalizeEvent.offerId.toString()).to.equal(id.toString());
+    expect(finalizeEvent.tokenAAmount.toString()).to.equal(tokenAOffered.toString());
+    expect(finalizeEvent.tokenBAmount.toString()).to.equal(tokenBWanted.toString());
+
+    // Both escrows are drained, the seller vault is closed with the last slice
+    expect(await provider.connection.getBalance(sellerVault)).to.equal(0);
+    // Whatever the top-up left over goes back to the buyer, the vault keeps only its rent
+    expect(await provider.connection.getBalance(buyerVault)).to.equal(
+      await provider.connection.getMinimumBalanceForRentExemption(8)
+    );
+
+    // A single fill of the whole offer settles it and closes the account
+    const filledEvent = await filledEventPromise as any;
+    expect(filledEvent.taker.toBase58()).to.equal(payer.publicKey.toBase58());
+    expect(filledEvent.filledAmount.toString()).to.equal(tokenAOffered.toString());
+    expect(filledEvent.remainingAmount.toNumber()).to.equal(0);
+    expect(await provider.connection.getAccountInfo(intrachainOffer)).to.equal(null);
   });
 
   it("Complete intrachain swap with escrow & asset transfers works!", async () => {
@@ -867,8 +1835,8 @@ describe("ConfidentialCrossChainExchange", () => {
     const sellerHashBuffer = createHash("sha256").update(sellerIdentity).digest();
     const buyerHashBuffer = createHash("sha256").update(buyerIdentity).digest();
 
-    const sellerHashU64 = sellerHashBuffer.readBigUInt64LE();
-    const buyerHashU64 = buyerHashBuffer.readBigUInt64LE();
+    const sellerHashLimbs = hashToLimbs(sellerHashBuffer);
+    const buyerHashLimbs = hashToLimbs(buyerHashBuffer);
 
     console.log(`  Seller: ${sellerIdentity} → hash: 0x${sellerHashBuffer.toString('hex').substring(0, 16)}`);
     console.log(`  Buyer:  ${buyerIdentity} → hash: 0x${buyerHashBuffer.toString('hex').substring(0, 16)}\n`);
@@ -892,8 +1860,9 @@ describe("ConfidentialCrossChainExchange", () => {
     // Encrypt identities
     const sellerNonce = randomBytes(16);
     const buyerNonce = randomBytes(16);
-    const sellerCiphertext = sellerCipher.encrypt([sellerHashU64], sellerNonce);
-    const buyerCiphertext = buyerCipher.encrypt([buyerHashU64], buyerNonce);
+    const sellerCiphertext = sellerCipher.encrypt(sellerHashLimbs, sellerNonce);
+    // The buyer proves knowledge of the seller commitment handed over off-chain
+    const buyerCiphertext = buyerCipher.encrypt(sellerHashLimbs, buyerNonce);
 
     // 2. SELLER CREATES OFFER
     console.log("💼 STEP 2: Seller creates offer...");
@@ -925,13 +1894,15 @@ describe("ConfidentialCrossChainExchange", () => {
         tokenAAmount,
         true,
         new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
-        Array.from(sellerCiphertext[0]),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
         Array.from(sellerPublicKey),
         new anchor.BN(deserializeLE(sellerNonce).toString()),
         computationOffset
       )
       .accounts({
         payer: seller.publicKey,
+        maker: seller.publicKey,
         intrachainOffer,
         signPdaAccount: getSignPdaAccAddress(program.programId),
         computationAccount: getComputationAccAddress(
@@ -990,57 +1961,14 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log(`  Vault balance:         ${vaultBalanceAfterSeller / anchor.web3.LAMPORTS_PER_SOL} SOL`);
     console.log(`  ✅ Seller deposited ${tokenAAmount.toNumber() / anchor.web3.LAMPORTS_PER_SOL} SOL to escrow\n`);
 
-    // 4. BUYER FINALIZES OFFER
-    console.log("🤝 STEP 4: Buyer finalizes offer...");
-
-    const buyerComputationOffset = new anchor.BN(randomBytes(8), "hex");
-
-    // Initialize finalize computation definition (already done in previous test)
-    // await initFinalizeIntrachainOfferCompDef(program, owner, false, false);
-
-    await program.methods
-      .finalizeIntrachainOffer(
-        offerId,
-        Array.from(buyerCiphertext[0]),
-        Array.from(buyerPublicKey),
-        new anchor.BN(deserializeLE(buyerNonce).toString()),
-        buyerComputationOffset
-      )
-      .accountsPartial({
-        payer: buyer.publicKey,
-        signPdaAccount: getSignPdaAccAddress(program.programId),
-        computationAccount: getComputationAccAddress(
-          program.programId,
-          buyerComputationOffset
-        ),
-        clusterAccount: arciumEnv.arciumClusterPubkey,
-        mxeAccount: getMXEAccAddress(program.programId),
-        mempoolAccount: getMempoolAccAddress(program.programId),
-        executingPool: getExecutingPoolAccAddress(program.programId),
-        compDefAccount: getCompDefAccAddress(
-          program.programId,
-          Buffer.from(getCompDefAccOffset("finalize_intrachain_offer")).readUInt32LE()
-        ),
-      })
-      .signers([buyer])
-      .rpc({ skipPreflight: true, commitment: "confirmed" });
-
-    await awaitComputationFinalization(
-      provider as anchor.AnchorProvider,
-      buyerComputationOffset,
-      program.programId,
-      "confirmed"
-    );
-    console.log("  ✅ Buyer identity verified via MPC\n");
-
-    // 5. BUYER DEPOSITS TO ESCROW
-    console.log("🔒 STEP 5: Buyer deposits token B to escrow vault...");
+    // 4. BUYER DEPOSITS TO ESCROW
+    console.log("🔒 STEP 4: Buyer deposits token B to escrow vault...");
 
     const buyerVault = anchor.web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from("buyer_vault"),
+        intrachainOffer.toBuffer(),
         buyer.publicKey.toBuffer(),
-        offerId.toArrayLike(Buffer, "le", 8),
       ],
       program.programId
     )[0];
@@ -1053,6 +1981,7 @@ describe("ConfidentialCrossChainExchange", () => {
       .depositToBuyerVault(offerId, tokenBAmount)
       .accountsPartial({
         buyer: buyer.publicKey,
+        offer: intrachainOffer,
         buyerVault: buyerVault,
       })
       .signers([buyer])
@@ -1065,29 +1994,62 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log(`  Vault balance:         ${vaultBalanceAfterBuyer / anchor.web3.LAMPORTS_PER_SOL} SOL`);
     console.log(`  ✅ Buyer deposited ${tokenBAmount.toNumber() / anchor.web3.LAMPORTS_PER_SOL} SOL to escrow\n`);
 
-    // 6. EXECUTE ATOMIC SWAP
-    console.log("⚡ STEP 6: Executing atomic swap...");
+    // 5. BUYER FINALIZES OFFER, MPC CALLBACK SETTLES
+    console.log("⚡ STEP 5: Buyer finalizes offer, callback settles atomically...");
     console.log("  📊 Pre-swap balances:");
     console.log(`     Seller: ${sellerBalanceAfterDeposit / anchor.web3.LAMPORTS_PER_SOL} SOL`);
     console.log(`     Buyer:  ${buyerBalanceAfterDeposit / anchor.web3.LAMPORTS_PER_SOL} SOL`);
     console.log(`     Seller vault: ${vaultBalanceAfterSeller / anchor.web3.LAMPORTS_PER_SOL} SOL`);
     console.log(`     Buyer vault:  ${vaultBalanceAfterBuyer / anchor.web3.LAMPORTS_PER_SOL} SOL\n`);
 
-    // Execute the swap via Anchor RPC. Include both seller and buyer as signers to ensure
-    // any required signatures are present and to let Anchor manage recent blockhash fetching.
+    const buyerComputationOffset = new anchor.BN(randomBytes(8), "hex");
+    const finalizeEventPromise = awaitEvent("finalizeIntrachainOfferEvent");
+
+    // Finalize computation definition is already initialized in a previous test
     await program.methods
-      .executeIntrachainSwap(offerId)
+      .finalizeIntrachainOffer(
+        offerId,
+        tokenAAmount,
+        buyerCiphertext.map((c) => Array.from(c)),
+        Array.from(buyerPublicKey),
+        new anchor.BN(deserializeLE(buyerNonce).toString()),
+        buyerComputationOffset
+      )
       .accountsPartial({
-        intrachainOffer: intrachainOffer,
-        seller: seller.publicKey,
-        buyer: buyer.publicKey,
-        sellerVault: sellerVault,
-        buyerVault: buyerVault,
+        payer: buyer.publicKey,
+        intrachainOffer,
+        maker: seller.publicKey,
+        sellerVault,
+        buyerVault,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          buyerComputationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("finalize_intrachain_offer")).readUInt32LE()
+        ),
       })
-      .signers([seller, buyer])
+      .signers([buyer])
       .rpc({ skipPreflight: true, commitment: "confirmed" });
 
-    // 7. VERIFY FINAL BALANCES
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      buyerComputationOffset,
+      program.programId,
+      "confirmed"
+    );
+    const finalizeEvent = await finalizeEventPromise as any;
+    expect(finalizeEvent.offerId.toString()).to.equal(offerId.toString());
+    expect(finalizeEvent.matched).to.equal(true);
+    console.log("  ✅ Buyer identity verified via MPC and escrow released\n");
+
+    // 6. VERIFY FINAL BALANCES
     console.log("\n  📊 Post-swap balances:");
 
     const sellerFinal = await provider.connection.getBalance(seller.publicKey);
@@ -1121,101 +2083,2060 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("╚══════════════════════════════════════════════════════════════╝\n");
   });
 
-  async function initAddTogetherCompDef(
-    program: Program<ConfidentialCrossChainExchange>,
-    owner: anchor.web3.Keypair,
-    uploadRawCircuit: boolean,
-    offchainSource: boolean
-  ): Promise<string> {
-    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
-      "ComputationDefinitionAccount"
+  it("Intrachain offer fills in pro-rata slices and closes when fully filled!", async () => {
+    const seller = (provider.wallet as any).payer;
+    const buyers = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const kp of buyers) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 3 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig, "confirmed");
+    }
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
     );
-    const offset = getCompDefAccOffset("add_together");
+    const sellerHashLimbs = identityHashLimbs(seller.publicKey.toBytes());
 
-    const compDefPDA = PublicKey.findProgramAddressSync(
-      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
-      getArciumProgAddress()
-    )[0];
+    const offerId = new anchor.BN(67890);
+    const tokenAAmount = new anchor.BN(3 * anchor.web3.LAMPORTS_PER_SOL);
+    const tokenBAmount = new anchor.BN(1 * anchor.web3.LAMPORTS_PER_SOL);
+    const minFill = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+    const intrachainOffer = deriveIntrachainOfferPda(program.programId, seller.publicKey, offerId);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", seller.publicKey, offerId);
 
-    console.log("Comp def pda is ", compDefPDA);
+    const sellerPrivateKey = x25519.utils.randomSecretKey();
+    const sellerNonce = randomBytes(16);
+    const sellerCiphertext = new RescueCipher(
+      x25519.getSharedSecret(sellerPrivateKey, mxePublicKey)
+    ).encrypt(sellerHashLimbs, sellerNonce);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
-    const sig = await program.methods
-      .initAddTogetherCompDef()
+    await program.methods
+      .depositSellerNative(
+        offerId,
+        tokenBAmount,
+        tokenAAmount,
+        true,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        minFill,
+        sellerCiphertext.map((c) => Array.from(c)),
+        Array.from(x25519.getPublicKey(sellerPrivateKey)),
+        new anchor.BN(deserializeLE(sellerNonce).toString()),
+        computationOffset
+      )
       .accounts({
-        compDefAccount: compDefPDA,
-        payer: owner.publicKey,
+        payer: seller.publicKey,
+        maker: seller.publicKey,
+        intrachainOffer,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          computationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
         mxeAccount: getMXEAccAddress(program.programId),
-      })
-      .signers([owner])
-      .rpc({
-        commitment: "confirmed",
-      });
-    console.log("Init add together computation definition transaction", sig);
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("deposit_seller_native")).readUInt32LE()
+        ),
+      } as any)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
 
-    if (uploadRawCircuit) {
-      const rawCircuit = fs.readFileSync("build/add_together.arcis");
+    await program.methods
+      .depositToSellerVault(offerId, tokenAAmount)
+      .accountsPartial({ seller: seller.publicKey, sellerVault })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
 
-      await uploadCircuit(
+    // Each buyer proves the seller commitment and takes one slice of token A
+    const fill = async (buyer: anchor.web3.Keypair, fillAmount: anchor.BN) => {
+      const privateKey = x25519.utils.randomSecretKey();
+      const nonce = randomBytes(16);
+      const buyerCiphertext = new RescueCipher(
+        x25519.getSharedSecret(privateKey, mxePublicKey)
+      ).encrypt(sellerHashLimbs, nonce);
+      const offset = new anchor.BN(randomBytes(8), "hex");
+      const filledEventPromise = awaitEvent("offerFilledEvent");
+      await program.methods
+        .finalizeIntrachainOffer(
+          offerId,
+          fillAmount,
+          buyerCiphertext.map((c) => Array.from(c)),
+          Array.from(x25519.getPublicKey(privateKey)),
+          new anchor.BN(deserializeLE(nonce).toString()),
+          offset
+        )
+        .accountsPartial({
+          payer: buyer.publicKey,
+          intrachainOffer,
+          maker: seller.publicKey,
+          sellerVault,
+          buyerVault: deriveBuyerVaultPda(program.programId, "buyer_vault", intrachainOffer, buyer.publicKey),
+          signPdaAccount: getSignPdaAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, offset),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("finalize_intrachain_offer")).readUInt32LE()
+          ),
+        })
+        .signers([buyer])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      await awaitComputationFinalization(
         provider as anchor.AnchorProvider,
-        "add_together",
+        offset,
         program.programId,
-        rawCircuit,
-        true
-      );
-    } else if (!offchainSource) {
-      const finalizeTx = await buildFinalizeCompDefTx(
-        provider as anchor.AnchorProvider,
-        Buffer.from(offset).readUInt32LE(),
-        program.programId
+        "confirmed"
       );
+      return await filledEventPromise as any;
+    };
 
-      const latestBlockhash = await provider.connection.getLatestBlockhash();
-      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
-      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
-
-      finalizeTx.sign(owner);
+    // Slices below the maker's minimum are rejected up front
+    let rejected = false;
+    try {
+      await fill(buyers[0], minFill.divn(2));
+    } catch (e) {
+      rejected = true;
+    }
+    expect(rejected).to.equal(true);
+
+    const firstFill = await fill(buyers[0], new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL));
+    expect(firstFill.tokenAAmount.toString()).to.equal((2 * anchor.web3.LAMPORTS_PER_SOL).toString());
+    // 2/3 of the token B price, rounded up in the seller's favour
+    expect(firstFill.tokenBAmount.toString()).to.equal("666666667");
+    expect(firstFill.remainingAmount.toString()).to.equal(anchor.web3.LAMPORTS_PER_SOL.toString());
+
+    const partial = await (program.account as any).intraChainOffer.fetch(intrachainOffer);
+    expect(partial.status).to.deep.equal({ open: {} });
+    expect(partial.filledAmount.toString()).to.equal((2 * anchor.web3.LAMPORTS_PER_SOL).toString());
+    expect(partial.taker.toBase58()).to.equal(buyers[0].publicKey.toBase58());
+
+    // The last slice pays exactly what is left of the token B price
+    const lastFill = await fill(buyers[1], new anchor.BN(anchor.web3.LAMPORTS_PER_SOL));
+    expect(lastFill.tokenBAmount.toString()).to.equal("333333333");
+    expect(lastFill.remainingAmount.toNumber()).to.equal(0);
+    expect(await provider.connection.getAccountInfo(intrachainOffer)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(sellerVault)).to.equal(null);
+  });
 
-      await provider.sendAndConfirm(finalizeTx);
+  it("Intrachain settlement pays maker and taker fees to the treasury!", async () => {
+    const authority = (provider.wallet as any).payer;
+    const seller = authority;
+    const buyer = anchor.web3.Keypair.generate();
+    const treasury = anchor.web3.Keypair.generate();
+    for (const kp of [buyer, treasury]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig, "confirmed");
     }
-    return sig;
-  }
-  async function initRelayOfferCloneCompDef(
-    program: Program<ConfidentialCrossChainExchange>,
-    owner: anchor.web3.Keypair,
-    uploadRawCircuit: boolean,
-    offchainSource: boolean
-  ): Promise<string> {
-    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
-      "ComputationDefinitionAccount"
+
+    // Taker pays 0.5% of token A, maker 0.3% of token B, both in native SOL here
+    await program.methods
+      .updateConfig(
+        { ...zeroFees, intrachainMakerBps: 30, intrachainTakerBps: 50 },
+        [{ mint: PublicKey.default, treasury: treasury.publicKey }]
+      )
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
     );
-    const offset = getCompDefAccOffset("relay_offer_clone");
+    const sellerHashLimbs = identityHashLimbs(seller.publicKey.toBytes());
 
-    const compDefPDA = PublicKey.findProgramAddressSync(
-      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
-      getArciumProgAddress()
-    )[0];
+    const offerId = new anchor.BN(68901);
+    const tokenAAmount = new anchor.BN(1 * anchor.web3.LAMPORTS_PER_SOL);
+    const tokenBAmount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2);
+    const intrachainOffer = deriveIntrachainOfferPda(program.programId, seller.publicKey, offerId);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", seller.publicKey, offerId);
 
-    console.log("Comp def pda is ", compDefPDA);
+    const sellerPrivateKey = x25519.utils.randomSecretKey();
+    const sellerNonce = randomBytes(16);
+    const sellerCiphertext = new RescueCipher(
+      x25519.getSharedSecret(sellerPrivateKey, mxePublicKey)
+    ).encrypt(sellerHashLimbs, sellerNonce);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
 
-    const sig = await program.methods
-      .initRelayOfferCloneCompDef()
+    await program.methods
+      .depositSellerNative(
+        offerId,
+        tokenBAmount,
+        tokenAAmount,
+        true,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
+        Array.from(x25519.getPublicKey(sellerPrivateKey)),
+        new anchor.BN(deserializeLE(sellerNonce).toString()),
+        computationOffset
+      )
       .accounts({
-        compDefAccount: compDefPDA,
-        payer: owner.publicKey,
+        payer: seller.publicKey,
+        maker: seller.publicKey,
+        intrachainOffer,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          computationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
         mxeAccount: getMXEAccAddress(program.programId),
-      })
-      .signers([owner])
-      .rpc({
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("deposit_seller_native")).readUInt32LE()
+        ),
+      } as any)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    await program.methods
+      .depositToSellerVault(offerId, tokenAAmount)
+      .accountsPartial({ seller: seller.publicKey, sellerVault })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const buyerPrivateKey = x25519.utils.randomSecretKey();
+    const buyerNonce = randomBytes(16);
+    const buyerCiphertext = new RescueCipher(
+      x25519.getSharedSecret(buyerPrivateKey, mxePublicKey)
+    ).encrypt(sellerHashLimbs, buyerNonce);
+    const finalizeOffset = new anchor.BN(randomBytes(8), "hex");
+    const treasuryBefore = await provider.connection.getBalance(treasury.publicKey);
+    const filledEventPromise = awaitEvent("offerFilledEvent");
+
+    await program.methods
+      .finalizeIntrachainOffer(
+        offerId,
+        tokenAAmount,
+        buyerCiphertext.map((c) => Array.from(c)),
+        Array.from(x25519.getPublicKey(buyerPrivateKey)),
+        new anchor.BN(deserializeLE(buyerNonce).toString()),
+        finalizeOffset
+      )
+      .accountsPartial({
+        payer: buyer.publicKey,
+        intrachainOffer,
+        maker: seller.publicKey,
+        sellerVault,
+        buyerVault: deriveBuyerVaultPda(program.programId, "buyer_vault", intrachainOffer, buyer.publicKey),
+        exchangeConfig,
+        tokenATreasury: treasury.publicKey,
+        tokenBTreasury: treasury.publicKey,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(program.programId, finalizeOffset),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("finalize_intrachain_offer")).readUInt32LE()
+        ),
+      })
+      .signers([buyer])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      finalizeOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    const takerFee = tokenAAmount.muln(50).divn(10_000);
+    const makerFee = tokenBAmount.muln(30).divn(10_000);
+    const filledEvent = await filledEventPromise as any;
+    expect(filledEvent.tokenAAmount.toString()).to.equal(tokenAAmount.sub(takerFee).toString());
+    expect(filledEvent.tokenBAmount.toString()).to.equal(tokenBAmount.sub(makerFee).toString());
+    expect(await provider.connection.getBalance(treasury.publicKey)).to.equal(
+      treasuryBefore + takerFee.add(makerFee).toNumber()
+    );
+
+    // Later tests assume a fee-free exchange
+    await program.methods
+      .updateConfig(zeroFees, [])
+      .accountsPartial({ authority: authority.publicKey, exchangeConfig })
+      .rpc({ commitment: "confirmed" });
+  });
+
+  it("Cancel intrachain offer refunds the seller and closes the offer!", async () => {
+    const seller = (provider.wallet as any).payer;
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const offerId = new anchor.BN(56789);
+    const tokenAAmount = new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL);
+    const tokenBAmount = new anchor.BN(1 * anchor.web3.LAMPORTS_PER_SOL);
+    const nonce = randomBytes(16);
+    const sellerCiphertext = cipher.encrypt(identityHashLimbs(seller.publicKey.toBytes()), nonce);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+
+    const intrachainOffer = deriveIntrachainOfferPda(program.programId, seller.publicKey, offerId);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", seller.publicKey, offerId);
+
+    await program.methods
+      .depositSellerNative(
+        offerId,
+        tokenBAmount,
+        tokenAAmount,
+        true,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(nonce).toString()),
+        computationOffset
+      )
+      .accounts({
+        payer: seller.publicKey,
+        maker: seller.publicKey,
+        intrachainOffer,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          computationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("deposit_seller_native")).readUInt32LE()
+        ),
+      } as any)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    await program.methods
+      .depositToSellerVault(offerId, tokenAAmount)
+      .accountsPartial({ seller: seller.publicKey, sellerVault })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    const vaultBalance = await provider.connection.getBalance(sellerVault);
+
+    // Two buyers prefund their leg, only one of them is passed to cancel
+    const otherBuyer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(otherBuyer.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+    const buyerVault = deriveBuyerVaultPda(program.programId, "buyer_vault", intrachainOffer, seller.publicKey);
+    const otherBuyerVault = deriveBuyerVaultPda(program.programId, "buyer_vault", intrachainOffer, otherBuyer.publicKey);
+    const buyerDeposit = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10);
+    await program.methods
+      .depositToBuyerVault(offerId, buyerDeposit)
+      .accountsPartial({ buyer: seller.publicKey, offer: intrachainOffer, buyerVault })
+      .rpc({ commitment: "confirmed" });
+    await program.methods
+      .depositToBuyerVault(offerId, buyerDeposit)
+      .accountsPartial({ buyer: otherBuyer.publicKey, offer: intrachainOffer, buyerVault: otherBuyerVault })
+      .signers([otherBuyer])
+      .rpc({ commitment: "confirmed" });
+    const buyerVaultBalance = await provider.connection.getBalance(buyerVault);
+    const otherBuyerVaultBalance = await provider.connection.getBalance(otherBuyerVault);
+
+    // Nobody pulls a vault out from under a live offer
+    let withdrawnEarly = true;
+    try {
+      await program.methods
+        .withdrawBuyerVault()
+        .accountsPartial({ buyer: otherBuyer.publicKey, offer: intrachainOffer, buyerVault: otherBuyerVault })
+        .signers([otherBuyer])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      withdrawnEarly = false;
+    }
+    expect(withdrawnEarly).to.equal(false);
+
+    const cancelEventPromise = awaitEvent("offerCancelledEvent");
+    await program.methods
+      .cancelIntrachainOffer(offerId)
+      .accountsPartial({
+        intrachainOffer,
+        maker: seller.publicKey,
+        payer: seller.publicKey,
+        caller: seller.publicKey,
+        sellerVault,
+        buyer: seller.publicKey,
+        buyerVault,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const cancelEvent = await cancelEventPromise as any;
+    expect(cancelEvent.offerId.toString()).to.equal(offerId.toString());
+    expect(cancelEvent.refunded.toNumber()).to.equal(vaultBalance);
+    expect(cancelEvent.buyerRefund.toNumber()).to.equal(buyerVaultBalance);
+
+    // Vaults and offer are all gone, rent included
+    expect(await provider.connection.getAccountInfo(sellerVault)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(buyerVault)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(intrachainOffer)).to.equal(null);
+
+    // The buyer left out of cancel reclaims its escrow once the offer is closed
+    const withdrawEventPromise = awaitEvent("buyerVaultWithdrawnEvent");
+    await program.methods
+      .withdrawBuyerVault()
+      .accountsPartial({ buyer: otherBuyer.publicKey, offer: intrachainOffer, buyerVault: otherBuyerVault })
+      .signers([otherBuyer])
+      .rpc({ commitment: "confirmed" });
+    const withdrawEvent = await withdrawEventPromise as any;
+    expect(withdrawEvent.refunded.toNumber()).to.equal(otherBuyerVaultBalance);
+    expect(await provider.connection.getAccountInfo(otherBuyerVault)).to.equal(null);
+  });
+
+  it("Relayer-funded offer is owned by the maker and refunds rent to the relayer!", async () => {
+    const relayer = (provider.wallet as any).payer;
+    const maker = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(maker.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const offerId = new anchor.BN(57890);
+    const nonce = randomBytes(16);
+    const sellerCiphertext = cipher.encrypt(identityHashLimbs(maker.publicKey.toBytes()), nonce);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+
+    const intrachainOffer = deriveIntrachainOfferPda(program.programId, maker.publicKey, offerId);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", maker.publicKey, offerId);
+
+    await program.methods
+      .depositSellerNative(
+        offerId,
+        new anchor.BN(anchor.web3.LAMPORTS_PER_SOL),
+        new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2),
+        true,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(nonce).toString()),
+        computationOffset
+      )
+      .accounts({
+        payer: relayer.publicKey,
+        maker: maker.publicKey,
+        intrachainOffer,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          computationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("deposit_seller_native")).readUInt32LE()
+        ),
+      } as any)
+      .signers([maker])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    const offer = await (program.account as any).intraChainOffer.fetch(intrachainOffer);
+    expect(offer.maker.toBase58()).to.equal(maker.publicKey.toBase58());
+    expect(offer.payer.toBase58()).to.equal(relayer.publicKey.toBase58());
+    expect(offer.taker).to.equal(null);
+
+    // Only the recorded payer can receive the offer rent
+    let rejected = false;
+    try {
+      await program.methods
+        .cancelIntrachainOffer(offerId)
+        .accountsPartial({ intrachainOffer, maker: maker.publicKey, payer: maker.publicKey, caller: maker.publicKey, sellerVault })
+        .signers([maker])
+        .rpc({ commitment: "confirmed" });
+    } catch (e) {
+      rejected = true;
+    }
+    expect(rejected).to.equal(true);
+
+    // Anyone else may only refund an offer through the expiry crank
+    await expectAnchorError(
+      program.methods
+        .cancelIntrachainOffer(offerId)
+        .accountsPartial({ intrachainOffer, maker: maker.publicKey, payer: relayer.publicKey, caller: relayer.publicKey, sellerVault })
+        .signers([relayer])
+        .rpc({ commitment: "confirmed" }),
+      "NotOfferMaker"
+    );
+
+    const offerRent = await provider.connection.getBalance(intrachainOffer);
+    const relayerBefore = await provider.connection.getBalance(relayer.publicKey);
+    await program.methods
+      .cancelIntrachainOffer(offerId)
+      .accountsPartial({ intrachainOffer, maker: maker.publicKey, payer: relayer.publicKey, caller: maker.publicKey, sellerVault })
+      .signers([maker])
+      .rpc({ commitment: "confirmed" });
+
+    expect(await provider.connection.getAccountInfo(intrachainOffer)).to.equal(null);
+    expect(await provider.connection.getBalance(relayer.publicKey)).to.be.at.least(relayerBefore + offerRent - 10_000);
+  });
+
+  it("Expire intrachain offer refunds both vaults and pays the cranker!", async () => {
+    const seller = (provider.wallet as any).payer;
+    const buyer = anchor.web3.Keypair.generate();
+    const cranker = anchor.web3.Keypair.generate();
+    for (const kp of [buyer, cranker]) {
+      const sig = await provider.connection.requestAirdrop(kp.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig, "confirmed");
+    }
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const offerId = new anchor.BN(67890);
+    const tokenAAmount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+    const tokenBAmount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 2);
+    // Short deadline so the crank becomes callable within the test
+    const deadline = Math.floor(Date.now() / 1000) + 20;
+    const nonce = randomBytes(16);
+    const sellerCiphertext = cipher.encrypt(identityHashLimbs(seller.publicKey.toBytes()), nonce);
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+
+    const intrachainOffer = deriveIntrachainOfferPda(program.programId, seller.publicKey, offerId);
+    const sellerVault = deriveVaultPda(program.programId, "seller_vault", seller.publicKey, offerId);
+    const buyerVault = deriveBuyerVaultPda(program.programId, "buyer_vault", intrachainOffer, buyer.publicKey);
+
+    await program.methods
+      .depositSellerNative(
+        offerId,
+        tokenBAmount,
+        tokenAAmount,
+        true,
+        new anchor.BN(deadline),
+        new anchor.BN(0),
+        sellerCiphertext.map((c) => Array.from(c)),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(nonce).toString()),
+        computationOffset
+      )
+      .accounts({
+        payer: seller.publicKey,
+        maker: seller.publicKey,
+        intrachainOffer,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(
+          program.programId,
+          computationOffset
+        ),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("deposit_seller_native")).readUInt32LE()
+        ),
+      } as any)
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      computationOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    await program.methods
+      .depositToSellerVault(offerId, tokenAAmount)
+      .accountsPartial({ seller: seller.publicKey, sellerVault })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await program.methods
+      .depositToBuyerVault(offerId, tokenBAmount)
+      .accountsPartial({ buyer: buyer.publicKey, offer: intrachainOffer, buyerVault })
+      .signers([buyer])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+
+    const buyerVaultBalance = await provider.connection.getBalance(buyerVault);
+    const crankerBefore = await provider.connection.getBalance(cranker.publicKey);
+
+    // Wait until the validator clock is past the deadline
+    while ((await provider.connection.getBlockTime(await provider.connection.getSlot())) <= deadline) {
+      await new Promise((resolve) => setTimeout(resolve, 1000));
+    }
+
+    const expireEventPromise = awaitEvent("offerExpiredEvent");
+    await program.methods
+      .expireIntrachainOffer(offerId)
+      .accountsPartial({
+        intrachainOffer,
+        maker: seller.publicKey,
+        payer: seller.publicKey,
+        sellerVault,
+        buyer: buyer.publicKey,
+        buyerVault,
+        caller: cranker.publicKey,
+      })
+      .signers([cranker])
+      .rpc({ commitment: "confirmed" });
+
+    const expireEvent = await expireEventPromise as any;
+    expect(expireEvent.buyerRefund.toNumber()).to.equal(buyerVaultBalance);
+    expect(expireEvent.bounty.toNumber()).to.be.above(0);
+
+    // Cranker nets the bounty minus its transaction fee
+    const crankerAfter = await provider.connection.getBalance(cranker.publicKey);
+    expect(crankerAfter - crankerBefore + 5000).to.equal(expireEvent.bounty.toNumber());
+
+    expect(await provider.connection.getAccountInfo(sellerVault)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(buyerVault)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(intrachainOffer)).to.equal(null);
+  });
+
+  it("Token-2022 transfer-fee mint is quoted net of both fees, refunded on cancel and delivered in full on fill!", async () => {
+    const seller = (provider.wallet as any).payer as anchor.web3.Keypair;
+
+    // 1% transfer fee, uncapped for the amounts used here
+    const feeBps = 100;
+    const mintKp = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const createMintTx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: seller.publicKey,
+        newAccountPubkey: mintKp.publicKey,
+        space: mintLen,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        mintKp.publicKey,
+        seller.publicKey,
+        seller.publicKey,
+        feeBps,
+        BigInt("18446744073709551615"),
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(mintKp.publicKey, 6, seller.publicKey, null, TOKEN_2022_PROGRAM_ID)
+    );
+    await anchor.web3.sendAndConfirmTransaction(provider.connection, createMintTx, [seller, mintKp], {
+      commitment: "confirmed",
+    });
+    const tokenAMint = mintKp.publicKey;
+
+    const tokenAOffered = new anchor.BN(1_000_000_000);
+    const sellerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        seller,
+        tokenAMint,
+        seller.publicKey,
+        false,
+        "confirmed",
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+    ).address;
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const privateKey = x25519.utils.randomSecretKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+    const depositFeeOffer = async (offerId: anchor.BN) => {
+      await mintTo(
+        provider.connection,
+        seller,
+        tokenAMint,
+        sellerTokenAccount,
+        seller,
+        BigInt(tokenAOffered.toString()),
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const nonce = randomBytes(16);
+      const sellerCiphertext = cipher.encrypt(identityHashLimbs(seller.publicKey.toBytes()), nonce);
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+
+      const intrachainOffer = deriveIntrachainOfferPda(program.programId, seller.publicKey, offerId);
+      const sellerVault = deriveVaultPda(program.programId, "seller_vault", seller.publicKey, offerId);
+      const offerTokenVault = getAssociatedTokenAddressSync(
+        tokenAMint,
+        intrachainOffer,
+        true,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      await program.methods
+        .depositSellerSpl(
+          offerId,
+          new anchor.BN(anchor.web3.LAMPORTS_PER_SOL),
+          tokenAOffered,
+          true,
+          new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+          new anchor.BN(0),
+          sellerCiphertext.map((c) => Array.from(c)),
+          Array.from(publicKey),
+          new anchor.BN(deserializeLE(nonce).toString()),
+          computationOffset
+        )
+        .accounts({
+          payer: seller.publicKey,
+          maker: seller.publicKey,
+          intrachainOffer,
+          tokenAMint,
+          sellerTokenAccount,
+          offerTokenVault,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          signPdaAccount: getSignPdaAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(
+            program.programId,
+            computationOffset
+          ),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("deposit_seller_spl")).readUInt32LE()
+          ),
+        } as any)
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      await awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        computationOffset,
+        program.programId,
+        "confirmed"
+      );
+      return { intrachainOffer, sellerVault, offerTokenVault };
+    };
+
+    // The vault receives the offer minus the inbound fee, and the offer is quoted
+    // minus the outbound fee on top so the buyer receives all of it
+    const received = tokenAOffered.muln(10_000 - feeBps).divn(10_000);
+    const quoted = received.sub(received.muln(feeBps).addn(9_999).divn(10_000));
+
+    const offerId = new anchor.BN(78901);
+    const { intrachainOffer, sellerVault, offerTokenVault } = await depositFeeOffer(offerId);
+    const fetched = await (program.account as any).intraChainOffer.fetch(intrachainOffer);
+    expect(fetched.tokenAOfferedAmount.toString()).to.equal(quoted.toString());
+    // Partial fills would pay the outbound fee per slice, a fee mint fills at once
+    expect(fetched.minFillAmount.toString()).to.equal(quoted.toString());
+    const vault = await getAccount(provider.connection, offerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID);
+    expect(vault.amount.toString()).to.equal(received.toString());
+
+    const cancelEventPromise = awaitEvent("offerCancelledEvent");
+    await program.methods
+      .cancelIntrachainOffer(offerId)
+      .accountsPartial({
+        intrachainOffer,
+        maker: seller.publicKey,
+        payer: seller.publicKey,
+        caller: seller.publicKey,
+        sellerVault,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        tokenAMint,
+        offerTokenVault,
+        sellerTokenAccount,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const cancelEvent = await cancelEventPromise as any;
+    expect(cancelEvent.refundedTokens.toString()).to.equal(received.toString());
+    expect(await provider.connection.getAccountInfo(offerTokenVault)).to.equal(null);
+
+    // A buyer filling the whole offer ends up with exactly the quoted amount of token A
+    const fillId = new anchor.BN(78902);
+    const fill = await depositFeeOffer(fillId);
+    const balanceBefore = (await getAccount(provider.connection, sellerTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+    const buyerVault = deriveBuyerVaultPda(program.programId, "buyer_vault", fill.intrachainOffer, seller.publicKey);
+    const fillNonce = randomBytes(16);
+    const buyerCiphertext = cipher.encrypt(identityHashLimbs(seller.publicKey.toBytes()), fillNonce);
+    const fillOffset = new anchor.BN(randomBytes(8), "hex");
+    const finalizeEventPromise = awaitEvent("finalizeIntrachainOfferEvent");
+    await program.methods
+      .finalizeIntrachainOffer(
+        fillId,
+        quoted,
+        buyerCiphertext.map((c) => Array.from(c)),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(fillNonce).toString()),
+        fillOffset
+      )
+      .accountsPartial({
+        payer: seller.publicKey,
+        intrachainOffer: fill.intrachainOffer,
+        maker: seller.publicKey,
+        sellerVault: fill.sellerVault,
+        buyerVault,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        tokenAMint,
+        offerTokenVault: fill.offerTokenVault,
+        buyerTokenAAccount: sellerTokenAccount,
+        signPdaAccount: getSignPdaAccAddress(program.programId),
+        computationAccount: getComputationAccAddress(program.programId, fillOffset),
+        clusterAccount: arciumEnv.arciumClusterPubkey,
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("finalize_intrachain_offer")).readUInt32LE()
+        ),
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      fillOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    const finalizeEvent = await finalizeEventPromise as any;
+    expect(finalizeEvent.matched).to.equal(true);
+    expect(finalizeEvent.tokenAAmount.toString()).to.equal(quoted.toString());
+    const balanceAfter = (await getAccount(provider.connection, sellerTokenAccount, "confirmed", TOKEN_2022_PROGRAM_ID)).amount;
+    expect((balanceAfter - balanceBefore).toString()).to.equal(quoted.toString());
+    expect(await provider.connection.getAccountInfo(fill.offerTokenVault)).to.equal(null);
+  });
+
+  it("Confidential offer settles both legs between encrypted balances and reveals the amounts only to both parties!", async () => {
+    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+    const maker = (provider.wallet as any).payer;
+
+    await initCreateConfidentialOfferCompDef(program, owner, false, false);
+    await initMatchConfidentialOfferCompDef(program, owner, false, false);
+    await initConfidentialDepositSplCompDef(program, owner, false, false);
+    await initConfidentialWithdrawSplCompDef(program, owner, false, false);
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const makerPrivateKey = x25519.utils.randomSecretKey();
+    const makerPublicKey = x25519.getPublicKey(makerPrivateKey);
+    const makerCipher = new RescueCipher(x25519.getSharedSecret(makerPrivateKey, mxePublicKey));
+    const takerPrivateKey = x25519.utils.randomSecretKey();
+    const takerPublicKey = x25519.getPublicKey(takerPrivateKey);
+    const takerCipher = new RescueCipher(x25519.getSharedSecret(takerPrivateKey, mxePublicKey));
+
+    const taker = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(taker.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+
+    // Token A is lamports from the maker's native balance, token B a mint the taker holds
+    const tokenBMint = await createMint(provider.connection, maker, maker.publicKey, null, 6);
+    const takerTokenB = await getOrCreateAssociatedTokenAccount(provider.connection, maker, tokenBMint, taker.publicKey);
+    const makerTokenB = await getOrCreateAssociatedTokenAccount(provider.connection, maker, tokenBMint, maker.publicKey);
+    await mintTo(provider.connection, maker, tokenBMint, takerTokenB.address, maker, BigInt(600));
+    const [poolTokenVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("confidential_pool_token"), tokenBMint.toBuffer()],
+      program.programId
+    );
+    const queueAccounts = (computationOffset: anchor.BN, circuit: string) => ({
+      signPdaAccount: getSignPdaAccAddress(program.programId),
+      computationAccount: getComputationAccAddress(program.programId, computationOffset),
+      clusterAccount: arciumEnv.arciumClusterPubkey,
+      mxeAccount: getMXEAccAddress(program.programId),
+      mempoolAccount: getMempoolAccAddress(program.programId),
+      executingPool: getExecutingPoolAccAddress(program.programId),
+      compDefAccount: getCompDefAccAddress(
+        program.programId,
+        Buffer.from(getCompDefAccOffset(circuit)).readUInt32LE()
+      ),
+    });
+    const finalize = (computationOffset: anchor.BN) =>
+      awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        computationOffset,
+        program.programId,
+        "confirmed"
+      );
+
+    // The taker funds a token B balance, only the deposit itself is public
+    const depositOffset = new anchor.BN(randomBytes(8), "hex");
+    const depositNonce = randomBytes(16);
+    const depositEventPromise = awaitEvent("confidentialDepositSplEvent");
+    await program.methods
+      .confidentialDepositSpl(
+        depositOffset,
+        new anchor.BN(600),
+        Array.from(takerPublicKey),
+        new anchor.BN(deserializeLE(depositNonce).toString())
+      )
+      .accountsPartial({
+        payer: taker.publicKey,
+        mint: tokenBMint,
+        confidentialBalance: deriveConfidentialBalancePda(taker.publicKey, tokenBMint),
+        payerTokenAccount: takerTokenB.address,
+        poolTokenVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...queueAccounts(depositOffset, "confidential_deposit_spl"),
+      })
+      .signers([taker])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await finalize(depositOffset);
+    const depositEvent = await depositEventPromise as any;
+    expect(takerCipher.decrypt([depositEvent.balance], Uint8Array.from(depositEvent.nonce))[0]).to.equal(BigInt(600));
+
+    // Ask: 1000 lamports for 500 token B, never written in plaintext
+    const offerId = new anchor.BN(67890);
+    const askNonce = randomBytes(16);
+    const askCiphertext = makerCipher.encrypt([BigInt(1000), BigInt(500)], askNonce);
+    const createOffset = new anchor.BN(randomBytes(8), "hex");
+    const [confidentialOffer] = PublicKey.findProgramAddressSync(
+      [Buffer.from("ConfidentialOffer"), maker.publicKey.toBuffer(), u64ToLeBytes(offerId)],
+      program.programId
+    );
+
+    const createdEventPromise = awaitEvent("confidentialOfferCreatedEvent");
+    await program.methods
+      .createConfidentialOffer(
+        offerId,
+        NATIVE_MINT,
+        tokenBMint,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        askCiphertext.map((c) => Array.from(c)),
+        Array.from(makerPublicKey),
+        new anchor.BN(deserializeLE(askNonce).toString()),
+        createOffset
+      )
+      .accountsPartial({
+        payer: maker.publicKey,
+        maker: maker.publicKey,
+        confidentialOffer,
+        makerTokenBBalance: deriveConfidentialBalancePda(maker.publicKey, tokenBMint),
+        ...queueAccounts(createOffset, "create_confidential_offer"),
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await finalize(createOffset);
+    const createdEvent = await createdEventPromise as any;
+    expect(createdEvent.offerId.toString()).to.equal(offerId.toString());
+
+    // Bids take any part of the ask for at most the given token B
+    const match = (
+      bidder: anchor.web3.Keypair,
+      matchOffset: anchor.BN,
+      tokenAAmount: bigint,
+      maxTokenBAmount: bigint
+    ) => {
+      const bidNonce = randomBytes(16);
+      const bidCiphertext = takerCipher.encrypt([tokenAAmount, maxTokenBAmount], bidNonce);
+      const makerNonce = randomBytes(16);
+      return program.methods
+        .matchConfidentialOffer(
+          offerId,
+          bidCiphertext.map((c) => Array.from(c)),
+          Array.from(takerPublicKey),
+          new anchor.BN(deserializeLE(bidNonce).toString()),
+          new anchor.BN(deserializeLE(makerNonce).toString()),
+          matchOffset
+        )
+        .accountsPartial({
+          payer: bidder.publicKey,
+          confidentialOffer,
+          makerTokenABalance: deriveConfidentialBalancePda(maker.publicKey, NATIVE_MINT),
+          makerTokenBBalance: deriveConfidentialBalancePda(maker.publicKey, tokenBMint),
+          takerTokenABalance: deriveConfidentialBalancePda(bidder.publicKey, NATIVE_MINT),
+          takerTokenBBalance: deriveConfidentialBalancePda(bidder.publicKey, tokenBMint),
+          ...queueAccounts(matchOffset, "match_confidential_offer"),
+        })
+        .signers([bidder])
+        .rpc({ commitment: "confirmed" });
+    };
+
+    // Makers cannot take their own offer
+    await expectAnchorError(
+      match(maker, new anchor.BN(randomBytes(8), "hex"), BigInt(1000), BigInt(600)),
+      "SelfMatch"
+    );
+    const matchAndSettle = async (tokenAAmount: bigint, maxTokenBAmount: bigint) => {
+      const matchOffset = new anchor.BN(randomBytes(8), "hex");
+      const matchEventPromise = awaitEvent("confidentialMatchEvent");
+      await match(taker, matchOffset, tokenAAmount, maxTokenBAmount);
+      await finalize(matchOffset);
+      const matchEvent = await matchEventPromise as any;
+      const takerSettlement = takerCipher.decrypt(
+        matchEvent.takerSettlement,
+        Uint8Array.from(matchEvent.takerNonce)
+      );
+      const makerSettlement = makerCipher.decrypt(
+        matchEvent.makerSettlement,
+        Uint8Array.from(matchEvent.makerNonce)
+      );
+      return { matchEvent, takerSettlement, makerSettlement };
+    };
+    const matchBond = 1_000_000;
+
+    // A bid under the maker's price settles nothing and its bond goes to the maker
+    const makerBeforeJunk = await provider.connection.getBalance(maker.publicKey);
+    const junk = await matchAndSettle(BigInt(100), BigInt(10));
+    expect(junk.matchEvent.matched).to.equal(false);
+    expect(junk.takerSettlement).to.deep.equal([BigInt(0), BigInt(0)]);
+    expect(await provider.connection.getBalance(maker.publicKey)).to.equal(makerBeforeJunk + matchBond);
+
+    // A partial bid takes 400 of the 1000 lamports at the maker's rate, the rest stays open
+    const takerBeforePartial = await provider.connection.getBalance(taker.publicKey);
+    const partial = await matchAndSettle(BigInt(400), BigInt(250));
+    expect(partial.matchEvent.matched).to.equal(true);
+    expect(partial.matchEvent.offerFilled).to.equal(false);
+    expect(partial.takerSettlement).to.deep.equal([BigInt(400), BigInt(200)]);
+    expect(partial.makerSettlement).to.deep.equal([BigInt(400), BigInt(200)]);
+    // The bond came back, only the transaction fee was spent
+    expect(takerBeforePartial - (await provider.connection.getBalance(taker.publicKey))).to.be.below(matchBond);
+    let fetched = await (program.account as any).confidentialOffer.fetch(confidentialOffer);
+    expect(fetched.status).to.deep.equal({ open: {} });
+    expect(fetched.taker).to.equal(null);
+
+    // The next bid takes the rest and settles the offer
+    const rest = await matchAndSettle(BigInt(600), BigInt(300));
+    expect(rest.matchEvent.matched).to.equal(true);
+    expect(rest.matchEvent.offerFilled).to.equal(true);
+    expect(rest.takerSettlement).to.deep.equal([BigInt(600), BigInt(300)]);
+    expect(rest.makerSettlement).to.deep.equal([BigInt(600), BigInt(300)]);
+
+    fetched = await (program.account as any).confidentialOffer.fetch(confidentialOffer);
+    expect(fetched.status).to.deep.equal({ settled: {} });
+    expect(fetched.taker.toBase58()).to.equal(taker.publicKey.toBase58());
+
+    // The legs really moved: the taker withdraws the lamports, the maker the token B
+    const takerWithdrawOffset = new anchor.BN(randomBytes(8), "hex");
+    const takerWithdrawNonce = randomBytes(16);
+    const takerWithdrawPromise = awaitEvent("confidentialWithdrawNativeEvent");
+    await program.methods
+      .confidentialWithdrawNative(
+        takerWithdrawOffset,
+        new anchor.BN(1000),
+        Array.from(takerPublicKey),
+        new anchor.BN(deserializeLE(takerWithdrawNonce).toString())
+      )
+      .accountsPartial({
+        payer: taker.publicKey,
+        confidentialBalance: deriveConfidentialBalancePda(taker.publicKey, NATIVE_MINT),
+        ...queueAccounts(takerWithdrawOffset, "confidential_withdraw_native"),
+      })
+      .signers([taker])
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await finalize(takerWithdrawOffset);
+    const takerWithdraw = await takerWithdrawPromise as any;
+    expect(takerWithdraw.withdrawn).to.equal(true);
+    expect(takerCipher.decrypt([takerWithdraw.balance], Uint8Array.from(takerWithdraw.nonce))[0]).to.equal(BigInt(0));
+
+    const makerWithdrawOffset = new anchor.BN(randomBytes(8), "hex");
+    const makerWithdrawNonce = randomBytes(16);
+    const makerWithdrawPromise = awaitEvent("confidentialWithdrawSplEvent");
+    await program.methods
+      .confidentialWithdrawSpl(
+        makerWithdrawOffset,
+        new anchor.BN(500),
+        Array.from(makerPublicKey),
+        new anchor.BN(deserializeLE(makerWithdrawNonce).toString())
+      )
+      .accountsPartial({
+        payer: maker.publicKey,
+        mint: tokenBMint,
+        confidentialBalance: deriveConfidentialBalancePda(maker.publicKey, tokenBMint),
+        payerTokenAccount: makerTokenB.address,
+        poolTokenVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...queueAccounts(makerWithdrawOffset, "confidential_withdraw_spl"),
+      })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await finalize(makerWithdrawOffset);
+    const makerWithdraw = await makerWithdrawPromise as any;
+    expect(makerWithdraw.withdrawn).to.equal(true);
+    expect((await getAccount(provider.connection, makerTokenB.address)).amount).to.equal(BigInt(500));
+
+    await program.methods
+      .closeConfidentialOffer(offerId)
+      .accountsPartial({ confidentialOffer, maker: maker.publicKey, payer: maker.publicKey })
+      .rpc({ commitment: "confirmed" });
+    expect(await provider.connection.getAccountInfo(confidentialOffer)).to.equal(null);
+  });
+
+  it("Sealed auction reveals only the winner and clearing price, settles from collateral and slashes a no-show!", async () => {
+    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+    const maker = (provider.wallet as any).payer;
+
+    await initPlaceSealedBidCompDef(program, owner, false, false);
+    await initResolveSealedAuctionCompDef(program, owner, false, false);
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+
+    const lotAmount = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL);
+    const reservePrice = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10);
+    const biddingEndsAt = Math.floor(Date.now() / 1000) + 60;
+    // The second auction's winner never settles, its deadline passes shortly after bidding
+    const createAuction = async (auctionId: anchor.BN, bidCollateral: anchor.BN, settleDeadline: number) => {
+      const [sealedAuction] = PublicKey.findProgramAddressSync(
+        [Buffer.from("SealedAuction"), maker.publicKey.toBuffer(), u64ToLeBytes(auctionId)],
+        program.programId
+      );
+      const [auctionVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from("auction_vault"), maker.publicKey.toBuffer(), u64ToLeBytes(auctionId)],
+        program.programId
+      );
+      await program.methods
+        .createSealedAuction(
+          auctionId,
+          lotAmount,
+          reservePrice,
+          bidCollateral,
+          new anchor.BN(biddingEndsAt),
+          new anchor.BN(settleDeadline)
+        )
+        .accountsPartial({ payer: maker.publicKey, maker: maker.publicKey, sealedAuction, auctionVault })
+        .rpc({ commitment: "confirmed" });
+      return { auctionId, sealedAuction, auctionVault };
+    };
+    // A collateral tier below the reserve could never pay, it is rejected up front
+    await expectAnchorError(
+      createAuction(new anchor.BN(78900), reservePrice.divn(2), biddingEndsAt + 600),
+      "CollateralBelowReserve"
+    );
+    // Every bidder escrows the same tier, so the collateral says nothing about a bid
+    const auctionCollateral = new anchor.BN(0.6 * anchor.web3.LAMPORTS_PER_SOL);
+    const auction = await createAuction(new anchor.BN(78901), auctionCollateral, biddingEndsAt + 600);
+    const unsettled = await createAuction(
+      new anchor.BN(78902),
+      new anchor.BN(0.4 * anchor.web3.LAMPORTS_PER_SOL),
+      biddingEndsAt + 30
+    );
+    const bidVault = (sealedAuction: PublicKey, bidder: PublicKey) =>
+      deriveBuyerVaultPda(program.programId, "bid_vault", sealedAuction, bidder);
+
+    const queueAccounts = (sealedAuction: PublicKey, computationOffset: anchor.BN, circuit: string) => ({
+      sealedAuction,
+      signPdaAccount: getSignPdaAccAddress(program.programId),
+      computationAccount: getComputationAccAddress(program.programId, computationOffset),
+      clusterAccount: arciumEnv.arciumClusterPubkey,
+      mxeAccount: getMXEAccAddress(program.programId),
+      mempoolAccount: getMempoolAccAddress(program.programId),
+      executingPool: getExecutingPoolAccAddress(program.programId),
+      compDefAccount: getCompDefAccAddress(
+        program.programId,
+        Buffer.from(getCompDefAccOffset(circuit)).readUInt32LE()
+      ),
+    });
+
+    // The bid stays sealed, the vault escrows the auction's tier
+    const placeBid = async (
+      { auctionId, sealedAuction }: { auctionId: anchor.BN; sealedAuction: PublicKey },
+      bidder: anchor.web3.Keypair,
+      amount: bigint
+    ) => {
+      const privateKey = x25519.utils.randomSecretKey();
+      const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+      const nonce = randomBytes(16);
+      const [ciphertext] = cipher.encrypt([amount], nonce);
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+
+      const bidEventPromise = awaitEvent("sealedBidPlacedEvent");
+      await program.methods
+        .placeSealedBid(
+          auctionId,
+          Array.from(ciphertext),
+          Array.from(x25519.getPublicKey(privateKey)),
+          new anchor.BN(deserializeLE(nonce).toString()),
+          computationOffset
+        )
+        .accountsPartial({
+          payer: bidder.publicKey,
+          bidVault: bidVault(sealedAuction, bidder.publicKey),
+          ...queueAccounts(sealedAuction, computationOffset, "place_sealed_bid"),
+        })
+        .signers([bidder])
+        .rpc({ commitment: "confirmed" });
+      await awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        computationOffset,
+        program.programId,
+        "confirmed"
+      );
+      return (await bidEventPromise as any).bidCount as number;
+    };
+
+    const resolve = async ({ auctionId, sealedAuction }: { auctionId: anchor.BN; sealedAuction: PublicKey }) => {
+      const resolveOffset = new anchor.BN(randomBytes(8), "hex");
+      const resolvedEventPromise = awaitEvent("sealedAuctionResolvedEvent");
+      await program.methods
+        .resolveSealedAuction(auctionId, resolveOffset)
+        .accountsPartial({
+          payer: maker.publicKey,
+          ...queueAccounts(sealedAuction, resolveOffset, "resolve_sealed_auction"),
+        })
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      await awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        resolveOffset,
+        program.programId,
+        "confirmed"
+      );
+      return await resolvedEventPromise as any;
+    };
+
+    const withdrawCollateral = (sealedAuction: PublicKey, bidder: anchor.web3.Keypair) =>
+      program.methods
+        .withdrawBidCollateral()
+        .accountsPartial({
+          bidder: bidder.publicKey,
+          sealedAuction,
+          bidVault: bidVault(sealedAuction, bidder.publicKey),
+        })
+        .signers([bidder])
+        .rpc({ commitment: "confirmed" });
+
+    // Three buyers bid blind, the second one bids highest
+    const sol = (n: number) => BigInt(n * anchor.web3.LAMPORTS_PER_SOL);
+    const bids = [sol(0.2), sol(0.5), sol(0.45)];
+    const collateral = BigInt(auctionCollateral.toString());
+    const bidders = bids.map(() => anchor.web3.Keypair.generate());
+    const noShow = anchor.web3.Keypair.generate();
+    for (const bidder of [...bidders, noShow]) {
+      const airdropSig = await provider.connection.requestAirdrop(bidder.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(airdropSig, "confirmed");
+    }
+
+    for (const [i, bidder] of bidders.entries()) {
+      expect(await placeBid(auction, bidder, bids[i])).to.equal(i + 1);
+      // Each vault holds the same tier on top of its rent
+      expect(await provider.connection.getBalance(bidVault(auction.sealedAuction, bidder.publicKey))).to.be.greaterThan(
+        Number(collateral)
+      );
+    }
+    expect(await placeBid(unsettled, noShow, sol(0.2))).to.equal(1);
+
+    // A bid that counts cannot be withdrawn while the auction may still pick it
+    await expectAnchorError(withdrawCollateral(auction.sealedAuction, bidders[0]), "CollateralLocked");
+
+    // Resolve only once the window has closed
+    const waitMs = (biddingEndsAt + 2) * 1000 - Date.now();
+    if (waitMs > 0) {
+      await new Promise((resolve) => setTimeout(resolve, waitMs));
+    }
+    const resolvedEvent = await resolve(auction);
+    expect(resolvedEvent.winner.toBase58()).to.equal(bidders[1].publicKey.toBase58());
+    expect(resolvedEvent.clearingPrice.toString()).to.equal(bids[1].toString());
+    expect((await resolve(unsettled)).winner.toBase58()).to.equal(noShow.publicKey.toBase58());
+
+    // Losers get their collateral back, the winner's stays locked until settlement
+    const loserBefore = await provider.connection.getBalance(bidders[0].publicKey);
+    await withdrawCollateral(auction.sealedAuction, bidders[0]);
+    expect(await provider.connection.getBalance(bidders[0].publicKey)).to.be.greaterThan(
+      loserBefore + Number(collateral) - 10_000
+    );
+    await expectAnchorError(withdrawCollateral(auction.sealedAuction, bidders[1]), "CollateralLocked");
+
+    // The clearing price comes out of the winner's collateral, the rest and the lot go to the winner
+    const makerBefore = await provider.connection.getBalance(maker.publicKey);
+    const winnerBefore = await provider.connection.getBalance(bidders[1].publicKey);
+    const settledEventPromise = awaitEvent("sealedAuctionSettledEvent");
+    await program.methods
+      .settleSealedAuction(auction.auctionId)
+      .accountsPartial({
+        sealedAuction: auction.sealedAuction,
+        winner: bidders[1].publicKey,
+        maker: maker.publicKey,
+        payer: maker.publicKey,
+        auctionVault: auction.auctionVault,
+        bidVault: bidVault(auction.sealedAuction, bidders[1].publicKey),
+      })
+      .signers([bidders[1]])
+      .rpc({ commitment: "confirmed" });
+    const settledEvent = await settledEventPromise as any;
+    expect(settledEvent.lotAmount.toNumber()).to.be.greaterThanOrEqual(lotAmount.toNumber());
+    expect(await provider.connection.getBalance(maker.publicKey)).to.be.greaterThan(
+      makerBefore + Number(bids[1])
+    );
+    expect(await provider.connection.getBalance(bidders[1].publicKey)).to.be.greaterThan(
+      winnerBefore + lotAmount.toNumber() + Number(collateral - bids[1]) - 10_000
+    );
+    expect(await provider.connection.getAccountInfo(auction.sealedAuction)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(auction.auctionVault)).to.equal(null);
+    expect(await provider.connection.getAccountInfo(bidVault(auction.sealedAuction, bidders[1].publicKey))).to.equal(null);
+
+    // With the auction closed the remaining loser withdraws too
+    await withdrawCollateral(auction.sealedAuction, bidders[2]);
+    expect(await provider.connection.getAccountInfo(bidVault(auction.sealedAuction, bidders[2].publicKey))).to.equal(null);
+
+    // The no-show's collateral goes to the maker along with the lot once the deadline passes
+    const slashWaitMs = (biddingEndsAt + 32) * 1000 - Date.now();
+    if (slashWaitMs > 0) {
+      await new Promise((resolve) => setTimeout(resolve, slashWaitMs));
+    }
+    const slashedEventPromise = awaitEvent("sealedAuctionSlashedEvent");
+    await program.methods
+      .closeSealedAuction(unsettled.auctionId)
+      .accountsPartial({
+        sealedAuction: unsettled.sealedAuction,
+        maker: maker.publicKey,
+        payer: maker.publicKey,
+        auctionVault: unsettled.auctionVault,
+        winnerBidVault: bidVault(unsettled.sealedAuction, noShow.publicKey),
+      })
+      .rpc({ commitment: "confirmed" });
+    const slashedEvent = await slashedEventPromise as any;
+    expect(slashedEvent.winner.toBase58()).to.equal(noShow.publicKey.toBase58());
+    expect(slashedEvent.slashed.toNumber()).to.be.greaterThanOrEqual(Number(sol(0.4)));
+    expect(await provider.connection.getAccountInfo(unsettled.sealedAuction)).to.equal(null);
+  });
+
+  it("Order book crosses encrypted limit orders and settles fills from escrow!", async () => {
+    const owner = readKpJson(`${os.homedir()}/.config/solana/id.json`);
+    const seller = (provider.wallet as any).payer;
+
+    await initAddOrderCompDef(program, owner, false, false);
+    await initMatchOrdersCompDef(program, owner, false, false);
+
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+
+    const buyer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(buyer.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdropSig, "confirmed");
+
+    // The seller holds base, the buyer quote, and each needs an account for both
+    const { mint: baseMint, tokenAccount: sellerBase } = await createFundedMint(new anchor.BN(10));
+    const quoteMint = await createMint(provider.connection, seller, seller.publicKey, null, 6);
+    const tokenAccount = async (mint: PublicKey, holder: PublicKey) =>
+      (await getOrCreateAssociatedTokenAccount(provider.connection, seller, mint, holder)).address;
+    const sellerQuote = await tokenAccount(quoteMint, seller.publicKey);
+    const buyerBase = await tokenAccount(baseMint, buyer.publicKey);
+    const buyerQuote = await tokenAccount(quoteMint, buyer.publicKey);
+    await mintTo(provider.connection, seller, quoteMint, buyerQuote, seller, BigInt(660));
+    const tokens = async (account: PublicKey) => (await getAccount(provider.connection, account)).amount;
+
+    const [orderBook] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order_book"), baseMint.toBuffer(), quoteMint.toBuffer()],
+      program.programId
+    );
+    const bookVault = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("order_book_vault"), orderBook.toBuffer(), mint.toBuffer()],
+        program.programId
+      )[0];
+    const orderTtl = 30;
+    await program.methods
+      .createOrderBook(new anchor.BN(orderTtl))
+      .accountsPartial({
+        authority: owner.publicKey,
+        orderBook,
+        baseMint,
+        quoteMint,
+        baseVault: bookVault(baseMint),
+        quoteVault: bookVault(quoteMint),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    const queueAccounts = (computationOffset: anchor.BN, circuit: string) => ({
+      orderBook,
+      signPdaAccount: getSignPdaAccAddress(program.programId),
+      computationAccount: getComputationAccAddress(program.programId, computationOffset),
+      clusterAccount: arciumEnv.arciumClusterPubkey,
+      mxeAccount: getMXEAccAddress(program.programId),
+      mempoolAccount: getMempoolAccAddress(program.programId),
+      executingPool: getExecutingPoolAccAddress(program.programId),
+      compDefAccount: getCompDefAccAddress(
+        program.programId,
+        Buffer.from(getCompDefAccOffset(circuit)).readUInt32LE()
+      ),
+    });
+    const bookAccounts = {
+      baseMint,
+      quoteMint,
+      baseVault: bookVault(baseMint),
+      quoteVault: bookVault(quoteMint),
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // Side is 1 for a buy, then the limit price and the size
+    const addOrder = async (
+      trader: anchor.web3.Keypair,
+      order: bigint[],
+      escrow: { base: number; quote: number; baseAccount: PublicKey; quoteAccount: PublicKey }
+    ) => {
+      const privateKey = x25519.utils.randomSecretKey();
+      const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+      const nonce = randomBytes(16);
+      const ciphertext = cipher.encrypt(order, nonce);
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      const addedEventPromise = awaitEvent("orderAddedEvent");
+      await program.methods
+        .addOrder(
+          new anchor.BN(escrow.base),
+          new anchor.BN(escrow.quote),
+          ciphertext.map((c) => Array.from(c)),
+          Array.from(x25519.getPublicKey(privateKey)),
+          new anchor.BN(deserializeLE(nonce).toString()),
+          computationOffset
+        )
+        .accountsPartial({
+          payer: trader.publicKey,
+          ...bookAccounts,
+          traderBaseAccount: escrow.baseAccount,
+          traderQuoteAccount: escrow.quoteAccount,
+          ...queueAccounts(computationOffset, "add_order"),
+        })
+        .signers([trader])
+        .rpc({ skipPreflight: true, commitment: "confirmed" });
+      await awaitComputationFinalization(
+        provider as anchor.AnchorProvider,
+        computationOffset,
+        program.programId,
+        "confirmed"
+      );
+      return (await addedEventPromise as any).slot as number;
+    };
+    const cancelOrder = (caller: anchor.web3.Keypair, slot: number, baseAccount: PublicKey, quoteAccount: PublicKey) =>
+      program.methods
+        .cancelOrder(slot)
+        .accountsPartial({
+          caller: caller.publicKey,
+          orderBook,
+          ...bookAccounts,
+          ownerBaseAccount: baseAccount,
+          ownerQuoteAccount: quoteAccount,
+        })
+        .signers([caller])
+        .rpc({ commitment: "confirmed" });
+
+    // The sell escrows its size in base, the buy size times its limit in quote
+    const sellSlot = await addOrder(seller, [BigInt(0), BigInt(100), BigInt(10)], {
+      base: 10,
+      quote: 0,
+      baseAccount: sellerBase,
+      quoteAccount: sellerQuote,
+    });
+    const sellPlacedAt = Math.floor(Date.now() / 1000);
+    const buySlot = await addOrder(buyer, [BigInt(1), BigInt(110), BigInt(6)], {
+      base: 0,
+      quote: 660,
+      baseAccount: buyerBase,
+      quoteAccount: buyerQuote,
+    });
+    expect(sellSlot).to.equal(0);
+    expect(buySlot).to.equal(1);
+    expect(await tokens(bookVault(baseMint))).to.equal(BigInt(10));
+    expect(await tokens(bookVault(quoteMint))).to.equal(BigInt(660));
+
+    // Only the owner may pull a fresh order
+    await expectAnchorError(cancelOrder(buyer, sellSlot, sellerBase, sellerQuote), "OrderNotExpired");
+
+    const matchOffset = new anchor.BN(randomBytes(8), "hex");
+    const fillEventPromise = awaitEvent("orderFillEvent");
+    await program.methods
+      .matchOrders(matchOffset)
+      .accountsPartial({ payer: seller.publicKey, ...queueAccounts(matchOffset, "match_orders") })
+      .rpc({ skipPreflight: true, commitment: "confirmed" });
+    await awaitComputationFinalization(
+      provider as anchor.AnchorProvider,
+      matchOffset,
+      program.programId,
+      "confirmed"
+    );
+
+    // Filled at the midpoint and settled between the two slots' escrow
+    const fillEvent = await fillEventPromise as any;
+    expect(fillEvent.buyer.toBase58()).to.equal(buyer.publicKey.toBase58());
+    expect(fillEvent.seller.toBase58()).to.equal(seller.publicKey.toBase58());
+    expect(fillEvent.price.toNumber()).to.equal(105);
+    expect(fillEvent.size.toNumber()).to.equal(6);
+
+    // Both slots stay occupied, the book doesn't reveal which orders are exhausted
+    const fetched = await (program.account as any).orderBook.fetch(orderBook);
+    const sellOrder = fetched.slots[sellSlot];
+    const buyOrder = fetched.slots[buySlot];
+    expect(sellOrder.owner.toBase58()).to.equal(seller.publicKey.toBase58());
+    expect(sellOrder.baseEscrow.toNumber()).to.equal(4);
+    expect(sellOrder.quoteEscrow.toNumber()).to.equal(630);
+    expect(buyOrder.baseEscrow.toNumber()).to.equal(6);
+    expect(buyOrder.quoteEscrow.toNumber()).to.equal(30);
+
+    // The buyer collects the fill and the unspent quote
+    const cancelledPromise = awaitEvent("orderCancelledEvent");
+    await cancelOrder(buyer, buySlot, buyerBase, buyerQuote);
+    const cancelled = await cancelledPromise as any;
+    expect(cancelled.evicted).to.equal(false);
+    expect(await tokens(buyerBase)).to.equal(BigInt(6));
+    expect(await tokens(buyerQuote)).to.equal(BigInt(30));
+
+    // Once stale, anyone may evict the rest of the sell, refunding the seller
+    const waitMs = (sellPlacedAt + orderTtl + 2) * 1000 - Date.now();
+    if (waitMs > 0) {
+      await new Promise((resolve) => setTimeout(resolve, waitMs));
+    }
+    const evictedPromise = awaitEvent("orderCancelledEvent");
+    await cancelOrder(buyer, sellSlot, sellerBase, sellerQuote);
+    const evicted = await evictedPromise as any;
+    expect(evicted.evicted).to.equal(true);
+    expect(evicted.baseRefund.toNumber()).to.equal(4);
+    expect(evicted.quoteRefund.toNumber()).to.equal(630);
+    expect(await tokens(sellerBase)).to.equal(BigInt(4));
+    expect(await tokens(sellerQuote)).to.equal(BigInt(630));
+
+    const emptied = await (program.account as any).orderBook.fetch(orderBook);
+    expect(emptied.slots[sellSlot]).to.equal(null);
+    expect(emptied.slots[buySlot]).to.equal(null);
+  });
+
+  async function initAddTogetherCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("add_together");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initAddTogetherCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log("Init add together computation definition transaction", sig);
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync("build/add_together.arcis");
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "add_together",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+  async function initRelayOfferCloneCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("relay_offer_clone");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initRelayOfferCloneCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log("Init relay offer clone computation definition transaction", sig);
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync("build/relay_offer_clone.arcis");
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "relay_offer_clone",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+
+  async function initConfidentialDepositNativeCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("confidential_deposit_native");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initConfidentialDepositNativeCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init confidential deposit native computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/confidential_deposit_native.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "confidential_deposit_native",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+
+  async function initConfidentialWithdrawNativeCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("confidential_withdraw_native");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initConfidentialWithdrawNativeCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init confidential withdraw native computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/confidential_withdraw_native.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "confidential_withdraw_native",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+
+  async function initConfidentialDepositSplCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("confidential_deposit_spl");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initConfidentialDepositSplCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init confidential deposit spl computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/confidential_deposit_spl.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "confidential_deposit_spl",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+
+  async function initConfidentialWithdrawSplCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("confidential_withdraw_spl");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initConfidentialWithdrawSplCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init confidential withdraw spl computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/confidential_withdraw_spl.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "confidential_withdraw_spl",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+
+  async function initInterchainOriginEvmDepositSellerSplCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("interchain_origin_evm_deposit_seller_spl");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initInterchainOriginEvmDepositSellerSplCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init interchain origin EVM deposit seller SPL computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      try {
+        const rawCircuit = fs.readFileSync(
+          "build/interchain_origin_evm_deposit_seller_spl.arcis"
+        );
+
+        await uploadCircuit(
+          provider as anchor.AnchorProvider,
+          "interchain_origin_evm_deposit_seller_spl",
+          program.programId,
+          rawCircuit,
+          true
+        );
+      } catch (error) {
+        console.log("Upload failed, perhaps already uploaded", error.message);
+      }
+
+      // Finalize after upload
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+      console.log("Finalize sig is ", finalizeTx.signature);
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+      console.log("Finalize sig is ", finalizeTx.signature);
+    }
+
+    // Always finalize for interchain
+    const finalizeTx = await buildFinalizeCompDefTx(
+      provider as anchor.AnchorProvider,
+      Buffer.from(offset).readUInt32LE(),
+      program.programId
+    );
+
+    const latestBlockhash = await provider.connection.getLatestBlockhash();
+    finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+    finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+    finalizeTx.sign(owner);
+
+    await provider.sendAndConfirm(finalizeTx);
+    console.log("Finalize sig is ", finalizeTx.signature);
+    return sig;
+  }
+
+  async function initFinalizeInterchainOriginEvmOfferCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("finalize_interchain_origin_evm_offer");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initFinalizeInterchainOriginEvmOfferCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
         commitment: "confirmed",
       });
-    console.log("Init relay offer clone computation definition transaction", sig);
+    console.log(
+      "Init finalize interchain origin EVM offer computation definition transaction",
+      sig
+    );
 
     if (uploadRawCircuit) {
-      const rawCircuit = fs.readFileSync("build/relay_offer_clone.arcis");
+      const rawCircuit = fs.readFileSync(
+        "build/finalize_interchain_origin_evm_offer.arcis"
+      );
 
       await uploadCircuit(
         provider as anchor.AnchorProvider,
-        "relay_offer_clone",
+        "finalize_interchain_origin_evm_offer",
         program.programId,
         rawCircuit,
         true
@@ -1238,7 +4159,7 @@ describe("ConfidentialCrossChainExchange", () => {
     return sig;
   }
 
-  async function initConfidentialDepositNativeCompDef(
+  async function initDepositSellerNativeCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
     uploadRawCircuit: boolean,
@@ -1247,7 +4168,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const baseSeedCompDefAcc = getArciumAccountBaseSeed(
       "ComputationDefinitionAccount"
     );
-    const offset = getCompDefAccOffset("confidential_deposit_native");
+    const offset = getCompDefAccOffset("deposit_seller_native");
 
     const compDefPDA = PublicKey.findProgramAddressSync(
       [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
@@ -1257,7 +4178,7 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Comp def pda is ", compDefPDA);
 
     const sig = await program.methods
-      .initConfidentialDepositNativeCompDef()
+      .initDepositSellerNativeCompDef(null)
       .accounts({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
@@ -1268,18 +4189,18 @@ describe("ConfidentialCrossChainExchange", () => {
         commitment: "confirmed",
       });
     console.log(
-      "Init confidential deposit native computation definition transaction",
+      "Init deposit seller native computation definition transaction",
       sig
     );
 
     if (uploadRawCircuit) {
       const rawCircuit = fs.readFileSync(
-        "build/confidential_deposit_native.arcis"
+        "build/deposit_seller_native.arcis"
       );
 
       await uploadCircuit(
         provider as anchor.AnchorProvider,
-        "confidential_deposit_native",
+        "deposit_seller_native",
         program.programId,
         rawCircuit,
         true
@@ -1302,7 +4223,7 @@ describe("ConfidentialCrossChainExchange", () => {
     return sig;
   }
 
-  async function initInterchainOriginEvmDepositSellerSplCompDef(
+  async function initDepositSellerSplCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
     uploadRawCircuit: boolean,
@@ -1311,7 +4232,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const baseSeedCompDefAcc = getArciumAccountBaseSeed(
       "ComputationDefinitionAccount"
     );
-    const offset = getCompDefAccOffset("interchain_origin_evm_deposit_seller_spl");
+    const offset = getCompDefAccOffset("deposit_seller_spl");
 
     const compDefPDA = PublicKey.findProgramAddressSync(
       [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
@@ -1321,7 +4242,7 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Comp def pda is ", compDefPDA);
 
     const sig = await program.methods
-      .initInterchainOriginEvmDepositSellerSplCompDef()
+      .initDepositSellerSplCompDef(null)
       .accounts({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
@@ -1332,28 +4253,23 @@ describe("ConfidentialCrossChainExchange", () => {
         commitment: "confirmed",
       });
     console.log(
-      "Init interchain origin EVM deposit seller SPL computation definition transaction",
+      "Init deposit seller SPL computation definition transaction",
       sig
     );
 
     if (uploadRawCircuit) {
-      try {
-        const rawCircuit = fs.readFileSync(
-          "build/interchain_origin_evm_deposit_seller_spl.arcis"
-        );
-
-        await uploadCircuit(
-          provider as anchor.AnchorProvider,
-          "interchain_origin_evm_deposit_seller_spl",
-          program.programId,
-          rawCircuit,
-          true
-        );
-      } catch (error) {
-        console.log("Upload failed, perhaps already uploaded", error.message);
-      }
+      const rawCircuit = fs.readFileSync(
+        "build/deposit_seller_spl.arcis"
+      );
 
-      // Finalize after upload
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "deposit_seller_spl",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
       const finalizeTx = await buildFinalizeCompDefTx(
         provider as anchor.AnchorProvider,
         Buffer.from(offset).readUInt32LE(),
@@ -1367,7 +4283,56 @@ describe("ConfidentialCrossChainExchange", () => {
       finalizeTx.sign(owner);
 
       await provider.sendAndConfirm(finalizeTx);
-      console.log("Finalize sig is ", finalizeTx.signature);
+    }
+    return sig;
+  }
+
+  async function initFinalizeIntrachainOfferCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("finalize_intrachain_offer");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initFinalizeIntrachainOfferCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init finalize intrachain offer computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/finalize_intrachain_offer.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "finalize_intrachain_offer",
+        program.programId,
+        rawCircuit,
+        true
+      );
     } else if (!offchainSource) {
       const finalizeTx = await buildFinalizeCompDefTx(
         provider as anchor.AnchorProvider,
@@ -1382,28 +4347,75 @@ describe("ConfidentialCrossChainExchange", () => {
       finalizeTx.sign(owner);
 
       await provider.sendAndConfirm(finalizeTx);
-      console.log("Finalize sig is ", finalizeTx.signature);
     }
+    return sig;
+  }
 
-    // Always finalize for interchain
-    const finalizeTx = await buildFinalizeCompDefTx(
-      provider as anchor.AnchorProvider,
-      Buffer.from(offset).readUInt32LE(),
-      program.programId
+  async function initCreateConfidentialOfferCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
     );
+    const offset = getCompDefAccOffset("create_confidential_offer");
 
-    const latestBlockhash = await provider.connection.getLatestBlockhash();
-    finalizeTx.recentBlockhash = latestBlockhash.blockhash;
-    finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
 
-    finalizeTx.sign(owner);
+    console.log("Comp def pda is ", compDefPDA);
 
-    await provider.sendAndConfirm(finalizeTx);
-    console.log("Finalize sig is ", finalizeTx.signature);
+    const sig = await program.methods
+      .initCreateConfidentialOfferCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init create confidential offer computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/create_confidential_offer.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "create_confidential_offer",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
     return sig;
   }
 
-  async function initFinalizeInterchainOriginEvmOfferCompDef(
+  async function initMatchConfidentialOfferCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
     uploadRawCircuit: boolean,
@@ -1412,7 +4424,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const baseSeedCompDefAcc = getArciumAccountBaseSeed(
       "ComputationDefinitionAccount"
     );
-    const offset = getCompDefAccOffset("finalize_interchain_origin_evm_offer");
+    const offset = getCompDefAccOffset("match_confidential_offer");
 
     const compDefPDA = PublicKey.findProgramAddressSync(
       [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
@@ -1422,7 +4434,7 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Comp def pda is ", compDefPDA);
 
     const sig = await program.methods
-      .initFinalizeInterchainOriginEvmOfferCompDef()
+      .initMatchConfidentialOfferCompDef(null)
       .accounts({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
@@ -1433,18 +4445,18 @@ describe("ConfidentialCrossChainExchange", () => {
         commitment: "confirmed",
       });
     console.log(
-      "Init finalize interchain origin EVM offer computation definition transaction",
+      "Init match confidential offer computation definition transaction",
       sig
     );
 
     if (uploadRawCircuit) {
       const rawCircuit = fs.readFileSync(
-        "build/finalize_interchain_origin_evm_offer.arcis"
+        "build/match_confidential_offer.arcis"
       );
 
       await uploadCircuit(
         provider as anchor.AnchorProvider,
-        "finalize_interchain_origin_evm_offer",
+        "match_confidential_offer",
         program.programId,
         rawCircuit,
         true
@@ -1467,7 +4479,7 @@ describe("ConfidentialCrossChainExchange", () => {
     return sig;
   }
 
-  async function initDepositSellerNativeCompDef(
+  async function initPlaceSealedBidCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
     uploadRawCircuit: boolean,
@@ -1476,7 +4488,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const baseSeedCompDefAcc = getArciumAccountBaseSeed(
       "ComputationDefinitionAccount"
     );
-    const offset = getCompDefAccOffset("deposit_seller_native");
+    const offset = getCompDefAccOffset("place_sealed_bid");
 
     const compDefPDA = PublicKey.findProgramAddressSync(
       [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
@@ -1486,7 +4498,7 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Comp def pda is ", compDefPDA);
 
     const sig = await program.methods
-      .initDepositSellerNativeCompDef()
+      .initPlaceSealedBidCompDef(null)
       .accounts({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
@@ -1497,18 +4509,18 @@ describe("ConfidentialCrossChainExchange", () => {
         commitment: "confirmed",
       });
     console.log(
-      "Init deposit seller native computation definition transaction",
+      "Init place sealed bid computation definition transaction",
       sig
     );
 
     if (uploadRawCircuit) {
       const rawCircuit = fs.readFileSync(
-        "build/deposit_seller_native.arcis"
+        "build/place_sealed_bid.arcis"
       );
 
       await uploadCircuit(
         provider as anchor.AnchorProvider,
-        "deposit_seller_native",
+        "place_sealed_bid",
         program.programId,
         rawCircuit,
         true
@@ -1530,8 +4542,7 @@ describe("ConfidentialCrossChainExchange", () => {
     }
     return sig;
   }
-
-  async function initDepositSellerSplCompDef(
+  async function initResolveSealedAuctionCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
     uploadRawCircuit: boolean,
@@ -1540,7 +4551,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const baseSeedCompDefAcc = getArciumAccountBaseSeed(
       "ComputationDefinitionAccount"
     );
-    const offset = getCompDefAccOffset("deposit_seller_spl");
+    const offset = getCompDefAccOffset("resolve_sealed_auction");
 
     const compDefPDA = PublicKey.findProgramAddressSync(
       [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
@@ -1550,7 +4561,7 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Comp def pda is ", compDefPDA);
 
     const sig = await program.methods
-      .initDepositSellerSplCompDef()
+      .initResolveSealedAuctionCompDef(null)
       .accounts({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
@@ -1561,18 +4572,18 @@ describe("ConfidentialCrossChainExchange", () => {
         commitment: "confirmed",
       });
     console.log(
-      "Init deposit seller SPL computation definition transaction",
+      "Init resolve sealed auction computation definition transaction",
       sig
     );
 
     if (uploadRawCircuit) {
       const rawCircuit = fs.readFileSync(
-        "build/deposit_seller_spl.arcis"
+        "build/resolve_sealed_auction.arcis"
       );
 
       await uploadCircuit(
         provider as anchor.AnchorProvider,
-        "deposit_seller_spl",
+        "resolve_sealed_auction",
         program.programId,
         rawCircuit,
         true
@@ -1595,7 +4606,7 @@ describe("ConfidentialCrossChainExchange", () => {
     return sig;
   }
 
-  async function initFinalizeIntrachainOfferCompDef(
+  async function initAddOrderCompDef(
     program: Program<ConfidentialCrossChainExchange>,
     owner: anchor.web3.Keypair,
     uploadRawCircuit: boolean,
@@ -1604,7 +4615,7 @@ describe("ConfidentialCrossChainExchange", () => {
     const baseSeedCompDefAcc = getArciumAccountBaseSeed(
       "ComputationDefinitionAccount"
     );
-    const offset = getCompDefAccOffset("finalize_intrachain_offer");
+    const offset = getCompDefAccOffset("add_order");
 
     const compDefPDA = PublicKey.findProgramAddressSync(
       [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
@@ -1614,7 +4625,7 @@ describe("ConfidentialCrossChainExchange", () => {
     console.log("Comp def pda is ", compDefPDA);
 
     const sig = await program.methods
-      .initFinalizeIntrachainOfferCompDef()
+      .initAddOrderCompDef(null)
       .accounts({
         compDefAccount: compDefPDA,
         payer: owner.publicKey,
@@ -1625,18 +4636,81 @@ describe("ConfidentialCrossChainExchange", () => {
         commitment: "confirmed",
       });
     console.log(
-      "Init finalize intrachain offer computation definition transaction",
+      "Init add order computation definition transaction",
       sig
     );
 
     if (uploadRawCircuit) {
       const rawCircuit = fs.readFileSync(
-        "build/finalize_intrachain_offer.arcis"
+        "build/add_order.arcis"
       );
 
       await uploadCircuit(
         provider as anchor.AnchorProvider,
-        "finalize_intrachain_offer",
+        "add_order",
+        program.programId,
+        rawCircuit,
+        true
+      );
+    } else if (!offchainSource) {
+      const finalizeTx = await buildFinalizeCompDefTx(
+        provider as anchor.AnchorProvider,
+        Buffer.from(offset).readUInt32LE(),
+        program.programId
+      );
+
+      const latestBlockhash = await provider.connection.getLatestBlockhash();
+      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+
+      finalizeTx.sign(owner);
+
+      await provider.sendAndConfirm(finalizeTx);
+    }
+    return sig;
+  }
+  async function initMatchOrdersCompDef(
+    program: Program<ConfidentialCrossChainExchange>,
+    owner: anchor.web3.Keypair,
+    uploadRawCircuit: boolean,
+    offchainSource: boolean
+  ): Promise<string> {
+    const baseSeedCompDefAcc = getArciumAccountBaseSeed(
+      "ComputationDefinitionAccount"
+    );
+    const offset = getCompDefAccOffset("match_orders");
+
+    const compDefPDA = PublicKey.findProgramAddressSync(
+      [baseSeedCompDefAcc, program.programId.toBuffer(), offset],
+      getArciumProgAddress()
+    )[0];
+
+    console.log("Comp def pda is ", compDefPDA);
+
+    const sig = await program.methods
+      .initMatchOrdersCompDef(null)
+      .accounts({
+        compDefAccount: compDefPDA,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc({
+        commitment: "confirmed",
+      });
+    console.log(
+      "Init match orders computation definition transaction",
+      sig
+    );
+
+    if (uploadRawCircuit) {
+      const rawCircuit = fs.readFileSync(
+        "build/match_orders.arcis"
+      );
+
+      await uploadCircuit(
+        provider as anchor.AnchorProvider,
+        "match_orders",
         program.programId,
         rawCircuit,
         true